@@ -1,6 +1,15 @@
-use std::time::Duration;
+use std::{
+	env,
+	process,
+	time::Duration
+};
+
 use log::info;
-use common::clock::Clock;
+
+use common::{
+	clock::Clock,
+	msg::chat
+};
 
 use client::{
 	Input,
@@ -19,8 +28,50 @@ fn main() {
 	// configurar um clock de fps
 	let mut clock = Clock::new();
 
+	// uso: chat-cli [--query] [endereço[:porta]] [nome de usuário]
+	//
+	// a senha da conta, quando existir, é lida da variável de ambiente `LESTINIA_PASSWORD`
+	let mut args = env::args().skip(1).peekable();
+
+	// no modo de consulta apenas o status do servidor é mostrado, sem entrar como jogador
+	let query = args.peek().map(|arg| arg == "--query").unwrap_or(false);
+
+	if query {
+		args.next();
+	}
+
+	let addr = args.next().unwrap_or_else(|| String::from("127.0.0.1"));
+	let username = args.next().unwrap_or_else(|| String::from("chat-cli"));
+
+	let addr = client::parse_server_addr(&addr)
+		.expect("endereço de servidor inválido");
+
+	if query {
+		match Client::query(addr) {
+			Ok(info) => {
+				println!("nome: {}", info.name);
+				println!("motd: {}", info.motd);
+				println!("versão: {}", info.version);
+				println!("jogadores: {}/{}", info.players, info.max_players);
+				println!("tick rate: {}", info.tick_rate);
+			},
+
+			Err(err) => {
+				println!("erro: {:?}", err);
+
+				process::exit(1);
+			}
+		}
+
+		return;
+	}
+
+	info!("conectando em {} como '{}'...", addr, username);
+
 	// criar client
-	let mut client = Client::new(([127, 0, 0, 1], 59003))
+	let password = env::var("LESTINIA_PASSWORD").ok();
+
+	let mut client = Client::new(addr, username, password)
 		.expect("falha ao criar uma instância client");
 
 	loop {
@@ -36,7 +87,9 @@ fn main() {
 
 		for event in events {
 			match event {
-				Event::Chat(msg) => println!("[chat] {}", msg)
+				Event::Chat { channel, sender, msg } => {
+					println!("{}", chat::format_chat_msg(&channel, sender.as_ref().map(|s| s.as_str()), &msg))
+				}
 			}
 		}
 
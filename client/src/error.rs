@@ -1,10 +1,28 @@
-use common::net::PostError;
+use common::{
+	net::PostError,
+	msg::DisconnectReason
+};
 
 #[derive(Debug)]
 pub enum Error {
 	Network(PostError),
 	ServerTimeout,
 	ServerShutdown,
+
+	VersionMismatch {
+		server_version: u32,
+		client_version: u32
+	},
+
+	Kicked(String),
+	Banned(String),
+	NotWhitelisted,
+	ServerFull,
+	InvalidUsername,
+	UsernameTaken,
+	WrongPassword,
+	ProtocolError,
+
 	Other(String)
 }
 
@@ -17,3 +35,30 @@ impl From<PostError> for Error {
 		}
 	}
 }
+
+impl From<DisconnectReason> for Error {
+	fn from(reason: DisconnectReason) -> Self {
+		match reason {
+			DisconnectReason::Timeout => Error::ServerTimeout,
+
+			DisconnectReason::Quit |
+			DisconnectReason::ConnectionLost |
+			DisconnectReason::ServerShutdown => Error::ServerShutdown,
+
+			DisconnectReason::Kicked(reason) => Error::Kicked(reason),
+			DisconnectReason::Banned(reason) => Error::Banned(reason),
+			DisconnectReason::NotWhitelisted => Error::NotWhitelisted,
+			DisconnectReason::ServerFull => Error::ServerFull,
+			DisconnectReason::InvalidUsername => Error::InvalidUsername,
+			DisconnectReason::UsernameTaken => Error::UsernameTaken,
+			DisconnectReason::WrongPassword => Error::WrongPassword,
+
+			DisconnectReason::VersionMismatch { server_version, client_version } => Error::VersionMismatch {
+				server_version,
+				client_version
+			},
+
+			DisconnectReason::ProtocolError => Error::ProtocolError
+		}
+	}
+}
@@ -1,4 +1,4 @@
-#![feature(label_break_value)]
+#![feature(label_break_value, euclidean_division)]
 
 pub mod error;
 pub mod input;
@@ -12,8 +12,25 @@ pub use crate::{
 };
 
 use std::{
-    time::Duration,
-    net::SocketAddr
+    sync::{
+        mpsc,
+        Arc
+    },
+
+    collections::{
+        HashSet,
+        VecDeque
+    },
+
+    net::{
+        SocketAddr,
+        ToSocketAddrs
+    },
+
+    time::{
+        Duration,
+        Instant
+    }
 };
 
 use vek::*;
@@ -25,12 +42,25 @@ use common::{
     comp,
     
     state::State,
-    terrain::TerrainChunk,
-    net::PostBox,
+    terrain::{
+        TerrainChunk,
+        TerrainMap
+    },
+    net::{
+        PostBox,
+        ClientTls
+    },
+    weather::Weather,
     
     msg::{
         ClientMsg,
-        ServerMsg
+        ServerMsg,
+        ServerInfo,
+        DisconnectReason,
+        ChatChannel,
+
+        PROTOCOL_VERSION,
+        DEFAULT_PORT
     }
 };
 
@@ -38,8 +68,37 @@ use world::World;
 
 const SERVER_TIMEOUT: f64 = 5.0; // segundos
 
+// tempo máximo de espera por uma resposta do servidor durante o handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// distância de visão padrão, em chunks ao redor do jogador
+pub const DEFAULT_VIEW_DISTANCE: u32 = 6;
+
+// chunks mantidos acima e abaixo do chunk do jogador
+const VERTICAL_VIEW_DISTANCE: i32 = 1;
+
+// máximo de chunks sendo gerados ao mesmo tempo, para que os chunks mais próximos não esperem atrás de uma fila longa
+const MAX_PENDING_CHUNKS: usize = 8;
+
+/// converte um endereço no formato `host[:porta]` em um `SocketAddr`, utilizando a porta padrão caso nenhuma seja fornecida
+pub fn parse_server_addr(addr: &str) -> Option<SocketAddr> {
+    let addr = addr.trim();
+
+    addr.to_socket_addrs()
+        .or_else(|_| (addr, DEFAULT_PORT).to_socket_addrs())
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+}
+
 pub enum Event {
-    Chat(String)
+    Chat {
+        channel: ChatChannel,
+
+        // `None` para mensagens do próprio servidor
+        sender: Option<String>,
+
+        msg: String
+    }
 }
 
 pub struct Client {
@@ -48,41 +107,160 @@ pub struct Client {
     last_ping: f64,
     postbox: PostBox<ClientMsg, ServerMsg>,
 
+    // mensagens recebidas durante o handshake que ainda não foram tratadas
+    backlog: VecDeque<ServerMsg>,
+
     tick: u64,
     state: State,
-    player: Option<Uid>,
+    player: Option<EcsEntity>,
 
     // teste
-    world: World,
+    world: Arc<World>,
     
-    pub chunk: Option<TerrainChunk>
+    pub chunk: Option<TerrainChunk>,
+
+    // chunks ao redor do jogador, em chunks
+    view_distance: u32,
+
+    // chunks sendo gerados pelo threadpool e o canal pelo qual eles retornam
+    pending_chunks: HashSet<Vec3<i32>>,
+    chunk_send: mpsc::Sender<(Vec3<i32>, TerrainChunk)>,
+    chunk_recv: mpsc::Receiver<(Vec3<i32>, TerrainChunk)>
 }
 
 impl Client {
-    /// cria um novo `client`
+    /// cria um novo `client` conectado como jogador ao servidor no endereço fornecido
+    ///
+    /// bloqueia até que o servidor aceite a conexão, recuse a versão do protocolo ou o tempo limite se esgote.
+    /// com uma senha, o nome de usuário é registrado no primeiro login e protegido por ela nos seguintes
+    #[allow(dead_code)]
+    pub fn new<A: Into<SocketAddr>>(addr: A, username: String, password: Option<String>) -> Result<Self, Error> {
+        Self::connect(PostBox::to_server(addr)?, username, password)
+    }
+
+    /// igual a `new`, mas a conexão é criptografada e o certificado do servidor é verificado com `tls`
     #[allow(dead_code)]
-    pub fn new<A: Into<SocketAddr>>(addr: A) -> Result<Self, Error> {
+    pub fn new_tls<A: Into<SocketAddr>>(
+        addr: A,
+        username: String,
+        password: Option<String>,
+        tls: &ClientTls
+    ) -> Result<Self, Error> {
+        Self::connect(PostBox::to_server_tls(addr, tls)?, username, password)
+    }
+
+    fn connect(
+        mut postbox: PostBox<ClientMsg, ServerMsg>,
+        username: String,
+        password: Option<String>
+    ) -> Result<Self, Error> {
         let state = State::new();
-        let mut postbox = PostBox::to_server(addr)?;
-        
-        Ok(Self {
+
+        postbox.send(ClientMsg::Connect {
+            version: PROTOCOL_VERSION,
+            username,
+            password: password.filter(|password| !password.is_empty()),
+
+            compression: true
+        });
+
+        let mut backlog = VecDeque::new();
+
+        // esperar o servidor atribuir uma entidade ao jogador. a compressão, a hora do dia e o clima enviados junto
+        // com ela ficam no backlog e são aplicados no primeiro tick
+        let player_uid = Self::wait_for(&mut postbox, &mut backlog, |msg| match msg {
+            ServerMsg::SetPlayerEntity(uid) => Some(Ok(*uid)),
+
+            ServerMsg::Disconnect { reason } => Some(Err(reason.clone().into())),
+
+            _ => None
+        })?;
+
+        let (chunk_send, chunk_recv) = mpsc::channel();
+
+        let mut client = Self {
             thread_pool: threadpool::Builder::new()
                 .thread_name("lestinia-worker".into())
                 .build(),
 
             last_ping: state.get_time(),
             postbox,
+            backlog,
 
             tick: 0,
             state,
             player: None,
 
             // teste
-            world: World::new(),
-            chunk: None
+            world: Arc::new(World::new()),
+            chunk: None,
+
+            view_distance: DEFAULT_VIEW_DISTANCE,
+
+            pending_chunks: HashSet::new(),
+            chunk_send,
+            chunk_recv
+        };
+
+        let ecs_entity = client.state.build_uid_entity_with_uid(player_uid).build();
+
+        client.player = Some(ecs_entity);
+
+        Ok(client)
+    }
+
+    /// consulta o status de um servidor sem entrar como jogador
+    #[allow(dead_code)]
+    pub fn query<A: Into<SocketAddr>>(addr: A) -> Result<ServerInfo, Error> {
+        let mut postbox = PostBox::to_server(addr)?;
+
+        postbox.send(ClientMsg::Query);
+
+        Self::wait_for(&mut postbox, &mut VecDeque::new(), |msg| match msg {
+            ServerMsg::QueryResponse(info) => Some(Ok(info.clone())),
+            ServerMsg::Disconnect { reason } => Some(Err(reason.clone().into())),
+
+            _ => None
         })
     }
 
+    /// espera até que `f` aceite alguma mensagem do servidor ou o tempo limite do handshake se esgote
+    ///
+    /// as mensagens que `f` não aceitar, inclusive as recebidas depois da aceita, são guardadas em `backlog`
+    fn wait_for<T, F: FnMut(&ServerMsg) -> Option<Result<T, Error>>>(
+        postbox: &mut PostBox<ClientMsg, ServerMsg>,
+        backlog: &mut VecDeque<ServerMsg>,
+        mut f: F
+    ) -> Result<T, Error> {
+        let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+
+        loop {
+            let now = Instant::now();
+
+            if now >= deadline {
+                return Err(Error::ServerTimeout);
+            }
+
+            let mut msgs = postbox.wait_messages(deadline - now);
+
+            while let Some(msg) = msgs.next() {
+                match f(&msg) {
+                    Some(result) => {
+                        backlog.extend(msgs);
+
+                        return result;
+                    },
+
+                    None => backlog.push_back(msg)
+                }
+            }
+
+            if let Some(err) = postbox.error() {
+                return Err(err.into());
+            }
+        }
+    }
+
     /// obtém a referência do threadpool do worker do client.
     ///
     /// esse pool deve ser utilizado para qualquer opearação expansiva que roda fora da thread principal
@@ -99,11 +277,18 @@ impl Client {
         self
     }
 
-    /// todo: obtém o rid disso
-    pub fn load_chunk(&mut self, pos: Vec3<i32>) {
-        self.state.terrain_mut().insert(pos, self.world.generate_chunk(pos));
+    /// obtém a distância de visão atual, em chunks
+    #[allow(dead_code)]
+    pub fn view_distance(&self) -> u32 {
+        self.view_distance
+    }
 
-        self.state.changes_mut().new_chunks.push(pos);
+    /// altera a distância de visão, em chunks ao redor do jogador
+    ///
+    /// chunks fora da nova distância são descartados e os que faltarem são carregados nos próximos ticks
+    #[allow(dead_code)]
+    pub fn set_view_distance(&mut self, view_distance: u32) {
+        self.view_distance = view_distance;
     }
 
     /// obtém a referência para o estado do jogo do cliente
@@ -148,16 +333,65 @@ impl Client {
         self.player
     }
 
+    /// obtém o clima na posição do jogador, de acordo com a última atualização do servidor
+    #[allow(dead_code)]
+    pub fn current_weather(&self) -> Weather {
+        self.player
+            .and_then(|ecs_entity| self.state.read_storage::<comp::phys::Pos>().get(ecs_entity).cloned())
+            .map(|pos| self.state.weather().at(pos.0))
+            .unwrap_or_default()
+    }
+
     /// obtém o número de tick atual
     #[allow(dead_code)]
     pub fn get_tick(&self) -> u64 {
         self.tick
     }
 
-    /// envia uma mensagem do chat para o servidor
+    /// envia uma mensagem do chat para o servidor pelo canal fornecido
+    #[allow(dead_code)]
+    pub fn send_chat(&mut self, channel: ChatChannel, msg: String) {
+        self.postbox.send(ClientMsg::Chat {
+            channel,
+            msg
+        })
+    }
+
+    /// envia uma linha digitada pelo jogador, escolhendo o canal pelo prefixo
+    ///
+    /// `/l <mensagem>` envia para o chat local, `/w <jogador> <mensagem>` sussurra para um jogador
+    /// e `/g <mensagem>` envia para o chat global, assim como qualquer outra linha. as demais linhas
+    /// começando com `/` são interpretadas pelo servidor como comandos
+    #[allow(dead_code)]
+    pub fn send_chat_input(&mut self, input: &str) {
+        let mut words = input.trim().splitn(2, ' ');
+
+        let (channel, msg) = match (words.next(), words.next()) {
+            (Some("/g"), Some(msg)) => (ChatChannel::Global, msg),
+            (Some("/l"), Some(msg)) => (ChatChannel::Local, msg),
+
+            (Some("/w"), Some(rest)) => {
+                let mut rest = rest.trim().splitn(2, ' ');
+
+                match (rest.next(), rest.next()) {
+                    (Some(target), Some(msg)) => (ChatChannel::Whisper(target.to_string()), msg),
+
+                    _ => return
+                }
+            },
+
+            _ => (ChatChannel::Global, input.trim())
+        };
+
+        if !msg.trim().is_empty() {
+            self.send_chat(channel, msg.trim().to_string());
+        }
+    }
+
+    /// envia o personagem escolhido na criação de personagem para o servidor
     #[allow(dead_code)]
-    pub fn send_chat(&mut self, msg: String) {
-        self.postbox.send(ClientMsg::Chat(msg))
+    pub fn request_character(&mut self, character: comp::Character) {
+        self.postbox.send(ClientMsg::Character(character))
     }
 
     /// executar tick de cliente único, ajudar input e atualizar estado do jogo pela duração recebida
@@ -192,6 +426,9 @@ impl Client {
         // tick para o localstate do client (passo 3)
         self.state.tick(dt);
 
+        // carregar e descartar chunks ao redor da nova posição do jogador (passo 4)
+        self.maintain_terrain();
+
         // atualizar o servidor por conta dos atributos físicos do jogador
         if let Some(ecs_entity) = self.player {
             match (
@@ -222,24 +459,92 @@ impl Client {
         self.state.cleanup();
     }
 
+    /// mantém carregados apenas os chunks dentro da distância de visão ao redor do jogador e os vizinhos deles
+    fn maintain_terrain(&mut self) {
+        let player_pos = self.player
+            .and_then(|ecs_entity| self.state.read_storage::<comp::phys::Pos>().get(ecs_entity).cloned())
+            .map(|pos| pos.0)
+            .unwrap_or(Vec3::zero());
+
+        let center = player_pos.map2(TerrainMap::chunk_size(), |e, sz| (e.floor() as i32).div_euclid(sz as i32));
+
+        let view_distance = self.view_distance as i32;
+
+        // receber os chunks gerados desde o último tick
+        while let Ok((key, chunk)) = self.chunk_recv.try_recv() {
+            self.pending_chunks.remove(&key);
+
+            // o jogador pode ter se afastado enquanto o chunk era gerado
+            if !chunk_needed(key, center, view_distance, VERTICAL_VIEW_DISTANCE) {
+                continue;
+            }
+
+            self.state.insert_chunk(key, chunk);
+        }
+
+        // descartar chunks distantes, com uma margem para que andar na borda não carregue e descarte o mesmo chunk
+        let far_chunks = self.state
+            .terrain()
+            .keys()
+            .filter(|key| !chunk_needed(*key, center, view_distance + 1, VERTICAL_VIEW_DISTANCE + 1))
+            .collect::<Vec<_>>();
+
+        for key in far_chunks {
+            self.state.remove_chunk(key);
+        }
+
+        if self.pending_chunks.len() >= MAX_PENDING_CHUNKS {
+            return;
+        }
+
+        // os chunks mais próximos do jogador são pedidos primeiro
+        let missing_chunks = {
+            let terrain = self.state.terrain();
+
+            needed_chunks(center, view_distance, VERTICAL_VIEW_DISTANCE)
+                .into_iter()
+                .filter(|key| terrain.get_key(*key).is_none() && !self.pending_chunks.contains(key))
+                .collect::<Vec<_>>()
+        };
+
+        for key in missing_chunks.into_iter().take(MAX_PENDING_CHUNKS - self.pending_chunks.len()) {
+            let world = self.world.clone();
+            let send = self.chunk_send.clone();
+
+            self.thread_pool.execute(move || {
+                // o client pode ter sido fechado enquanto o chunk era gerado
+                let _ = send.send((key, world.generate_chunk(key)));
+            });
+
+            self.pending_chunks.insert(key);
+        }
+    }
+
     /// auxiliar novas mensagens do servidor
     fn handle_new_messages(&mut self) -> Result<Vec<Event>, Error> {
         let mut frontend_events = Vec::new();
 
-        // passo 1
-        let new_msgs = self.postbox.new_messages();
+        // passo 1, começando pelas mensagens que chegaram durante o handshake
+        let new_msgs = self.backlog
+            .drain(..)
+            .chain(self.postbox.new_messages())
+            .collect::<Vec<_>>();
 
         if new_msgs.len() > 0 {
             self.last_ping = self.state.get_time();
 
             for msg in new_msgs {
                 match msg {            
-                    ServerMsg::Shutdown => return Err(Error::ServerShutdown),
+                    ServerMsg::Disconnect { reason } => return Err(reason.into()),
 
                     ServerMsg::Ping => self.postbox.send(ClientMsg::Pong),
                     ServerMsg::Pong => {},
 
-                    ServerMsg::Chat(msg) => frontend_events.push(Event::Chat(msg)),
+                    ServerMsg::Chat { channel, sender, msg } => frontend_events.push(Event::Chat {
+                        channel,
+                        sender,
+                        msg
+                    }),
 
                     ServerMsg::SetPlayerEntity(uid) => {
                         let ecs_entity = self.state
@@ -249,7 +554,7 @@ impl Client {
                         self.player = Some(ecs_entity);
                     },
 
-                    ServerMsg::EntityPhysics { uid, pos, vel, dir } => {
+                    ServerMsg::EntityPhysics { uid, pos, vel, dir, .. } => {
                         let ecs_entity = self.state
                             .get_entity(uid)
                             .unwrap_or_else(|| self.state.build_uid_entity_with_uid(uid).build());
@@ -261,12 +566,28 @@ impl Client {
 
                     ServerMsg::EntityDeleted(uid) => {
                         self.state.delete_entity(uid);
-                    }
+                    },
+
+                    ServerMsg::TimeOfDay(time_of_day) => self.state.set_time_of_day(time_of_day),
+
+                    ServerMsg::Weather { region, weather } => {
+                        self.state.weather_mut().set(region, weather);
+                    },
+
+                    ServerMsg::Compression { enabled } => self.postbox.set_compression(enabled),
+
+                    // respostas de handshake não são esperadas depois do client estar conectado
+                    ServerMsg::QueryResponse(_) => {}
                 }
             }
-        } else if let Some(err) = self.postbox.status() {
+        } else if let Some(err) = self.postbox.error() {
             return Err(err.into());
         } else if self.state.get_time() - self.last_ping > SERVER_TIMEOUT {
+            // avisar o servidor, caso ele ainda esteja ouvindo
+            self.postbox.send(ClientMsg::Disconnect {
+                reason: DisconnectReason::Timeout
+            });
+
             return Err(Error::ServerTimeout);
         } else if self.state.get_time() - self.last_ping > SERVER_TIMEOUT * 0.5 {
 			// tentar pingar o servidor caso o timeout esteja próximo
@@ -278,8 +599,94 @@ impl Client {
     }
 }
 
+/// verifica se o chunk em `key` está dentro da distância horizontal (circular) e vertical do chunk `center`
+fn chunk_in_range(key: Vec3<i32>, center: Vec3<i32>, horizontal: i32, vertical: i32) -> bool {
+    let diff = key - center;
+
+    diff.x * diff.x + diff.y * diff.y <= horizontal * horizontal && diff.z.abs() <= vertical
+}
+
+/// verifica se o chunk em `key` está dentro da distância ou é vizinho de um chunk dentro dela
+///
+/// um chunk só pode ser meshado com todos os 26 vizinhos carregados, então o client mantém um anel extra de chunks
+/// em volta da distância de visão
+fn chunk_needed(key: Vec3<i32>, center: Vec3<i32>, horizontal: i32, vertical: i32) -> bool {
+    // o chunk da vizinhança de `key` mais próximo de `center`
+    let nearest = key - (key - center).map(|e| e.signum());
+
+    chunk_in_range(nearest, center, horizontal, vertical)
+}
+
+/// todos os chunks necessários ao redor de `center`, do mais próximo para o mais distante
+fn needed_chunks(center: Vec3<i32>, horizontal: i32, vertical: i32) -> Vec<Vec3<i32>> {
+    let mut keys = Vec::new();
+
+    for x in -(horizontal + 1)..=horizontal + 1 {
+        for y in -(horizontal + 1)..=horizontal + 1 {
+            for z in -(vertical + 1)..=vertical + 1 {
+                let key = center + Vec3::new(x, y, z);
+
+                if chunk_needed(key, center, horizontal, vertical) {
+                    keys.push(key);
+                }
+            }
+        }
+    }
+
+    keys.sort_by_key(|key| (*key - center).map(|e| e * e).sum());
+
+    keys
+}
+
 impl Drop for Client {
     fn drop(&mut self) {
-        self.postbox.send(ClientMsg::Disconnect);
+        self.postbox.send(ClientMsg::Disconnect {
+            reason: DisconnectReason::Quit
+        });
     }
 }
+
+// testes
+
+#[test]
+fn chunk_ranges() {
+    let center = Vec3::new(5, -3, 2);
+
+    assert!(chunk_in_range(center + Vec3::new(3, 4, 1), center, 5, 1));
+    assert!(!chunk_in_range(center + Vec3::new(4, 4, 0), center, 5, 1));
+    assert!(!chunk_in_range(center + Vec3::new(0, 0, 2), center, 5, 1));
+
+    // o anel extra só inclui os vizinhos de chunks dentro da distância
+    assert!(chunk_needed(center + Vec3::new(0, 0, 2), center, 5, 1));
+    assert!(chunk_needed(center + Vec3::new(6, 0, -2), center, 5, 1));
+    assert!(!chunk_needed(center + Vec3::new(0, 0, 3), center, 5, 1));
+    assert!(!chunk_needed(center + Vec3::new(7, 0, 0), center, 5, 1));
+}
+
+#[test]
+fn needed_chunks_cover_neighbourhoods() {
+    let center = Vec3::new(-2, 7, 0);
+
+    let keys = needed_chunks(center, 4, 1);
+    let set = keys.iter().cloned().collect::<std::collections::HashSet<_>>();
+
+    assert_eq!(keys.len(), set.len());
+
+    // todos os vizinhos de um chunk dentro da distância são carregados, então ele pode ser meshado
+    for key in keys.iter().filter(|key| chunk_in_range(**key, center, 4, 1)) {
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    assert!(set.contains(&(*key + Vec3::new(x, y, z))), "{:?}", key);
+                }
+            }
+        }
+    }
+
+    // os chunks mais próximos são pedidos primeiro
+    assert_eq!(keys[0], center);
+
+    let dist_sq = |key: &Vec3<i32>| (*key - center).map(|e| e * e).sum();
+
+    assert!(keys.windows(2).all(|pair| dist_sq(&pair[0]) <= dist_sq(&pair[1])));
+}
@@ -5,10 +5,8 @@ use specs::{
     VecStorage
 };
 
-use vek::*;
-
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
-enum Race {
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Race {
     Danari,
     Dwarf,
     Elf,
@@ -17,23 +15,96 @@ enum Race {
     Undead
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
-pub struct Gender {
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Gender {
     Female,
     Male,
     Unspecified
 }
 
-#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Head {
+    DefaultHead,
+    ElfHead
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Chest {
+    DefaultChest,
+    ElfChest
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Belt {
+    DefaultBelt,
+    ElfBelt
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Pants {
+    DefaultPants,
+    ElfPants
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hand {
+    DefaultHand,
+    ElfHand
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Foot {
+    DefaultFoot,
+    ElfFoot
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Weapon {
+    Sword,
+    ElfSword
+}
+
+// listas de todas as variantes, utilizadas para percorrer as opções na criação de personagem
+pub const ALL_RACES: [Race; 6] = [Race::Danari, Race::Dwarf, Race::Elf, Race::Human, Race::Orc, Race::Undead];
+pub const ALL_GENDERS: [Gender; 3] = [Gender::Female, Gender::Male, Gender::Unspecified];
+pub const ALL_HEADS: [Head; 2] = [Head::DefaultHead, Head::ElfHead];
+pub const ALL_CHESTS: [Chest; 2] = [Chest::DefaultChest, Chest::ElfChest];
+pub const ALL_BELTS: [Belt; 2] = [Belt::DefaultBelt, Belt::ElfBelt];
+pub const ALL_PANTS: [Pants; 2] = [Pants::DefaultPants, Pants::ElfPants];
+pub const ALL_HANDS: [Hand; 2] = [Hand::DefaultHand, Hand::ElfHand];
+pub const ALL_FEET: [Foot; 2] = [Foot::DefaultFoot, Foot::ElfFoot];
+pub const ALL_WEAPONS: [Weapon; 2] = [Weapon::Sword, Weapon::ElfSword];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Character {
-    race: Race,
-    gender: Gender,
-    
-    head: (),
-    chest: (),
-    belt: (),
-    arms: (),
-    feet: ()
+    pub race: Race,
+    pub gender: Gender,
+
+    pub head: Head,
+    pub chest: Chest,
+    pub belt: Belt,
+    pub pants: Pants,
+    pub hand: Hand,
+    pub foot: Foot,
+    pub weapon: Weapon
+}
+
+impl Default for Character {
+    /// cria um personagem com as partes do corpo padrões
+    fn default() -> Self {
+        Self {
+            race: Race::Human,
+            gender: Gender::Unspecified,
+
+            head: Head::DefaultHead,
+            chest: Chest::DefaultChest,
+            belt: Belt::DefaultBelt,
+            pants: Pants::DefaultPants,
+            hand: Hand::DefaultHand,
+            foot: Foot::DefaultFoot,
+            weapon: Weapon::Sword
+        }
+    }
 }
 
 impl Component for Character {
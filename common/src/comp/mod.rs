@@ -1,8 +1,10 @@
+pub mod character;
 pub mod phys;
 pub mod uid;
 pub mod util;
 
 // re-exportações
+pub use character::Character;
 pub use uid::{Uid, UidAllocator};
     
 use specs::World as EcsWorld;
@@ -17,4 +19,6 @@ pub fn register_local_components(ecs_world: &mut EcsWorld) {
     ecs_world.register::<phys::Vel>();
     ecs_world.register::<phys::Dir>();
     ecs_world.register::<phys::UpdateKind>();
+
+    ecs_world.register::<character::Character>();
 }
@@ -62,7 +62,7 @@ pub struct UidAllocator {
 impl UidAllocator {
 	pub fn new() -> Self {
 		Self {
-			range: 0..64::MAX,
+			range: 0..u64::MAX,
 			mapping: HashMap::new()
 		}
 	}
@@ -1,8 +1,12 @@
 #![feature(euclidean_division, duration_float, try_from, trait_alias)]
+#![cfg_attr(test, feature(test))]
 
 #[macro_use]
 extern crate serde_derive;
 
+#[cfg(test)]
+extern crate test;
+
 pub mod clock;
 pub mod comp;
 pub mod figure;
@@ -13,35 +17,45 @@ pub mod terrain;
 pub mod util;
 pub mod volumes;
 pub mod vol;
+pub mod weather;
 
-// todo: não ignorar o código aqui, por alguma razão isso refusa para compilar enquanto não tenha nenhum problema de cópia e cola
 /// o módulo de networking contém wrappers de alto nível do `tcplistener` e `tcpstream` e dados utilizados por ambos os servidores e cliente
 ///
 /// # exemplos
-/// ```ignore
-/// use std::net::SocketAddr;
+/// ```no_run
+/// use std::{
+/// 	thread,
+/// 	time::Duration
+/// };
 ///
-/// use lestinia_common::net::{
-/// 	PostOffice,
-/// 	PostBox
-/// }
+/// use lestinia_common::{
+/// 	net::{
+/// 		PostOffice,
+/// 		PostBox
+/// 	},
+///
+/// 	msg::{
+/// 		ClientMsg,
+/// 		ServerMsg
+/// 	}
+/// };
 ///
-/// let listen_addr = SocketAddr::from(([0, 0, 0, 0], 12345u16));
-/// let conn_addr = SocketAddr::from(([127, 0, 0, 1], 12345u16));
+/// let mut server = PostOffice::<ServerMsg, ClientMsg>::bind(([0, 0, 0, 0], 12345)).unwrap();
+/// let mut client = PostBox::<ClientMsg, ServerMsg>::to_server(([127, 0, 0, 1], 12345)).unwrap();
+/// thread::sleep(Duration::from_millis(100));
 ///
-/// let server: PostOffice<String, String> = PostOffice::new(&listen_addr).unwrap();
-/// let client: PostBox<String, String> = PostBox::to_server(&conn_addr).unwrap();
-/// std::thread::sleep(std::time::Duration::from_millis(100));
+/// let mut scon = server.new_connections().next().unwrap();
 ///
-/// let scon = server.get_iter().unwrap().next().unwrap().unwrap();
-/// std::thread::sleep(std::time::Duration::from_millis(100));
+/// scon.send(ServerMsg::Ping);
+/// client.send(ClientMsg::Ping);
+/// thread::sleep(Duration::from_millis(100));
 ///
-/// scon.send(String::from("foo"));
-/// client.send(String::from("bar"));
-/// std::thread::sleep(std::time::Duration::from_millis(100));
+/// assert_eq!(client.new_messages().count(), 1);
+/// assert_eq!(scon.new_messages().count(), 1);
 ///
-/// assert_eq!("foo", client.recv_iter().unwrap().next().unwrap().unwrap());
-/// assert_eq!("bar", scon.recv_iter().unwrap().next().unwrap().unwrap());
+/// // erros preservam a causa original, como o tipo do erro de io ou o limite excedido
+/// if let Some(err) = client.error() {
+/// 	println!("{}", err);
+/// }
 /// ```
-
 pub mod net;
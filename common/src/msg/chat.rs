@@ -0,0 +1,40 @@
+use std::fmt;
+
+/// tamanho máximo, em caracteres, de uma mensagem de chat
+pub const MAX_CHAT_MSG_LEN: usize = 256;
+
+/// canal pelo qual uma mensagem de chat é enviada
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ChatChannel {
+	/// visível para todos os jogadores do servidor
+	Global,
+
+	/// visível apenas para os jogadores próximos do remetente
+	Local,
+
+	/// mensagem privada para o jogador com o nome fornecido
+	Whisper(String),
+
+	/// anúncios e respostas do próprio servidor, que não podem ser enviados por clients
+	System
+}
+
+impl fmt::Display for ChatChannel {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ChatChannel::Global => write!(f, "global"),
+			ChatChannel::Local => write!(f, "local"),
+			ChatChannel::Whisper(_) => write!(f, "sussurro"),
+			ChatChannel::System => write!(f, "sistema")
+		}
+	}
+}
+
+/// formata uma mensagem de chat recebida para ser mostrada ao jogador
+pub fn format_chat_msg(channel: &ChatChannel, sender: Option<&str>, msg: &str) -> String {
+	match (channel, sender) {
+		(ChatChannel::Whisper(target), Some(sender)) => format!("[{}] {} -> {}: {}", channel, sender, target, msg),
+		(_, Some(sender)) => format!("[{}] {}: {}", channel, sender, msg),
+		(_, None) => format!("[{}] {}", channel, msg)
+	}
+}
@@ -1,15 +1,47 @@
 use crate::comp::{
+	self,
+
 	Uid,
 
 	phys
 };
 
+use crate::net::{
+	PostMsg,
+	Reliability
+};
+
+use super::{
+	DisconnectReason,
+	ChatChannel
+};
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ClientMsg {
+	// mensagens de handshake, enviadas antes de o client possuir uma entidade
+	Connect {
+		version: u32,
+		username: String,
+
+		/// senha da conta do jogador. o primeiro login com senha registra o nome de usuário, e a partir daí
+		/// o nome só pode ser utilizado com a mesma senha. sem senha, apenas nomes não registrados são aceitos
+		password: Option<String>,
+
+		/// o client aceita que o servidor passe a comprimir mensagens grandes
+		compression: bool
+	},
+
+	Query,
+
 	Ping,
 	Pong,
 	
-	Chat(String),
+	Chat {
+		channel: ChatChannel,
+		msg: String
+	},
+
+	Character(comp::Character),
 
 	PlayerPhysics {
 		pos: phys::Pos,
@@ -17,5 +49,48 @@ pub enum ClientMsg {
 		dir: phys::Dir
 	},
 
-	Disconnect
+	Disconnect {
+		reason: DisconnectReason
+	}
+}
+
+/// tamanho máximo de um nome de usuário, em caracteres
+pub const MAX_USERNAME_LEN: usize = 24;
+
+/// tamanho máximo de uma senha, em bytes
+pub const MAX_PASSWORD_LEN: usize = 128;
+
+/// verifica se um nome de usuário pode ser utilizado: não vazio, curto e apenas com letras, números, `_` e `-`
+///
+/// nomes com espaços ou símbolos tornariam ambíguos os comandos e sussurros que recebem o nome de um jogador
+pub fn is_valid_username(username: &str) -> bool {
+	!username.is_empty() &&
+	username.chars().count() <= MAX_USERNAME_LEN &&
+	username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+impl PostMsg for ClientMsg {
+	fn reliability(&self) -> Reliability {
+		match self {
+			// a física é enviada a cada tick, então uma atualização perdida é logo substituída
+			ClientMsg::PlayerPhysics { .. } => Reliability::Unreliable,
+
+			_ => Reliability::Reliable
+		}
+	}
+}
+
+// testes
+
+#[test]
+fn username_validation() {
+	assert!(is_valid_username("jogador_1"));
+	assert!(is_valid_username("a-b"));
+	assert!(is_valid_username(&"a".repeat(MAX_USERNAME_LEN)));
+
+	assert!(!is_valid_username(""));
+	assert!(!is_valid_username(&"a".repeat(MAX_USERNAME_LEN + 1)));
+	assert!(!is_valid_username("dois nomes"));
+	assert!(!is_valid_username("admin\n"));
+	assert!(!is_valid_username("ádmin"));
 }
@@ -0,0 +1,74 @@
+use std::fmt;
+
+/// motivo pelo qual uma conexão entre client e servidor foi encerrada
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DisconnectReason {
+	/// o client saiu do jogo normalmente
+	Quit,
+
+	/// o outro lado parou de responder
+	Timeout,
+
+	/// a conexão foi perdida por um erro de rede
+	ConnectionLost,
+
+	/// o jogador foi expulso por um administrador
+	Kicked(String),
+
+	/// o jogador está banido do servidor
+	Banned(String),
+
+	/// o servidor aceita apenas jogadores da whitelist
+	NotWhitelisted,
+
+	/// o servidor atingiu o número máximo de jogadores
+	ServerFull,
+
+	/// o nome de usuário está vazio, é longo demais ou possui caracteres não permitidos
+	InvalidUsername,
+
+	/// já existe um jogador conectado com o mesmo nome
+	UsernameTaken,
+
+	/// o nome de usuário está registrado e a senha não confere
+	WrongPassword,
+
+	/// o servidor está sendo desligado
+	ServerShutdown,
+
+	/// client e servidor utilizam versões incompatíveis do protocolo
+	VersionMismatch {
+		server_version: u32,
+		client_version: u32
+	},
+
+	/// o outro lado enviou uma mensagem inválida ou inesperada
+	ProtocolError
+}
+
+impl fmt::Display for DisconnectReason {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			DisconnectReason::Quit => write!(f, "saiu do jogo"),
+			DisconnectReason::Timeout => write!(f, "tempo de conexão esgotado"),
+			DisconnectReason::ConnectionLost => write!(f, "conexão perdida"),
+			DisconnectReason::Kicked(reason) => write!(f, "expulso: {}", reason),
+			DisconnectReason::Banned(reason) => write!(f, "banido: {}", reason),
+			DisconnectReason::NotWhitelisted => write!(f, "fora da whitelist do servidor"),
+			DisconnectReason::ServerFull => write!(f, "servidor cheio"),
+			DisconnectReason::InvalidUsername => write!(f, "nome de usuário inválido"),
+			DisconnectReason::UsernameTaken => write!(f, "nome de usuário já está em uso"),
+			DisconnectReason::WrongPassword => write!(f, "senha incorreta"),
+			DisconnectReason::ServerShutdown => write!(f, "o servidor foi desligado"),
+
+			DisconnectReason::VersionMismatch { server_version, client_version } => write!(
+				f,
+				"versão incompatível (servidor: {}, cliente: {})",
+				server_version,
+				client_version
+			),
+
+			DisconnectReason::ProtocolError => write!(f, "erro de protocolo")
+		}
+	}
+}
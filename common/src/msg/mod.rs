@@ -1,6 +1,35 @@
 pub mod server;
 pub mod client;
+pub mod disconnect;
+pub mod chat;
 
 // re-exportações
-pub use server::ServerMsg;
-pub use client::ClientMsg;
+pub use disconnect::DisconnectReason;
+
+pub use chat::{
+	ChatChannel,
+
+	MAX_CHAT_MSG_LEN
+};
+
+pub use server::{
+	ServerMsg,
+	ServerInfo
+};
+
+pub use client::{
+	ClientMsg,
+
+	MAX_USERNAME_LEN,
+	MAX_PASSWORD_LEN,
+
+	is_valid_username
+};
+
+/// versão do protocolo de mensagens entre client e servidor
+///
+/// deve ser incrementada sempre que `ClientMsg` ou `ServerMsg` mudarem de forma incompatível
+pub const PROTOCOL_VERSION: u32 = 7;
+
+/// porta padrão em que o servidor escuta por conexões
+pub const DEFAULT_PORT: u16 = 59003;
@@ -1,26 +1,129 @@
+use vek::*;
+
 use crate::comp::{
 	Uid,
 
 	phys
 };
 
+use crate::weather::Weather;
+
+use crate::net::{
+	PostMsg,
+	Reliability
+};
+
+use super::{
+	DisconnectReason,
+	ChatChannel
+};
+
+/// informações públicas do servidor, respondidas a um `ClientMsg::Query` sem criar um jogador
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServerInfo {
+	pub name: String,
+	pub motd: String,
+
+	pub version: u32,
+
+	pub players: u32,
+	pub max_players: u32,
+
+	pub tick_rate: u32
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ServerMsg {
-	Shutdown,
+	// respostas de handshake
+	QueryResponse(ServerInfo),
+
+	Disconnect {
+		reason: DisconnectReason
+	},
+
+	/// enviada antes de `SetPlayerEntity`, indicando se os dois lados devem comprimir mensagens grandes
+	Compression {
+		enabled: bool
+	},
 
 	Ping,
 	Pong,
 	
-	Chat(String),
+	Chat {
+		channel: ChatChannel,
+
+		// `None` para mensagens do próprio servidor
+		sender: Option<String>,
+
+		msg: String
+	},
+
 	SetPlayerEntity(Uid),
 
+	/// hora do dia do servidor, enviada ao entrar e periodicamente para corrigir o relógio do client
+	TimeOfDay(f64),
+
+	/// clima atual de uma região, enviado ao entrar e sempre que ele mudar
+	Weather {
+		region: Vec2<i32>,
+		weather: Weather
+	},
+
 	EntityPhysics {
 		uid: Uid,
 		
 		pos: phys::Pos,
 		vel: phys::Vel,
-		dir: phys::Dir
+		dir: phys::Dir,
+
+		/// atualizações forçadas, como teleportes, não podem ser perdidas
+		force: bool
 	},
 
 	EntityDeleted(Uid)
 }
+
+impl PostMsg for ServerMsg {
+	fn reliability(&self) -> Reliability {
+		match self {
+			// a física é enviada a cada tick, então uma atualização perdida é logo substituída
+			ServerMsg::EntityPhysics { force: false, .. } => Reliability::Unreliable,
+
+			_ => Reliability::Reliable
+		}
+	}
+
+	fn sequence(&self) -> u64 {
+		match self {
+			// a física de uma entidade não descarta a das outras
+			ServerMsg::EntityPhysics { uid, .. } => (*uid).into(),
+
+			_ => 0
+		}
+	}
+}
+
+// testes
+
+#[cfg(test)]
+fn physics_msg(uid: u64, force: bool) -> ServerMsg {
+	ServerMsg::EntityPhysics {
+		uid: Uid(uid),
+
+		pos: phys::Pos(Vec3::zero()),
+		vel: phys::Vel(Vec3::zero()),
+		dir: phys::Dir(Vec3::unit_y()),
+
+		force
+	}
+}
+
+#[test]
+fn forced_physics_is_reliable() {
+	assert_eq!(physics_msg(1, false).reliability(), Reliability::Unreliable);
+	assert_eq!(physics_msg(1, true).reliability(), Reliability::Reliable);
+
+	// cada entidade possui a sua própria sequência
+	assert_eq!(physics_msg(1, false).sequence(), 1);
+	assert_eq!(physics_msg(2, false).sequence(), 2);
+}
@@ -1,24 +1,25 @@
-pub mod data;
-pub mod error;
-
 pub mod post;
-pub mod postbox;
-pub mod postoffice;
+pub mod tls;
+
+mod udp;
 
 // re-exportações
 pub use self::{
-    data::{
-        ClientMsg,
-        ServerMsg
-    },
-
     post::{
         Error as PostError,
 
+        ConnectionLimits,
+        Reliability,
+
         PostBox,
-        PostOffice
+        PostMsg,
+        PostOffice,
+        PostRecv,
+        PostSend
+    },
+
+    tls::{
+        ClientTls,
+        ServerTls
     }
 };
-
-pub trait PostSend = 'static + serde::Serialize + std::marker::Send + std::fmt::Debug;
-pub trait PostRecv = 'static + serde::de::DeserializeOwned + std::marker::Send + std::fmt::Debug;
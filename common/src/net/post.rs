@@ -3,21 +3,31 @@ use std::{
     thread,
 
     net::{
-        SocketAddr,
-        Shutdown
+        IpAddr,
+        SocketAddr
     },
 
-    sync::mpsc::TryRecvError,
+    sync::{
+        Arc,
+        Mutex,
 
-    io::{
-        self,
+        mpsc::TryRecvError
+    },
+
+    io,
+
+    collections::{
+        HashMap,
+        VecDeque,
+
+        vec_deque
+    },
 
-        Read,
-        Write
+    time::{
+        Duration,
+        Instant
     },
 
-    collections::VecDeque,
-    time::Duration,
     convert::TryFrom
 };
 
@@ -44,41 +54,143 @@ use mio_extras::channel::{
 };
 
 use bincode;
+use lz4_flex;
+use rustls;
+
+use super::{
+    tls::{
+        ClientTls,
+        ServerTls,
+        Transport
+    },
+
+    udp::{
+        DATAGRAM_HEADER_BYTES,
+
+        UdpChannel
+    }
+};
 
+/// erro de uma conexão, preservando a causa original
 #[derive(Clone, Debug, PartialEq)]
 pub enum Error {
+    /// o outro lado encerrou a conexão
     Disconnect,
-    Network,
 
-    InvalidMsg,
+    /// erro de io do socket ou do poll, com o tipo e a descrição do erro original
+    Io(io::ErrorKind, String),
+
+    /// uma mensagem não pôde ser serializada ou desserializada
+    Serde(String),
+
+    /// um frame declarou um corpo maior que o limite
+    MsgSizeLimit {
+        size: usize,
+        limit: usize
+    },
+
+    /// frame com dados comprimidos ou de controle inválidos
+    InvalidFrame,
+
+    /// a conexão excedeu o orçamento de bytes recebidos
+    RecvBudgetExceeded,
+
+    /// a fila de envio cresceu além do limite, normalmente por um client lento demais
+    SendBufferFull,
+
+    /// configuração tls inválida, ou o handshake tls falhou
+    Tls(String),
+
+    /// falha na comunicação entre as threads do postbox ou postoffice
     Internal
 }
 
+impl Error {
+    /// verifica se o erro foi causado por dados inválidos ou abusivos vindos do outro lado
+    pub fn is_protocol_violation(&self) -> bool {
+        match self {
+            Error::Serde(_) |
+            Error::MsgSizeLimit { .. } |
+            Error::InvalidFrame |
+            Error::RecvBudgetExceeded => true,
+
+            _ => false
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Disconnect => write!(f, "conexão encerrada"),
+            Error::Io(kind, msg) => write!(f, "erro de io ({:?}): {}", kind, msg),
+            Error::Serde(msg) => write!(f, "mensagem inválida: {}", msg),
+            Error::MsgSizeLimit { size, limit } => write!(f, "mensagem de {} bytes excede o limite de {} bytes", size, limit),
+            Error::InvalidFrame => write!(f, "frame inválido"),
+            Error::RecvBudgetExceeded => write!(f, "limite de dados recebidos excedido"),
+            Error::SendBufferFull => write!(f, "fila de envio cheia"),
+            Error::Tls(msg) => write!(f, "erro de tls: {}", msg),
+            Error::Internal => write!(f, "erro interno")
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl From<io::Error> for Error {
     fn from(err: io::Error) -> Self {
-        Error::Network
+        // erros da sessão tls chegam envolvidos em erros de io pelo `Transport`
+        match err.get_ref() {
+            Some(inner) if inner.is::<rustls::TLSError>() => Error::Tls(inner.to_string()),
+
+            _ => Error::Io(err.kind(), err.to_string())
+        }
     }
 }
 
 impl From<TryRecvError> for Error {
-    fn from(err: TryRecvError) -> Self {
+    fn from(_: TryRecvError) -> Self {
         Error::Internal
     }
 }
 
-impl From<bincode::ErrorKind> for Error {
-    fn from(err: bincode::ErrorKind) -> Self {
-        Error::InvalidMsg
+impl From<bincode::Error> for Error {
+    fn from(err: bincode::Error) -> Self {
+        Error::Serde(err.to_string())
     }
 }
 
 impl<T> From<mio_extras::channel::SendError<T>> for Error {
-    fn from(err: mio_extras::channel::SendError<T>) -> Self {
+    fn from(_: mio_extras::channel::SendError<T>) -> Self {
         Error::Internal
     }
 }
 
-pub trait PostSend = 'static + serde::Serialize + Send + fmt::Debug;
+/// como uma mensagem deve ser entregue
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Reliability {
+    /// entregue sempre e em ordem pelo tcp
+    Reliable,
+
+    /// enviada pelo canal udp quando ele estiver disponível. pode ser perdida, e mensagens mais antigas que a última
+    /// recebida são descartadas
+    Unreliable
+}
+
+/// permite que cada tipo de mensagem declare a sua classe de entrega
+pub trait PostMsg {
+    fn reliability(&self) -> Reliability {
+        Reliability::Reliable
+    }
+
+    /// sequência de uma mensagem não confiável: uma mensagem só é descartada por chegar depois de uma mais nova
+    /// da mesma sequência, como atualizações de uma mesma entidade
+    fn sequence(&self) -> u64 {
+        0
+    }
+}
+
+pub trait PostSend = 'static + serde::Serialize + Send + fmt::Debug + PostMsg;
 pub trait PostRecv = 'static + serde::de::DeserializeOwned + Send + fmt::Debug;
 
 const TCP_TOK:     Token = Token(0);
@@ -86,11 +198,152 @@ const CTRL_TOK:    Token = Token(1);
 const POSTBOX_TOK: Token = Token(2);
 const SEND_TOK:    Token = Token(3);
 const RECV_TOK:    Token = Token(4);
+const UDP_TOK:     Token = Token(5);
 
+// tamanho do prefixo de cada frame
+const HEADER_BYTES: usize = 4;
+
+// tamanho máximo do corpo de um frame, antes e depois da descompressão
 const MAX_MSG_BYTES: usize = 1 << 20;
 
+// bit do prefixo que marca um corpo comprimido com lz4
+const COMPRESSED_FLAG: u32 = 1 << 31;
+
+// bit do prefixo que marca um frame de controle do próprio postbox, que nunca chega à aplicação
+const CONTROL_FLAG: u32 = 1 << 30;
+
+// bits do prefixo com o tamanho do corpo
+const LEN_MASK: u32 = !(COMPRESSED_FLAG | CONTROL_FLAG);
+
+// mensagens menores que isso nunca são comprimidas, mantendo pings e chat baratos
+const COMPRESSION_THRESHOLD: usize = 256;
+
+// tamanho do buffer utilizado em cada leitura do socket
+const READ_BUF_BYTES: usize = 1 << 14;
+
+// bytes ainda não enviados que uma conexão pode acumular antes de ser encerrada
+const MAX_SEND_BUF_BYTES: usize = 1 << 23;
+
+// orçamento de bytes recebidos por conexão: taxa recuperada por segundo e máximo acumulado
+const RECV_BUDGET_BYTES_PER_SEC: f64 = (1 << 20) as f64;
+const RECV_BUDGET_BURST_BYTES: f64 = (1 << 22) as f64;
+
+// tempo máximo esperando a fila de envio esvaziar quando um postbox é descartado
+const SHUTDOWN_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// limites aplicados às conexões de cada endereço ip antes de um postbox ser criado
+///
+/// conexões acima dos limites são fechadas imediatamente pelo worker do postoffice
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionLimits {
+    /// número máximo de conexões abertas ao mesmo tempo por um endereço
+    pub max_per_ip: Option<usize>,
+
+    /// número máximo de novas conexões de um endereço dentro do intervalo fornecido
+    pub max_rate_per_ip: Option<(usize, Duration)>
+}
+
+/// mantém a contagem de conexões abertas por endereço, decrementada quando o postbox é descartado
+struct ConnGuard {
+    ip: IpAddr,
+    open: Arc<Mutex<HashMap<IpAddr, usize>>>
+}
+
+impl Drop for ConnGuard {
+    fn drop(&mut self) {
+        if let Ok(mut open) = self.open.lock() {
+            let remove = match open.get_mut(&self.ip) {
+                Some(count) => {
+                    *count = count.saturating_sub(1);
+
+                    *count == 0
+                },
+
+                None => false
+            };
+
+            if remove {
+                open.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// aplica os `ConnectionLimits` às conexões aceitas pelo worker do postoffice
+struct ConnLimiter {
+    limits: ConnectionLimits,
+
+    open: Arc<Mutex<HashMap<IpAddr, usize>>>,
+    recent: HashMap<IpAddr, VecDeque<Instant>>
+}
+
+impl ConnLimiter {
+    fn new(limits: ConnectionLimits) -> Self {
+        Self {
+            limits,
+
+            open: Arc::new(Mutex::new(HashMap::new())),
+            recent: HashMap::new()
+        }
+    }
+
+    /// decide se uma nova conexão do endereço fornecido deve ser aceita
+    fn admit(&mut self, ip: IpAddr) -> Option<ConnGuard> {
+        if let Some((max, window)) = self.limits.max_rate_per_ip {
+            let now = Instant::now();
+
+            // esquecer tentativas antigas, e endereços sem tentativas recentes
+            self.recent.retain(|_, attempts| {
+                while attempts.front().map(|t| now.duration_since(*t) > window).unwrap_or(false) {
+                    attempts.pop_front();
+                }
+
+                !attempts.is_empty()
+            });
+
+            let attempts = self.recent.entry(ip).or_insert_with(VecDeque::new);
+
+            if attempts.len() >= max {
+                return None;
+            }
+
+            attempts.push_back(now);
+        }
+
+        let mut open = self.open.lock().ok()?;
+        let count = open.entry(ip).or_insert(0);
+
+        if self.limits.max_per_ip.map(|max| *count >= max).unwrap_or(false) {
+            return None;
+        }
+
+        *count += 1;
+
+        Some(ConnGuard {
+            ip,
+            open: self.open.clone()
+        })
+    }
+}
+
 enum CtrlMsg {
-    Shutdown
+    Shutdown,
+
+    /// fecha o socket de escuta, sem afetar as conexões já existentes
+    StopListening,
+
+    /// ativa ou desativa a compressão dos frames enviados
+    SetCompression(bool)
+}
+
+/// mensagens trocadas entre os workers dos postboxes através do tcp
+#[derive(Debug, Serialize, Deserialize)]
+enum ControlFrame {
+    /// anunciada pelo servidor: porta do canal udp da conexão e o token que os datagramas devem carregar
+    Udp {
+        port: u16,
+        token: u64
+    }
 }
 
 pub struct PostOffice<S: PostSend, R: PostRecv> {
@@ -103,6 +356,20 @@ pub struct PostOffice<S: PostSend, R: PostRecv> {
 
 impl<S: PostSend, R: PostRecv> PostOffice<S, R> {
     pub fn bind<A: Into<SocketAddr>>(addr: A) -> Result<Self, Error> {
+        Self::bind_with_limits(addr, ConnectionLimits::default())
+    }
+
+    /// cria um postoffice que recusa conexões acima dos limites por endereço fornecidos
+    pub fn bind_with_limits<A: Into<SocketAddr>>(addr: A, limits: ConnectionLimits) -> Result<Self, Error> {
+        Self::bind_with(addr, limits, None)
+    }
+
+    /// cria um postoffice em que todas as conexões são criptografadas com o certificado fornecido
+    pub fn bind_tls<A: Into<SocketAddr>>(addr: A, limits: ConnectionLimits, tls: ServerTls) -> Result<Self, Error> {
+        Self::bind_with(addr, limits, Some(tls))
+    }
+
+    fn bind_with<A: Into<SocketAddr>>(addr: A, limits: ConnectionLimits, tls: Option<ServerTls>) -> Result<Self, Error> {
         let tcp_listener = TcpListener::bind(&addr.into())?;
 
         let (ctrl_tx, ctrl_rx) = channel();
@@ -119,6 +386,8 @@ impl<S: PostSend, R: PostRecv> PostOffice<S, R> {
         let worker = thread::spawn(move || office_worker(
             worker_poll,
             tcp_listener,
+            ConnLimiter::new(limits),
+            tls,
 
             ctrl_rx,
             postbox_tx
@@ -139,6 +408,22 @@ impl<S: PostSend, R: PostRecv> PostOffice<S, R> {
         self.err.clone()
     }
 
+    /// para de aceitar novas conexões, mantendo as já existentes
+    pub fn stop_listening(&mut self) {
+        let _ = self.ctrl_tx.send(CtrlMsg::StopListening);
+    }
+
+    /// encerra o worker do postoffice e espera a thread terminar, retornando o erro dela caso exista
+    pub fn shutdown(&mut self) -> Result<(), Error> {
+        let _ = self.ctrl_tx.send(CtrlMsg::Shutdown);
+
+        match self.worker.take() {
+            Some(worker) => worker.join().map_err(|_| Error::Internal)?,
+
+            None => Ok(())
+        }
+    }
+
     pub fn new_connections(&mut self) -> impl ExactSizeIterator<Item=PostBox<S, R>> {
         let mut conns = VecDeque::new();
 
@@ -161,7 +446,7 @@ impl<S: PostSend, R: PostRecv> PostOffice<S, R> {
         for event in events {
             match event.token() {
                 // manter lendo novos postboxes do canal
-                POSTBOX_TOKEN => loop {
+                POSTBOX_TOK => loop {
                     match self.postbox_rx.try_recv() {
                         Ok(Ok(conn)) => conns.push_back(conn),
 
@@ -191,19 +476,23 @@ impl<S: PostSend, R: PostRecv> PostOffice<S, R> {
 
 impl<S: PostSend, R: PostRecv> Drop for PostOffice<S, R> {
     fn drop(&mut self) {
-        let _ = self.ctrl_tx.send(CtrlMsg::Shutdown);
-        let _ = self.worker.take().map(|w| w.join());
+        let _ = self.shutdown();
     }
 }
 
 fn office_worker<S: PostSend, R: PostRecv>(
     poll: Poll,
     tcp_listener: TcpListener,
+    mut limiter: ConnLimiter,
+    tls: Option<ServerTls>,
     ctrl_rx: Receiver<CtrlMsg>,
     postbox_tx: Sender<Result<PostBox<S, R>, Error>>
 ) -> Result<(), Error> {
     let mut events = Events::with_capacity(64);
 
+    // `none` depois que o servidor parar de aceitar conexões
+    let mut tcp_listener = Some(tcp_listener);
+
     loop {
         if let Err(err) = poll.poll(&mut events, None) {
             postbox_tx.send(Err(err.into()))?;
@@ -217,7 +506,11 @@ fn office_worker<S: PostSend, R: PostRecv>(
                     match ctrl_rx.try_recv() {
                         Ok(CtrlMsg::Shutdown) => return Ok(()),
 
-                        Err(TryRecvError::Empty) => {},
+                        Ok(CtrlMsg::StopListening) => if let Some(listener) = tcp_listener.take() {
+                            poll.deregister(&listener)?;
+                        },
+
+                        Err(TryRecvError::Empty) => break,
 
                         Err(err) => {
                             postbox_tx.send(Err(err.into()))?;
@@ -227,13 +520,54 @@ fn office_worker<S: PostSend, R: PostRecv>(
                     }
                 },
 
-                TCP_TOK => postbox_tx.send(
-                    match tcp_listener.accept() {
-                        Ok((stream, _)) => PostBox::from_tcpstream(stream),
+                // aceitar todas as conexões pendentes, já que o listener é registrado com edge
+                TCP_TOK => if let Some(listener) = &tcp_listener {
+                    loop {
+                        match listener.accept() {
+                            // conexões acima dos limites são fechadas antes de um postbox ser criado
+                            Ok((stream, addr)) => if let Some(guard) = limiter.admit(addr.ip()) {
+                                let (transport, udp) = match &tls {
+                                    Some(tls) => (Transport::server(stream, tls), None),
+
+                                    // o canal udp não é criptografado, então ele só é oferecido sem tls
+                                    None => {
+                                        let udp = stream
+                                            .local_addr()
+                                            .ok()
+                                            .and_then(|addr| UdpChannel::offer(addr.ip()).ok());
+
+                                        (Transport::plain(stream), udp)
+                                    }
+                                };
+
+                                // o orçamento de bytes recebidos protege apenas o servidor contra clients abusivos
+                                let recv_budget = ByteBudget::new(RECV_BUDGET_BYTES_PER_SEC, RECV_BUDGET_BURST_BYTES);
+
+                                // uma conexão que falhou afeta apenas ela mesma, então o postoffice continua aceitando
+                                match PostBox::from_transport(transport, udp, false, Some(recv_budget)) {
+                                    Ok(mut postbox) => {
+                                        postbox.conn_guard = Some(guard);
+
+                                        postbox_tx.send(Ok(postbox))?;
+                                    },
+
+                                    Err(err) => log::warn!("falha ao criar postbox para {}: {:?}", addr, err)
+                                }
+                            },
+
+                            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                            Err(ref err) if err.kind() == io::ErrorKind::ConnectionAborted => continue,
 
-                        Err(err) => Err(err.into())
+                            // erros como o limite de arquivos abertos são temporários, e a próxima conexão tenta de novo
+                            Err(err) => {
+                                log::warn!("falha ao aceitar conexão: {:?}", err);
+
+                                break;
+                            }
+                        }
                     }
-                )?,
+                },
 
                 tok => panic!("token de evento inesperado '{:?}'", tok)
             }
@@ -248,23 +582,45 @@ pub struct PostBox<S: PostSend, R: PostRecv> {
     send_tx: Sender<S>,
     recv_rx: Receiver<Result<R, Error>>,
 
+    // endereço do outro lado da conexão
+    peer_addr: Option<SocketAddr>,
+
+    // presente em conexões aceitas por um postoffice, liberando a vaga do endereço quando descartado
+    conn_guard: Option<ConnGuard>,
+
     poll: Poll,
     err: Option<Error>
 }
 
 impl<S: PostSend, R: PostRecv> PostBox<S, R> {
     pub fn to_server<A: Into<SocketAddr>>(addr: A) -> Result<Self, Error> {
-        Self::from_tcpstream(TcpStream::connect(&addr.into())?)
+        Self::from_transport(Transport::plain(TcpStream::connect(&addr.into())?), None, true, None)
+    }
+
+    /// conecta a um servidor tls, verificando o certificado dele com as configurações de confiança fornecidas
+    ///
+    /// uma falha no handshake é reportada como `Error::Tls` pelo `error` do postbox
+    pub fn to_server_tls<A: Into<SocketAddr>>(addr: A, tls: &ClientTls) -> Result<Self, Error> {
+        Self::from_transport(Transport::client(TcpStream::connect(&addr.into())?, tls)?, None, false, None)
     }
 
-    fn from_tcpstream(tcp_stream: TcpStream) -> Result<Self, Error> {
+    // `udp` é o canal oferecido pelo servidor, `accept_udp` permite ao client aceitar um canal anunciado e
+    // `recv_budget` limita os bytes recebidos, encerrando a conexão quando esgotado
+    fn from_transport(
+        transport: Transport,
+        udp: Option<UdpChannel>,
+        accept_udp: bool,
+        recv_budget: Option<ByteBudget>
+    ) -> Result<Self, Error> {
+        let peer_addr = transport.tcp_stream().peer_addr().ok();
+
         let (ctrl_tx, ctrl_rx) = channel();
         let (send_tx, send_rx) = channel();
         let (recv_tx, recv_rx) = channel();
 
         let worker_poll = Poll::new()?;
 
-        worker_poll.register(&tcp_stream, TCP_TOK, Ready::readable(), PollOpt::edge())?;
+        worker_poll.register(transport.tcp_stream(), TCP_TOK, Ready::readable() | Ready::writable(), PollOpt::edge())?;
         worker_poll.register(&ctrl_rx, CTRL_TOK, Ready::readable(), PollOpt::edge())?;
         worker_poll.register(&send_rx, SEND_TOK, Ready::readable(), PollOpt::edge())?;
 
@@ -273,7 +629,10 @@ impl<S: PostSend, R: PostRecv> PostBox<S, R> {
 
         let worker = thread::spawn(move || postbox_worker(
             worker_poll,
-            tcp_stream,
+            transport,
+            udp,
+            accept_udp,
+            recv_budget,
 
             ctrl_rx,
             send_rx,
@@ -287,6 +646,9 @@ impl<S: PostSend, R: PostRecv> PostBox<S, R> {
             send_tx,
             recv_rx,
 
+            peer_addr,
+            conn_guard: None,
+
             poll: postbox_poll,
             err: None
         })
@@ -296,11 +658,41 @@ impl<S: PostSend, R: PostRecv> PostBox<S, R> {
         self.err.clone()
     }
 
+    /// obtém o endereço do outro lado da conexão, caso seja conhecido
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+
     pub fn send(&mut self, data: S) {
         let _ = self.send_tx.send(data);
     }
 
+    /// pede ao worker para enviar o que ainda está na fila e encerrar a conexão, sem esperar por ele
+    ///
+    /// fechar vários postboxes antes de descartá-los permite que as filas de todos sejam enviadas ao mesmo tempo,
+    /// em vez de uma espera de até `SHUTDOWN_FLUSH_TIMEOUT` para cada um
+    pub fn close(&mut self) {
+        let _ = self.ctrl_tx.send(CtrlMsg::Shutdown);
+    }
+
+    /// ativa ou desativa a compressão das mensagens enviadas a partir de agora
+    ///
+    /// frames comprimidos são sempre aceitos na leitura, então apenas o lado que envia precisa ser configurado,
+    /// normalmente após os dois lados concordarem durante o handshake
+    pub fn set_compression(&mut self, enabled: bool) {
+        let _ = self.ctrl_tx.send(CtrlMsg::SetCompression(enabled));
+    }
+
     pub fn new_messages(&mut self) -> impl ExactSizeIterator<Item = R> {
+        self.recv_messages(Duration::new(0, 0))
+    }
+
+    /// igual a `new_messages`, mas bloqueia até que alguma mensagem chegue, a conexão falhe ou `timeout` passe
+    pub fn wait_messages(&mut self, timeout: Duration) -> impl ExactSizeIterator<Item = R> {
+        self.recv_messages(timeout)
+    }
+
+    fn recv_messages(&mut self, timeout: Duration) -> vec_deque::IntoIter<R> {
         let mut msgs = VecDeque::new();
 
         if let Some(_) = self.err {
@@ -312,7 +704,7 @@ impl<S: PostSend, R: PostRecv> PostBox<S, R> {
         if let Err(err) = self.poll.poll(
             &mut events,
 
-            Some(Duration::new(0, 0))
+            Some(timeout)
         ) {
             self.err = Some(err.into());
 
@@ -322,7 +714,7 @@ impl<S: PostSend, R: PostRecv> PostBox<S, R> {
         for event in events {
             match event.token() {
                 // mantém lendo novas mensagens do canal
-                RECV_TOKEN => loop {
+                RECV_TOK => loop {
                     match self.recv_rx.try_recv() {
                         Ok(Ok(msg)) => msgs.push_back(msg),
 
@@ -360,18 +752,34 @@ impl<S: PostSend, R: PostRecv> Drop for PostBox<S, R> {
 fn postbox_worker<S: PostSend, R: PostRecv>(
     poll: Poll,
     
-    mut tcp_stream: TcpStream,
+    mut transport: Transport,
+    mut udp: Option<UdpChannel>,
+    accept_udp: bool,
+    mut recv_budget: Option<ByteBudget>,
 
     ctrl_rx: Receiver<CtrlMsg>,
     send_rx: Receiver<S>,
     recv_tx: Sender<Result<R, Error>>
 ) -> Result<(), Error> {
-    enum RecvState {
-        ReadHead(Vec<u8>),
-        ReadBody(usize, Vec<u8>)
+    let mut decoder = FrameDecoder::new();
+    let mut read_buf = vec![0; READ_BUF_BYTES];
+
+    // desativada até ser negociada pelo handshake
+    let mut compression = false;
+
+    // bytes já codificados que ainda não couberam no socket
+    let mut send_buf = Vec::new();
+
+    // anunciar o canal udp, enviado assim que o socket aceitar escrita
+    if let Some(channel) = &udp {
+        poll.register(channel.socket(), UDP_TOK, Ready::readable(), PollOpt::edge())?;
+
+        send_buf.extend_from_slice(&encode_control(&ControlFrame::Udp {
+            port: channel.port()?,
+            token: channel.token()
+        })?);
     }
 
-    let mut recv_state = RecvState::ReadHead(Vec::with_capacity(8));
     let mut events = Events::with_capacity(64);
 
     'work: loop {
@@ -385,7 +793,24 @@ fn postbox_worker<S: PostSend, R: PostRecv>(
             match event.token() {
                 CTRL_TOK => loop {
                     match ctrl_rx.try_recv() {
+                        // postboxes não escutam por conexões
+                        Ok(CtrlMsg::StopListening) => {},
+
+                        Ok(CtrlMsg::SetCompression(enabled)) => compression = enabled,
+
                         Ok(CtrlMsg::Shutdown) => {
+                            // enviar as mensagens que ainda estão na fila antes de encerrar a conexão,
+                            // assim uma resposta enviada logo antes do postbox ser descartado não é perdida
+                            while let Ok(outgoing_msg) = send_rx.try_recv() {
+                                match encode_frame(&outgoing_msg, compression) {
+                                    Ok(frame) => send_buf.extend_from_slice(&frame),
+
+                                    Err(_) => break
+                                }
+                            }
+
+                            let _ = flush_blocking(&mut transport, &mut send_buf, SHUTDOWN_FLUSH_TIMEOUT);
+
                             break 'work;
                         },
 
@@ -399,116 +824,180 @@ fn postbox_worker<S: PostSend, R: PostRecv>(
                     }
                 },
 
-                SEND_TOK => loop {
-                    match send_rx.try_recv() {
-                        Ok(outgoing_msg) => {
-                            let mut msg_bytes = match bincode::serialize(&outgoing_msg) {
-                                Ok(bytes) => bytes,
-
-                                Err(err) => {
-                                    recv_tx.send(Err((*err).into()));
+                SEND_TOK => {
+                    loop {
+                        match send_rx.try_recv() {
+                            Ok(outgoing_msg) => if let Err(err) = queue_msg(&outgoing_msg, compression, &mut udp, &mut send_buf) {
+                                recv_tx.send(Err(err))?;
 
-                                    break 'work;
-                                }
-                            };
+                                break 'work;
+                            },
 
-                            let mut packet = msg_bytes
-                                .len()
-                                .to_le_bytes()
-                                .as_ref()
-                                .to_vec();
+                            Err(TryRecvError::Empty) => break,
+                            
+                            Err(err) => Err(err)?
+                        }
 
-                            packet.append(&mut msg_bytes);
+                        // rajadas maiores que o limite são escritas enquanto o socket aceitar
+                        if send_buf.len() > MAX_SEND_BUF_BYTES {
+                            if let Err(err) = flush(&mut transport, &mut send_buf) {
+                                recv_tx.send(Err(err))?;
 
-                            match tcp_stream.write_all(&packet) {
-                                Ok(()) => {},
+                                break 'work;
+                            }
 
-                                Err(err) => {
-                                    recv_tx.send(Err(err.into()));
+                            // um client lento não pode fazer a fila de envio crescer sem limites
+                            if send_buf.len() > MAX_SEND_BUF_BYTES {
+                                recv_tx.send(Err(Error::SendBufferFull))?;
 
-                                    break 'work;
-                                }
+                                break 'work;
                             }
-                        },
+                        }
+                    }
 
-                        Err(TryRecvError::Empty) => break,
-                        
-                        Err(err) => Err(err)?
+                    if let Err(err) = flush(&mut transport, &mut send_buf) {
+                        recv_tx.send(Err(err))?;
+
+                        break 'work;
                     }
                 },
 
-                TCP_TOK => loop {
-                    match tcp_stream.take_error() {
+                TCP_TOK => {
+                    match transport.tcp_stream().take_error() {
                         Ok(None) => {},
 
                         Ok(Some(err)) => {
-                            recv_tx.send(Err(err.into()));
+                            recv_tx.send(Err(err.into()))?;
 
                             break 'work;
                         },
                         
                         Err(err) => {
-                            recv_tx.send(Err(err.into()));
+                            recv_tx.send(Err(err.into()))?;
+
+                            break 'work;
+                        }
+                    }
+
+                    // o socket voltou a aceitar escrita, continuar enviando a fila
+                    if event.readiness().is_writable() {
+                        if let Err(err) = flush(&mut transport, &mut send_buf) {
+                            recv_tx.send(Err(err))?;
 
                             break 'work;
                         }
                     }
 
-                    match &mut recv_state {
-                        RecvState::ReadHead(head) => if head.len() == 8 {
-                            let len = usize::from_le_bytes(<[u8; 8]>::try_from(head.as_slice()).unwrap());
+                    if !event.readiness().is_readable() {
+                        continue;
+                    }
 
-                            if len > MAX_MSG_BYTES {
-                                recv_tx.send(Err(Error::InvalidMsg));
+                    // ler até o socket esvaziar, já que ele é registrado com edge
+                    loop {
+                        let n = match transport.read(&mut read_buf) {
+                            Ok(0) => {
+                                recv_tx.send(Err(Error::Disconnect))?;
 
                                 break 'work;
-                            } else if len == 0 {
-                                recv_state = RecvState::ReadHead(Vec::with_capacity(8));
+                            },
 
-                                break;
-                            } else {
-                                recv_state = RecvState::ReadBody(
-                                    len,
+                            Ok(n) => n,
 
-                                    Vec::new()
-                                );
-                            }
-                        } else {
-                            let mut b = [0; 1];
+                            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
 
-                            match tcp_stream.read(&mut b) {
-                                Ok(_) => head.push(b[0]),
+                            Err(err) => {
+                                recv_tx.send(Err(err.into()))?;
 
-                                Err(_) => break
+                                break 'work;
                             }
-                        },
+                        };
 
-                        RecvState::ReadBody(len, body) => if body.len() == *len {
-                            match bincode::deserialize(&body) {
-                                Ok(msg) => {
-                                    recv_tx.send(Ok(msg))?;
-                                    
-                                    recv_state = RecvState::ReadHead(Vec::with_capacity(8));
+                        if !recv_budget.as_mut().map_or(true, |budget| budget.consume(n)) {
+                            recv_tx.send(Err(Error::RecvBudgetExceeded))?;
+
+                            break 'work;
+                        }
+
+                        decoder.push(&read_buf[..n]);
+
+                        loop {
+                            match decoder.next_frame() {
+                                Ok(Some(Frame::Msg(frame))) => match bincode::deserialize(&frame) {
+                                    Ok(msg) => recv_tx.send(Ok(msg))?,
+
+                                    Err(err) => {
+                                        recv_tx.send(Err(err.into()))?;
+
+                                        break 'work;
+                                    }
+                                },
+
+                                Ok(Some(Frame::Control(frame))) => match bincode::deserialize(&frame) {
+                                    Ok(ControlFrame::Udp { port, token }) => if accept_udp && udp.is_none() {
+                                        // sem o canal udp, mensagens não confiáveis continuam pelo tcp
+                                        let channel = transport.tcp_stream()
+                                            .peer_addr()
+                                            .ok()
+                                            .and_then(|addr| UdpChannel::accept(SocketAddr::new(addr.ip(), port), token).ok());
+
+                                        if let Some(channel) = channel {
+                                            poll.register(channel.socket(), UDP_TOK, Ready::readable(), PollOpt::edge())?;
+
+                                            udp = Some(channel);
+                                        }
+                                    },
+
+                                    Err(err) => {
+                                        recv_tx.send(Err(err.into()))?;
+
+                                        break 'work;
+                                    }
                                 },
-                                
+
+                                Ok(None) => break,
+
                                 Err(err) => {
-                                    recv_tx.send(Err((*err).into()))?;
+                                    recv_tx.send(Err(err))?;
 
                                     break 'work;
                                 }
                             }
-                        } else {
-                            let left = *len - body.len();
-                            let mut buf = vec![0; left];
+                        }
+                    }
 
-                            match tcp_stream.read(&mut buf) {
-                                Ok(_) => body.append(&mut buf),
+                    // o handshake tls pode ter terminado durante a leitura, liberando a fila de envio
+                    if !send_buf.is_empty() || transport.has_pending() {
+                        if let Err(err) = flush(&mut transport, &mut send_buf) {
+                            recv_tx.send(Err(err))?;
 
-                                Err(err) => {
-                                    recv_tx.send(Err(err.into()))?;
+                            break 'work;
+                        }
+                    }
+                },
 
-                                    break 'work;
-                                }
+                UDP_TOK => if let Some(channel) = &mut udp {
+                    loop {
+                        let (n, deliver) = match channel.recv(&mut read_buf) {
+                            Ok(received) => received,
+
+                            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+
+                            // erros de datagramas anteriores, como icmp de porta inalcançável, não encerram a conexão
+                            Err(_) => break
+                        };
+
+                        if !recv_budget.as_mut().map_or(true, |budget| budget.consume(n)) {
+                            recv_tx.send(Err(Error::RecvBudgetExceeded))?;
+
+                            break 'work;
+                        }
+
+                        // datagramas inválidos são descartados como se tivessem sido perdidos
+                        if deliver {
+                            if let Ok(msg) = bincode::deserialize(&read_buf[DATAGRAM_HEADER_BYTES..n]) {
+                                recv_tx.send(Ok(msg))?;
                             }
                         }
                     }
@@ -519,63 +1008,304 @@ fn postbox_worker<S: PostSend, R: PostRecv>(
         }
     }
 
-    tcp_stream.shutdown(Shutdown::Both);
+    transport.shutdown();
 
     Ok(())
 }
 
-// testes
+/// corpo de um frame recebido
+#[derive(Debug, PartialEq)]
+enum Frame {
+    Msg(Vec<u8>),
+    Control(Vec<u8>)
+}
 
-#[test]
-fn connect() {
-    let srv_addr = ([127, 0, 0, 1], 12345);
-    
-    let mut postoffice = PostOffice::<u32, f32>::bind(srv_addr).unwrap();
+/// separa os frames recebidos, cada um precedido pelo seu tamanho como um `u32` little endian
+///
+/// frames maiores que `MAX_MSG_BYTES` são recusados antes de qualquer alocação para o corpo, e frames
+/// marcados com `COMPRESSED_FLAG` são descomprimidos antes de serem retornados
+struct FrameDecoder {
+    buf: Vec<u8>
+}
 
-    // deve-se iniciar com 0 conexões pendentes
-    thread::sleep(Duration::from_millis(250));
+impl FrameDecoder {
+    fn new() -> Self {
+        Self {
+            buf: Vec::new()
+        }
+    }
 
-    assert_eq!(postoffice.new_connections().len(), 0);
-    assert_eq!(postoffice.error(), None);
+    fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
 
-    let postbox = PostBox::<f32, u32>::to_server(srv_addr).unwrap();
+    /// obtém o próximo frame completo, ou `None` caso ainda faltem bytes
+    fn next_frame(&mut self) -> Result<Option<Frame>, Error> {
+        if self.buf.len() < HEADER_BYTES {
+            return Ok(None);
+        }
 
-    // agora com um postbox criado, deve-se ter 1 novo
-    thread::sleep(Duration::from_millis(250));
+        let header = u32::from_le_bytes(<[u8; HEADER_BYTES]>::try_from(&self.buf[..HEADER_BYTES]).unwrap());
 
-    let incoming = postoffice.new_connections();
+        let compressed = header & COMPRESSED_FLAG != 0;
+        let control = header & CONTROL_FLAG != 0;
+        let len = (header & LEN_MASK) as usize;
 
-    assert_eq!(incoming.len(), 1);
-    assert_eq!(postoffice.error(), None);
+        if len > MAX_MSG_BYTES {
+            return Err(Error::MsgSizeLimit {
+                size: len,
+                limit: MAX_MSG_BYTES
+            });
+        }
+
+        if self.buf.len() < HEADER_BYTES + len {
+            return Ok(None);
+        }
+
+        let frame = if compressed {
+            decompress(&self.buf[HEADER_BYTES..HEADER_BYTES + len])?
+        } else {
+            self.buf[HEADER_BYTES..HEADER_BYTES + len].to_vec()
+        };
+
+        self.buf.drain(..HEADER_BYTES + len);
+
+        Ok(Some(if control {
+            Frame::Control(frame)
+        } else {
+            Frame::Msg(frame)
+        }))
+    }
 }
 
-#[test]
-fn connection_count() {
-    let srv_addr = ([127, 0, 0, 1], 12346);
+/// descomprime o corpo de um frame, precedido pelo seu tamanho original como um `u32` little endian
+///
+/// o tamanho original é verificado antes da descompressão, assim um frame pequeno não pode alocar mais que `MAX_MSG_BYTES`
+fn decompress(body: &[u8]) -> Result<Vec<u8>, Error> {
+    if body.len() < HEADER_BYTES {
+        return Err(Error::InvalidFrame);
+    }
 
-    let mut postoffice = PostOffice::<u32, f32>::bind(srv_addr).unwrap();
-    let mut postboxes = Vec::new();
+    let len = u32::from_le_bytes(<[u8; HEADER_BYTES]>::try_from(&body[..HEADER_BYTES]).unwrap()) as usize;
 
-    // deve-se iniciar com 0 conexões pendentes
-    thread::sleep(Duration::from_millis(250));
+    if len > MAX_MSG_BYTES {
+        return Err(Error::MsgSizeLimit {
+            size: len,
+            limit: MAX_MSG_BYTES
+        });
+    }
 
-    assert_eq!(postoffice.new_connections().len(), 0);
-    assert_eq!(postoffice.error(), None);
+    let msg_bytes = lz4_flex::decompress(&body[HEADER_BYTES..], len).map_err(|_| Error::InvalidFrame)?;
 
-    for _ in 0..5 {
-        postboxes.push(PostBox::<f32, u32>::to_server(srv_addr).unwrap());
+    if msg_bytes.len() != len {
+        return Err(Error::InvalidFrame);
     }
 
-    // 10 postboxes criados, deve-se ter 10
-    thread::sleep(Duration::from_millis(3500));
+    Ok(msg_bytes)
+}
 
-    let incoming = postoffice.new_connections();
+/// limita quantos bytes uma conexão pode enviar, recuperando `rate` bytes por segundo até o máximo de `burst`
+struct ByteBudget {
+    rate: f64,
+    burst: f64,
 
-    assert_eq!(incoming.len(), 5);
-    assert_eq!(postoffice.error(), None);
+    available: f64,
+    last: Instant
 }
 
-#[test]
+impl ByteBudget {
+    fn new(rate: f64, burst: f64) -> Self {
+        Self {
+            rate,
+            burst,
+
+            available: burst,
+            last: Instant::now()
+        }
+    }
+
+    /// consome `n` bytes do orçamento, retornando `false` caso ele tenha se esgotado
+    fn consume(&mut self, n: usize) -> bool {
+        let now = Instant::now();
+
+        self.available = (self.available + now.duration_since(self.last).as_secs_f64() * self.rate).min(self.burst);
+        self.last = now;
+
+        self.available -= n as f64;
+
+        self.available >= 0.0
+    }
+}
+
+/// serializa uma mensagem em um frame, precedida pelo seu tamanho
+///
+/// com `compression` ativada, mensagens a partir de `COMPRESSION_THRESHOLD` bytes são comprimidas quando isso
+/// realmente as torna menores
+fn encode_frame<S: PostSend>(msg: &S, compression: bool) -> Result<Vec<u8>, Error> {
+    let msg_bytes = bincode::serialize(msg).map_err(Error::from)?;
+
+    if msg_bytes.len() > MAX_MSG_BYTES {
+        return Err(Error::MsgSizeLimit {
+            size: msg_bytes.len(),
+            limit: MAX_MSG_BYTES
+        });
+    }
+
+    let (header, body) = if compression && msg_bytes.len() >= COMPRESSION_THRESHOLD {
+        let compressed = lz4_flex::compress_prepend_size(&msg_bytes);
+
+        if compressed.len() < msg_bytes.len() {
+            (compressed.len() as u32 | COMPRESSED_FLAG, compressed)
+        } else {
+            (msg_bytes.len() as u32, msg_bytes)
+        }
+    } else {
+        (msg_bytes.len() as u32, msg_bytes)
+    };
+
+    let mut frame = Vec::with_capacity(HEADER_BYTES + body.len());
+
+    frame.extend_from_slice(&header.to_le_bytes());
+    frame.extend_from_slice(&body);
+
+    Ok(frame)
+}
+
+/// serializa um frame de controle, que nunca é comprimido
+fn encode_control(ctrl: &ControlFrame) -> Result<Vec<u8>, Error> {
+    let ctrl_bytes = bincode::serialize(ctrl).map_err(Error::from)?;
+
+    let mut frame = Vec::with_capacity(HEADER_BYTES + ctrl_bytes.len());
+
+    frame.extend_from_slice(&(ctrl_bytes.len() as u32 | CONTROL_FLAG).to_le_bytes());
+    frame.extend_from_slice(&ctrl_bytes);
+
+    Ok(frame)
+}
+
+/// envia mensagens não confiáveis pelo canal udp quando possível, e todas as outras pela fila do tcp
+fn queue_msg<S: PostSend>(msg: &S, compression: bool, udp: &mut Option<UdpChannel>, send_buf: &mut Vec<u8>) -> Result<(), Error> {
+    if msg.reliability() == Reliability::Unreliable {
+        if let Some(channel) = udp {
+            let msg_bytes = bincode::serialize(msg).map_err(Error::from)?;
+
+            if channel.send(msg.sequence(), &msg_bytes) {
+                return Ok(());
+            }
+        }
+    }
+
+    send_buf.extend_from_slice(&encode_frame(msg, compression)?);
+
+    Ok(())
+}
+
+/// escreve o máximo possível da fila de envio sem bloquear, mantendo o restante para o próximo evento de escrita
+fn flush(transport: &mut Transport, send_buf: &mut Vec<u8>) -> Result<(), Error> {
+    transport.write_pending()?;
+
+    let mut written = 0;
+
+    while written < send_buf.len() {
+        match transport.write(&send_buf[written..]) {
+            Ok(0) => return Err(Error::Disconnect),
+            Ok(n) => written += n,
+
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {},
+
+            Err(err) => return Err(err.into())
+        }
+    }
+
+    send_buf.drain(..written);
+
+    Ok(())
+}
+
+/// escreve toda a fila de envio, esperando o socket aceitar escrita até o tempo limite
+fn flush_blocking(transport: &mut Transport, send_buf: &mut Vec<u8>, timeout: Duration) -> Result<(), Error> {
+    let start = Instant::now();
+
+    while !send_buf.is_empty() || transport.has_pending() {
+        flush(transport, send_buf)?;
+
+        if start.elapsed() > timeout {
+            return Err(Error::Io(io::ErrorKind::TimedOut, String::from("a fila de envio não esvaziou a tempo")));
+        }
+
+        if !send_buf.is_empty() || transport.has_pending() {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    Ok(())
+}
+
+// testes
+
+#[cfg(test)]
+impl PostMsg for String {}
+
+#[cfg(test)]
+impl PostMsg for u32 {}
+
+#[cfg(test)]
+impl PostMsg for f32 {}
+
+#[cfg(test)]
+impl<T> PostMsg for Vec<T> {}
+
+#[test]
+fn connect() {
+    let srv_addr = ([127, 0, 0, 1], 12345);
+    
+    let mut postoffice = PostOffice::<u32, f32>::bind(srv_addr).unwrap();
+
+    // deve-se iniciar com 0 conexões pendentes
+    thread::sleep(Duration::from_millis(250));
+
+    assert_eq!(postoffice.new_connections().len(), 0);
+    assert_eq!(postoffice.error(), None);
+
+    let postbox = PostBox::<f32, u32>::to_server(srv_addr).unwrap();
+
+    // agora com um postbox criado, deve-se ter 1 novo
+    thread::sleep(Duration::from_millis(250));
+
+    let incoming = postoffice.new_connections();
+
+    assert_eq!(incoming.len(), 1);
+    assert_eq!(postoffice.error(), None);
+}
+
+#[test]
+fn connection_count() {
+    let srv_addr = ([127, 0, 0, 1], 12346);
+
+    let mut postoffice = PostOffice::<u32, f32>::bind(srv_addr).unwrap();
+    let mut postboxes = Vec::new();
+
+    // deve-se iniciar com 0 conexões pendentes
+    thread::sleep(Duration::from_millis(250));
+
+    assert_eq!(postoffice.new_connections().len(), 0);
+    assert_eq!(postoffice.error(), None);
+
+    for _ in 0..5 {
+        postboxes.push(PostBox::<f32, u32>::to_server(srv_addr).unwrap());
+    }
+
+    // 10 postboxes criados, deve-se ter 10
+    thread::sleep(Duration::from_millis(3500));
+
+    let incoming = postoffice.new_connections();
+
+    assert_eq!(incoming.len(), 5);
+    assert_eq!(postoffice.error(), None);
+}
+
+#[test]
 fn disconnect() {
     let srv_addr = ([127, 0, 0, 1], 12347);
     
@@ -591,7 +1321,7 @@ fn disconnect() {
         assert_eq!(incoming.len(), 1);
         assert_eq!(postoffice.error(), None);
 
-        incoming.next().unwrap();
+        incoming.next().unwrap()
     };
 
     // o postbox do client foi desconectado
@@ -601,8 +1331,7 @@ fn disconnect() {
     
     assert_eq!(incoming_msgs.len(), 0);
 
-    // todo
-    // assert_eq!(server_postbox.error(), Some(Error::Disconnect));
+    assert_eq!(server_postbox.error(), Some(Error::Disconnect));
 }
 
 #[test]
@@ -658,3 +1387,867 @@ fn server_to_client() {
     assert_eq!(incoming_msgs.next().unwrap(), 39999999);
     assert_eq!(incoming_msgs.next().unwrap(), 17);
 }
+
+#[test]
+fn wait_messages() {
+    let srv_addr = ([127, 0, 0, 1], 12363);
+
+    let mut po = PostOffice::<u32, u32>::bind(srv_addr).unwrap();
+    let mut client_pb = PostBox::<u32, u32>::to_server(srv_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut server_pb = po.new_connections().next().unwrap();
+
+    // sem mensagens, a espera termina no tempo limite
+    let start = Instant::now();
+
+    assert_eq!(client_pb.wait_messages(Duration::from_millis(100)).count(), 0);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    // uma mensagem que chega durante a espera a encerra antes do tempo limite
+    let sender = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+
+        server_pb.send(7);
+
+        server_pb
+    });
+
+    let start = Instant::now();
+
+    assert_eq!(client_pb.wait_messages(Duration::from_secs(5)).next(), Some(7));
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let _ = sender.join();
+}
+
+#[test]
+fn client_recv_unlimited() {
+    let srv_addr = ([127, 0, 0, 1], 12362);
+
+    let mut po = PostOffice::<String, u32>::bind(srv_addr).unwrap();
+    let mut client_pb = PostBox::<u32, String>::to_server(srv_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut server_pb = po.new_connections().next().unwrap();
+
+    // bem mais que o máximo acumulado do orçamento de bytes recebidos, enviado de uma só vez
+    let msg = "a".repeat(1 << 19);
+    let count = (RECV_BUDGET_BURST_BYTES as usize / msg.len()) * 2;
+
+    for _ in 0..count {
+        server_pb.send(msg.clone());
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut received = 0;
+
+    while received < count && Instant::now() < deadline {
+        received += client_pb.wait_messages(Duration::from_millis(100)).count();
+    }
+
+    // o orçamento protege apenas o servidor, então o client recebe tudo
+    assert_eq!(received, count);
+    assert_eq!(client_pb.error(), None);
+}
+
+#[test]
+fn slow_readers_are_dropped() {
+    let srv_addr = ([127, 0, 0, 1], 12365);
+
+    let mut po = PostOffice::<String, u32>::bind(srv_addr).unwrap();
+    let mut fast_pb = PostBox::<u32, String>::to_server(srv_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut fast_server_pb = po.new_connections().next().unwrap();
+
+    // um client que lê o que recebe pode receber bem mais que o limite da fila, desde que acompanhe os envios
+    let msg = "a".repeat(1 << 19);
+    let burst = (MAX_SEND_BUF_BYTES * 3 / 4) / msg.len();
+
+    for _ in 0..4 {
+        for _ in 0..burst {
+            fast_server_pb.send(msg.clone());
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut received = 0;
+
+        while received < burst && Instant::now() < deadline {
+            received += fast_pb.wait_messages(Duration::from_millis(100)).count();
+        }
+
+        assert_eq!(received, burst);
+    }
+
+    assert_eq!(fast_pb.error(), None);
+    assert_eq!(fast_server_pb.new_messages().count(), 0);
+    assert_eq!(fast_server_pb.error(), None);
+
+    // um client que nunca lê enche o socket, e o que sobra na fila acaba passando do limite
+    let _stalled = std::net::TcpStream::connect(srv_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut stalled_server_pb = po.new_connections().next().unwrap();
+
+    for _ in 0..(MAX_SEND_BUF_BYTES * 6) / msg.len() {
+        stalled_server_pb.send(msg.clone());
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+
+    while stalled_server_pb.error().is_none() && Instant::now() < deadline {
+        let _ = stalled_server_pb.wait_messages(Duration::from_millis(100)).count();
+    }
+
+    assert_eq!(stalled_server_pb.error(), Some(Error::SendBufferFull));
+}
+
+#[test]
+fn close_flushes_queue() {
+    let srv_addr = ([127, 0, 0, 1], 12364);
+
+    let mut po = PostOffice::<u32, u32>::bind(srv_addr).unwrap();
+    let mut client_pbs = (0..4).map(|_| PostBox::<u32, u32>::to_server(srv_addr).unwrap()).collect::<Vec<_>>();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut server_pbs = po.new_connections().collect::<Vec<_>>();
+
+    assert_eq!(server_pbs.len(), 4);
+
+    // todos os postboxes são fechados antes de serem descartados, então ninguém espera pelos outros
+    for (i, server_pb) in server_pbs.iter_mut().enumerate() {
+        server_pb.send(i as u32);
+        server_pb.close();
+    }
+
+    let start = Instant::now();
+
+    drop(server_pbs);
+
+    assert!(start.elapsed() < SHUTDOWN_FLUSH_TIMEOUT);
+
+    thread::sleep(Duration::from_millis(250));
+
+    // as mensagens na fila ainda chegam antes da conexão ser encerrada
+    let mut received = client_pbs
+        .iter_mut()
+        .flat_map(|client_pb| client_pb.new_messages().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    received.sort();
+
+    assert_eq!(received, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn stop_listening() {
+    let srv_addr = ([127, 0, 0, 1], 12350);
+
+    let mut po = PostOffice::<u32, f32>::bind(srv_addr).unwrap();
+    let mut client_pb = PostBox::<f32, u32>::to_server(srv_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut server_pb = po.new_connections().next().unwrap();
+
+    po.stop_listening();
+
+    thread::sleep(Duration::from_millis(250));
+
+    // novas conexões são recusadas depois que o postoffice para de escutar
+    let _refused_pb = PostBox::<f32, u32>::to_server(srv_addr);
+
+    thread::sleep(Duration::from_millis(250));
+
+    assert_eq!(po.new_connections().len(), 0);
+
+    // mas as conexões existentes continuam funcionando
+    server_pb.send(42);
+
+    thread::sleep(Duration::from_millis(250));
+
+    assert_eq!(client_pb.new_messages().next(), Some(42));
+    assert_eq!(po.shutdown(), Ok(()));
+}
+
+#[test]
+fn connection_limit_per_ip() {
+    let srv_addr = ([127, 0, 0, 1], 12351);
+
+    let mut po = PostOffice::<u32, f32>::bind_with_limits(srv_addr, ConnectionLimits {
+        max_per_ip: Some(2),
+        max_rate_per_ip: None
+    }).unwrap();
+
+    let mut postboxes = Vec::new();
+
+    for _ in 0..3 {
+        postboxes.push(PostBox::<f32, u32>::to_server(srv_addr).unwrap());
+    }
+
+    thread::sleep(Duration::from_millis(250));
+
+    // a terceira conexão do mesmo endereço é recusada
+    let incoming = po.new_connections().collect::<Vec<_>>();
+
+    assert_eq!(incoming.len(), 2);
+
+    // ao descartar uma conexão, a vaga é liberada
+    drop(incoming);
+
+    postboxes.push(PostBox::<f32, u32>::to_server(srv_addr).unwrap());
+
+    thread::sleep(Duration::from_millis(250));
+
+    assert_eq!(po.new_connections().len(), 1);
+}
+
+#[test]
+fn connection_rate_per_ip() {
+    let srv_addr = ([127, 0, 0, 1], 12352);
+
+    let mut po = PostOffice::<u32, f32>::bind_with_limits(srv_addr, ConnectionLimits {
+        max_per_ip: None,
+        max_rate_per_ip: Some((3, Duration::from_secs(60)))
+    }).unwrap();
+
+    let mut postboxes = Vec::new();
+
+    for _ in 0..5 {
+        postboxes.push(PostBox::<f32, u32>::to_server(srv_addr).unwrap());
+    }
+
+    thread::sleep(Duration::from_millis(250));
+
+    // apenas as três primeiras tentativas dentro do intervalo são aceitas
+    assert_eq!(po.new_connections().len(), 3);
+}
+
+#[test]
+fn frame_decoder_split_frames() {
+    let msgs = (0..64u32).map(|i| vec![i as u8; (i * 37 % 300) as usize]).collect::<Vec<_>>();
+
+    let bytes = msgs
+        .iter()
+        .flat_map(|msg| encode_frame(msg, false).unwrap())
+        .collect::<Vec<_>>();
+
+    // os mesmos bytes entregues em pedaços de tamanhos arbitrários devem produzir as mesmas mensagens
+    let mut seed = 42;
+    let mut decoder = FrameDecoder::new();
+    let mut decoded = Vec::new();
+    let mut offs = 0;
+
+    while offs < bytes.len() {
+        let len = (crate::util::test_rng(&mut seed) as usize % 17 + 1).min(bytes.len() - offs);
+
+        decoder.push(&bytes[offs..offs + len]);
+        offs += len;
+
+        while let Some(Frame::Msg(frame)) = decoder.next_frame().unwrap() {
+            decoded.push(bincode::deserialize::<Vec<u8>>(&frame).unwrap());
+        }
+    }
+
+    assert_eq!(decoded, msgs);
+}
+
+#[test]
+fn frame_decoder_rejects_oversized_header() {
+    let mut decoder = FrameDecoder::new();
+
+    decoder.push(&((MAX_MSG_BYTES + 1) as u32).to_le_bytes());
+
+    assert_eq!(decoder.next_frame(), Err(Error::MsgSizeLimit {
+        size: MAX_MSG_BYTES + 1,
+        limit: MAX_MSG_BYTES
+    }));
+
+    // o corpo nunca é alocado para um tamanho recusado
+    assert!(decoder.buf.capacity() < MAX_MSG_BYTES);
+}
+
+#[test]
+fn frame_decoder_arbitrary_bytes() {
+    let mut seed = 1337;
+
+    for _ in 0..256 {
+        let mut decoder = FrameDecoder::new();
+
+        for _ in 0..64 {
+            let len = crate::util::test_rng(&mut seed) as usize % 64;
+            let bytes = (0..len).map(|_| crate::util::test_rng(&mut seed) as u8).collect::<Vec<_>>();
+
+            decoder.push(&bytes);
+
+            // bytes arbitrários podem produzir frames ou erros, mas nunca pânico ou crescimento sem limites
+            match decoder.next_frame() {
+                Ok(Some(Frame::Msg(frame))) | Ok(Some(Frame::Control(frame))) => {
+                    let _ = bincode::deserialize::<String>(&frame);
+                },
+
+                Ok(None) => {},
+                Err(_) => break
+            }
+
+            assert!(decoder.buf.len() <= HEADER_BYTES + MAX_MSG_BYTES);
+        }
+    }
+}
+
+#[test]
+fn postbox_arbitrary_bytes() {
+    use std::io::Write;
+
+    let srv_addr = ([127, 0, 0, 1], 12353);
+
+    let mut po = PostOffice::<u32, String>::bind(srv_addr).unwrap();
+    let mut seed = 7;
+
+    for _ in 0..8 {
+        let mut stream = std::net::TcpStream::connect(srv_addr).unwrap();
+
+        thread::sleep(Duration::from_millis(100));
+
+        let mut server_pb = po.new_connections().next().unwrap();
+
+        let len = crate::util::test_rng(&mut seed) as usize % 4096;
+        let bytes = (0..len).map(|_| crate::util::test_rng(&mut seed) as u8).collect::<Vec<_>>();
+
+        let _ = stream.write_all(&bytes);
+
+        drop(stream);
+
+        thread::sleep(Duration::from_millis(100));
+
+        // o postbox deve apenas reportar um erro, e o servidor continua funcionando
+        let _ = server_pb.new_messages().count();
+
+        assert!(server_pb.error().is_some());
+    }
+
+    assert_eq!(po.error(), None);
+}
+
+/// um chunk de terreno típico: pedra embaixo, uma camada de grama seguindo um relevo suave e ar em cima
+#[cfg(test)]
+fn terrain_chunk() -> crate::terrain::TerrainChunk {
+    use vek::*;
+
+    use crate::{
+        terrain::{
+            Block,
+
+            TerrainChunk,
+            TerrainChunkMeta,
+            TerrainChunkSize
+        },
+
+        vol::{
+            Vox,
+            VolSize,
+
+            WriteVol
+        }
+    };
+
+    let size = TerrainChunkSize::SIZE;
+    let mut chunk = TerrainChunk::filled(Block::empty(), TerrainChunkMeta::void());
+
+    for x in 0..size.x as i32 {
+        for y in 0..size.y as i32 {
+            let height = 12.0 + (x as f32 * 0.3).sin() * 4.0 + (y as f32 * 0.2).cos() * 3.0;
+
+            for z in 0..size.z as i32 {
+                let block = if (z as f32) < height - 3.0 {
+                    Block::new(1, Rgb::new(128, 128, 128))
+                } else if (z as f32) < height {
+                    Block::new(2, Rgb::new(40, 160 + (x % 4) as u8, 40))
+                } else {
+                    continue;
+                };
+
+                chunk.set(Vec3::new(x, y, z), block).unwrap();
+            }
+        }
+    }
+
+    chunk
+}
+
+#[cfg(test)]
+impl PostMsg for crate::terrain::TerrainChunk {}
+
+#[test]
+fn compressed_frames() {
+    let chunk = terrain_chunk();
+
+    let raw = encode_frame(&chunk, false).unwrap();
+    let compressed = encode_frame(&chunk, true).unwrap();
+
+    // terreno se repete bastante e deve encolher consideravelmente
+    assert!(compressed.len() * 4 < raw.len());
+
+    let mut decoder = FrameDecoder::new();
+
+    decoder.push(&compressed);
+    decoder.push(&raw);
+
+    for _ in 0..2 {
+        let frame = decoder.next_frame().unwrap().unwrap();
+
+        assert_eq!(frame, Frame::Msg(bincode::serialize(&chunk).unwrap()));
+    }
+
+    assert_eq!(decoder.next_frame(), Ok(None));
+}
+
+#[test]
+fn small_frames_stay_uncompressed() {
+    let msg = String::from("olá");
+
+    assert_eq!(encode_frame(&msg, true).unwrap(), encode_frame(&msg, false).unwrap());
+
+    // dados que não encolhem também são enviados sem compressão
+    let mut seed = 3;
+    let noise = (0..4096).map(|_| crate::util::test_rng(&mut seed) as u8).collect::<Vec<_>>();
+    let frame = encode_frame(&noise, true).unwrap();
+
+    assert_eq!(frame[HEADER_BYTES - 1] & 0x80, 0);
+}
+
+#[test]
+fn frame_decoder_rejects_compression_bomb() {
+    // um corpo pequeno que declara um tamanho descomprimido acima do máximo
+    let mut body = ((MAX_MSG_BYTES + 1) as u32).to_le_bytes().to_vec();
+    body.extend_from_slice(&[0; 16]);
+
+    let mut decoder = FrameDecoder::new();
+
+    decoder.push(&(body.len() as u32 | COMPRESSED_FLAG).to_le_bytes());
+    decoder.push(&body);
+
+    assert_eq!(decoder.next_frame(), Err(Error::MsgSizeLimit {
+        size: MAX_MSG_BYTES + 1,
+        limit: MAX_MSG_BYTES
+    }));
+}
+
+#[test]
+fn compressed_postbox() {
+    let srv_addr = ([127, 0, 0, 1], 12354);
+
+    let mut po = PostOffice::<crate::terrain::TerrainChunk, String>::bind(srv_addr).unwrap();
+    let mut client_pb = PostBox::<String, crate::terrain::TerrainChunk>::to_server(srv_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut server_pb = po.new_connections().next().unwrap();
+
+    // apenas o servidor comprime, o client continua enviando frames simples
+    server_pb.set_compression(true);
+
+    let chunk = terrain_chunk();
+
+    server_pb.send(chunk.clone());
+    client_pb.send(String::from("recebido"));
+
+    thread::sleep(Duration::from_millis(250));
+
+    let recv = client_pb.new_messages().next().unwrap();
+
+    assert_eq!(bincode::serialize(&recv).unwrap(), bincode::serialize(&chunk).unwrap());
+
+    assert_eq!(server_pb.new_messages().next(), Some(String::from("recebido")));
+}
+
+/// gera um certificado autoassinado para `localhost`, retornando o certificado e a chave privada em formato pem
+#[cfg(test)]
+fn test_cert() -> (String, String) {
+    let cert = rcgen::generate_simple_self_signed(vec![String::from("localhost")]).unwrap();
+
+    (cert.serialize_pem().unwrap(), cert.serialize_private_key_pem())
+}
+
+#[test]
+fn tls_client_to_server() {
+    let srv_addr = ([127, 0, 0, 1], 12355);
+
+    let (cert_pem, key_pem) = test_cert();
+
+    let server_tls = ServerTls::from_pem(cert_pem.as_bytes(), key_pem.as_bytes()).unwrap();
+    let client_tls = ClientTls::trust_pem(cert_pem.as_bytes(), "localhost").unwrap();
+
+    let mut po = PostOffice::<String, String>::bind_tls(srv_addr, ConnectionLimits::default(), server_tls).unwrap();
+    let mut client_pb = PostBox::<String, String>::to_server_tls(srv_addr, &client_tls).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut server_pb = po.new_connections().next().unwrap();
+
+    // mensagens enviadas antes do fim do handshake esperam por ele
+    client_pb.send(String::from("foo"));
+    server_pb.send(String::from("bar"));
+
+    thread::sleep(Duration::from_millis(250));
+
+    assert_eq!(server_pb.new_messages().next(), Some(String::from("foo")));
+    assert_eq!(client_pb.new_messages().next(), Some(String::from("bar")));
+}
+
+#[test]
+fn tls_untrusted_certificate() {
+    let srv_addr = ([127, 0, 0, 1], 12356);
+
+    let (cert_pem, key_pem) = test_cert();
+    let (other_cert_pem, _) = test_cert();
+
+    let server_tls = ServerTls::from_pem(cert_pem.as_bytes(), key_pem.as_bytes()).unwrap();
+
+    // o client confia em outro certificado, então o handshake deve falhar
+    let client_tls = ClientTls::trust_pem(other_cert_pem.as_bytes(), "localhost").unwrap();
+
+    let mut po = PostOffice::<String, String>::bind_tls(srv_addr, ConnectionLimits::default(), server_tls).unwrap();
+    let mut client_pb = PostBox::<String, String>::to_server_tls(srv_addr, &client_tls).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut server_pb = po.new_connections().next().unwrap();
+
+    client_pb.send(String::from("segredo"));
+
+    thread::sleep(Duration::from_millis(250));
+
+    assert_eq!(client_pb.new_messages().count(), 0);
+    match client_pb.error() {
+        Some(Error::Tls(_)) => {},
+
+        err => panic!("erro inesperado {:?}", err)
+    }
+
+    assert_eq!(server_pb.new_messages().count(), 0);
+    assert!(server_pb.error().is_some());
+}
+
+#[test]
+fn tls_invalid_config() {
+    assert!(ServerTls::from_pem(b"nada", b"nada").is_err());
+    assert!(ClientTls::trust_pem(b"nada", "localhost").is_err());
+
+    let (cert_pem, _) = test_cert();
+
+    assert!(ClientTls::trust_pem(cert_pem.as_bytes(), "nome inválido").is_err());
+}
+
+#[test]
+fn control_frames() {
+    let mut decoder = FrameDecoder::new();
+
+    decoder.push(&encode_control(&ControlFrame::Udp { port: 1234, token: 42 }).unwrap());
+    decoder.push(&encode_frame(&String::from("foo"), false).unwrap());
+
+    match decoder.next_frame() {
+        Ok(Some(Frame::Control(frame))) => match bincode::deserialize(&frame).unwrap() {
+            ControlFrame::Udp { port, token } => assert_eq!((port, token), (1234, 42))
+        },
+
+        frame => panic!("frame inesperado {:?}", frame)
+    }
+
+    assert_eq!(decoder.next_frame(), Ok(Some(Frame::Msg(bincode::serialize("foo").unwrap()))));
+}
+
+#[test]
+fn udp_channel_sequence() {
+    let mut server = UdpChannel::offer([127, 0, 0, 1].into()).unwrap();
+    let server_addr = SocketAddr::from(([127, 0, 0, 1], server.port().unwrap()));
+
+    // o servidor não conhece o endereço do client até receber o datagrama de apresentação
+    assert!(!server.send(0, b"cedo"));
+
+    let mut client = UdpChannel::accept(server_addr, server.token()).unwrap();
+    let mut buf = vec![0; 2048];
+
+    thread::sleep(Duration::from_millis(100));
+
+    assert_eq!(server.recv(&mut buf).unwrap(), (DATAGRAM_HEADER_BYTES, false));
+
+    assert!(client.send(0, b"um"));
+    assert!(server.send(0, b"dois"));
+
+    thread::sleep(Duration::from_millis(100));
+
+    let (n, deliver) = server.recv(&mut buf).unwrap();
+    assert!(deliver);
+    assert_eq!(&buf[DATAGRAM_HEADER_BYTES..n], b"um");
+
+    let (n, deliver) = client.recv(&mut buf).unwrap();
+    assert!(deliver);
+    assert_eq!(&buf[DATAGRAM_HEADER_BYTES..n], b"dois");
+
+    // datagramas com o token errado são descartados
+    let intruder = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let mut forged = (server.token() ^ 1).to_le_bytes().to_vec();
+    forged.extend_from_slice(&0u64.to_le_bytes());
+    forged.extend_from_slice(&100u32.to_le_bytes());
+    forged.extend_from_slice(b"falso");
+
+    intruder.send_to(&forged, server_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(100));
+
+    assert_eq!(server.recv(&mut buf).unwrap().1, false);
+
+    // assim como datagramas repetidos ou atrasados
+    let mut replayed = server.token().to_le_bytes().to_vec();
+    replayed.extend_from_slice(&0u64.to_le_bytes());
+    replayed.extend_from_slice(&1u32.to_le_bytes());
+    replayed.extend_from_slice(b"um");
+
+    client.socket().send_to(&replayed, &server_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(100));
+
+    assert_eq!(server.recv(&mut buf).unwrap().1, false);
+
+    // cada sequência é independente, então datagramas de outra chave não descartam os da primeira
+    for _ in 0..3 {
+        assert!(client.send(7, b"sete"));
+    }
+
+    assert!(client.send(0, b"zero"));
+
+    thread::sleep(Duration::from_millis(100));
+
+    let delivered = (0..4)
+        .map(|_| {
+            let (n, deliver) = server.recv(&mut buf).unwrap();
+
+            (buf[DATAGRAM_HEADER_BYTES..n].to_vec(), deliver)
+        })
+        .filter(|&(_, deliver)| deliver)
+        .map(|(payload, _)| payload)
+        .collect::<Vec<_>>();
+
+    assert_eq!(delivered.len(), 4);
+    assert_eq!(delivered.last().unwrap(), b"zero");
+
+    // mensagens grandes demais para um datagrama precisam ir pelo tcp
+    assert!(!client.send(0, &vec![0; 4096]));
+}
+
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+enum TestMsg {
+    Reliable(String),
+    Unreliable(u32)
+}
+
+#[cfg(test)]
+impl PostMsg for TestMsg {
+    fn reliability(&self) -> Reliability {
+        match self {
+            TestMsg::Reliable(_) => Reliability::Reliable,
+            TestMsg::Unreliable(_) => Reliability::Unreliable
+        }
+    }
+}
+
+#[test]
+fn unreliable_messages() {
+    let srv_addr = ([127, 0, 0, 1], 12357);
+
+    let mut po = PostOffice::<TestMsg, TestMsg>::bind(srv_addr).unwrap();
+    let mut client_pb = PostBox::<TestMsg, TestMsg>::to_server(srv_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut server_pb = po.new_connections().next().unwrap();
+
+    // as duas classes de mensagens chegam, com ou sem o canal udp pronto
+    for i in 0..4 {
+        client_pb.send(TestMsg::Unreliable(i));
+        client_pb.send(TestMsg::Reliable(format!("client {}", i)));
+
+        server_pb.send(TestMsg::Unreliable(i));
+        server_pb.send(TestMsg::Reliable(format!("servidor {}", i)));
+
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    let server_msgs = server_pb.new_messages().collect::<Vec<_>>();
+    let client_msgs = client_pb.new_messages().collect::<Vec<_>>();
+
+    for i in 0..4 {
+        assert!(server_msgs.contains(&TestMsg::Unreliable(i)));
+        assert!(server_msgs.contains(&TestMsg::Reliable(format!("client {}", i))));
+
+        assert!(client_msgs.contains(&TestMsg::Unreliable(i)));
+        assert!(client_msgs.contains(&TestMsg::Reliable(format!("servidor {}", i))));
+    }
+
+    // mensagens confiáveis mantêm a ordem
+    let reliable = client_msgs
+        .into_iter()
+        .filter(|msg| match msg {
+            TestMsg::Reliable(_) => true,
+            TestMsg::Unreliable(_) => false
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(reliable, (0..4).map(|i| TestMsg::Reliable(format!("servidor {}", i))).collect::<Vec<_>>());
+}
+
+#[test]
+fn bidirectional_order() {
+    let srv_addr = ([127, 0, 0, 1], 12358);
+
+    let mut po = PostOffice::<u32, u32>::bind(srv_addr).unwrap();
+    let mut client_pb = PostBox::<u32, u32>::to_server(srv_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut server_pb = po.new_connections().next().unwrap();
+
+    // os dois lados enviam ao mesmo tempo, e cada um recebe tudo na ordem em que foi enviado
+    for i in 0..1000 {
+        client_pb.send(i);
+        server_pb.send(i * 2);
+    }
+
+    thread::sleep(Duration::from_millis(500));
+
+    assert_eq!(server_pb.new_messages().collect::<Vec<_>>(), (0..1000).collect::<Vec<_>>());
+    assert_eq!(client_pb.new_messages().collect::<Vec<_>>(), (0..1000).map(|i| i * 2).collect::<Vec<_>>());
+
+    assert_eq!(server_pb.error(), None);
+    assert_eq!(client_pb.error(), None);
+}
+
+#[test]
+fn invalid_msg_cause() {
+    let srv_addr = ([127, 0, 0, 1], 12359);
+
+    let mut po = PostOffice::<u32, String>::bind(srv_addr).unwrap();
+
+    // um `u32` não possui bytes suficientes para ser lido como uma `String`
+    let mut client_pb = PostBox::<u32, u32>::to_server(srv_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut server_pb = po.new_connections().next().unwrap();
+
+    client_pb.send(7);
+
+    thread::sleep(Duration::from_millis(250));
+
+    assert_eq!(server_pb.new_messages().count(), 0);
+
+    match server_pb.error() {
+        Some(err @ Error::Serde(_)) => assert!(err.is_protocol_violation()),
+
+        err => panic!("erro inesperado {:?}", err)
+    }
+}
+
+#[test]
+fn oversized_msg_cause() {
+    let srv_addr = ([127, 0, 0, 1], 12360);
+
+    let mut po = PostOffice::<Vec<u8>, u32>::bind(srv_addr).unwrap();
+    let mut client_pb = PostBox::<u32, Vec<u8>>::to_server(srv_addr).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    let mut server_pb = po.new_connections().next().unwrap();
+
+    // o limite é verificado antes do envio, e o erro é reportado por quem tentou enviar
+    server_pb.send(vec![0; MAX_MSG_BYTES + 1]);
+
+    thread::sleep(Duration::from_millis(250));
+
+    assert_eq!(server_pb.new_messages().count(), 0);
+
+    match server_pb.error() {
+        Some(Error::MsgSizeLimit { limit, .. }) => assert_eq!(limit, MAX_MSG_BYTES),
+
+        err => panic!("erro inesperado {:?}", err)
+    }
+
+    assert_eq!(client_pb.new_messages().count(), 0);
+}
+
+#[test]
+fn io_error_cause() {
+    // nenhum servidor escuta nessa porta, então a conexão é recusada
+    let mut client_pb = PostBox::<u32, u32>::to_server(([127, 0, 0, 1], 12361)).unwrap();
+
+    thread::sleep(Duration::from_millis(250));
+
+    assert_eq!(client_pb.new_messages().count(), 0);
+
+    match client_pb.error() {
+        Some(Error::Io(kind, _)) => assert_eq!(kind, io::ErrorKind::ConnectionRefused),
+
+        err => panic!("erro inesperado {:?}", err)
+    }
+}
+
+// benchmarks
+
+#[bench]
+fn bench_encode_terrain_raw(b: &mut test::Bencher) {
+    let chunk = terrain_chunk();
+
+    b.iter(|| encode_frame(&chunk, false).unwrap());
+}
+
+#[bench]
+fn bench_encode_terrain_compressed(b: &mut test::Bencher) {
+    let chunk = terrain_chunk();
+
+    b.iter(|| encode_frame(&chunk, true).unwrap());
+}
+
+#[bench]
+fn bench_decode_terrain_raw(b: &mut test::Bencher) {
+    let frame = encode_frame(&terrain_chunk(), false).unwrap();
+
+    // inclui a desserialização do chunk, como acontece ao receber a mensagem
+    b.iter(|| {
+        let mut decoder = FrameDecoder::new();
+
+        decoder.push(&frame);
+
+        match decoder.next_frame().unwrap().unwrap() {
+            Frame::Msg(bytes) => bincode::deserialize::<crate::terrain::TerrainChunk>(&bytes).unwrap(),
+
+            frame => panic!("frame inesperado {:?}", frame)
+        }
+    });
+}
+
+#[bench]
+fn bench_decode_terrain_compressed(b: &mut test::Bencher) {
+    let frame = encode_frame(&terrain_chunk(), true).unwrap();
+
+    // inclui a desserialização do chunk, como acontece ao receber a mensagem
+    b.iter(|| {
+        let mut decoder = FrameDecoder::new();
+
+        decoder.push(&frame);
+
+        match decoder.next_frame().unwrap().unwrap() {
+            Frame::Msg(bytes) => bincode::deserialize::<crate::terrain::TerrainChunk>(&bytes).unwrap(),
+
+            frame => panic!("frame inesperado {:?}", frame)
+        }
+    });
+}
@@ -0,0 +1,274 @@
+use std::{
+    fs,
+
+    sync::Arc,
+
+    io::{
+        self,
+
+        BufReader,
+        Read,
+        Write
+    },
+
+    net::Shutdown
+};
+
+use mio::net::TcpStream;
+
+use rustls::{
+    internal::pemfile,
+
+    Certificate,
+    ClientConfig,
+    ClientSession,
+    NoClientAuth,
+    PrivateKey,
+    ServerConfig,
+    ServerSession,
+    Session,
+    TLSError
+};
+
+use webpki::DNSNameRef;
+
+use super::post::Error;
+
+/// certificado e chave privada que o servidor apresenta aos clients
+#[derive(Clone)]
+pub struct ServerTls {
+    config: Arc<ServerConfig>
+}
+
+impl ServerTls {
+    /// cria a configuração a partir de uma cadeia de certificados e uma chave privada (pkcs8 ou rsa) em formato pem
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<Self, Error> {
+        let certs = parse_certs(cert_pem)?;
+        let key = parse_key(key_pem)?;
+
+        let mut config = ServerConfig::new(NoClientAuth::new());
+
+        config
+            .set_single_cert(certs, key)
+            .map_err(|err| Error::Tls(err.to_string()))?;
+
+        Ok(Self {
+            config: Arc::new(config)
+        })
+    }
+
+    pub fn from_pem_files(cert_file: &str, key_file: &str) -> Result<Self, Error> {
+        Self::from_pem(&fs::read(cert_file)?, &fs::read(key_file)?)
+    }
+}
+
+/// certificados em que o client confia e o nome esperado no certificado do servidor
+#[derive(Clone)]
+pub struct ClientTls {
+    config: Arc<ClientConfig>,
+    server_name: String
+}
+
+impl ClientTls {
+    /// confia nas autoridades certificadoras públicas conhecidas
+    pub fn webpki_roots(server_name: &str) -> Result<Self, Error> {
+        let mut config = ClientConfig::new();
+
+        config.root_store.add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+
+        Self::with_config(config, server_name)
+    }
+
+    /// confia apenas nos certificados em formato pem fornecidos, como o certificado autoassinado de um servidor local
+    pub fn trust_pem(cert_pem: &[u8], server_name: &str) -> Result<Self, Error> {
+        let mut config = ClientConfig::new();
+
+        for cert in parse_certs(cert_pem)? {
+            config.root_store
+                .add(&cert)
+                .map_err(|err| Error::Tls(format!("certificado não confiável: {:?}", err)))?;
+        }
+
+        Self::with_config(config, server_name)
+    }
+
+    pub fn trust_pem_file(cert_file: &str, server_name: &str) -> Result<Self, Error> {
+        Self::trust_pem(&fs::read(cert_file)?, server_name)
+    }
+
+    fn with_config(config: ClientConfig, server_name: &str) -> Result<Self, Error> {
+        // nomes inválidos são recusados aqui em vez de falharem apenas na conexão
+        DNSNameRef::try_from_ascii_str(server_name).map_err(|_| invalid_name(server_name))?;
+
+        Ok(Self {
+            config: Arc::new(config),
+            server_name: server_name.to_string()
+        })
+    }
+}
+
+fn parse_certs(pem: &[u8]) -> Result<Vec<Certificate>, Error> {
+    let certs = pemfile::certs(&mut BufReader::new(pem)).map_err(|_| Error::Tls(String::from("certificado pem inválido")))?;
+
+    if certs.is_empty() {
+        return Err(Error::Tls(String::from("nenhum certificado encontrado")));
+    }
+
+    Ok(certs)
+}
+
+fn parse_key(pem: &[u8]) -> Result<PrivateKey, Error> {
+    let mut keys = pemfile::pkcs8_private_keys(&mut BufReader::new(pem))
+        .map_err(|_| Error::Tls(String::from("chave privada pem inválida")))?;
+
+    if keys.is_empty() {
+        keys = pemfile::rsa_private_keys(&mut BufReader::new(pem))
+            .map_err(|_| Error::Tls(String::from("chave privada pem inválida")))?;
+    }
+
+    keys.into_iter().next().ok_or(Error::Tls(String::from("nenhuma chave privada encontrada")))
+}
+
+/// o socket de uma conexão, opcionalmente envolvido por uma sessão tls
+///
+/// leituras e escritas se comportam como as do `TcpStream` não bloqueante: `WouldBlock` indica que é preciso esperar
+/// o próximo evento do socket, e o handshake tls avança sozinho durante elas
+pub(crate) struct Transport {
+    tcp_stream: TcpStream,
+    session: Option<Box<dyn Session>>
+}
+
+impl Transport {
+    pub fn plain(tcp_stream: TcpStream) -> Self {
+        Self {
+            tcp_stream,
+            session: None
+        }
+    }
+
+    pub fn client(tcp_stream: TcpStream, tls: &ClientTls) -> Result<Self, Error> {
+        let server_name = DNSNameRef::try_from_ascii_str(&tls.server_name).map_err(|_| invalid_name(&tls.server_name))?;
+
+        Ok(Self {
+            tcp_stream,
+            session: Some(Box::new(ClientSession::new(&tls.config, server_name)))
+        })
+    }
+
+    pub fn server(tcp_stream: TcpStream, tls: &ServerTls) -> Self {
+        Self {
+            tcp_stream,
+            session: Some(Box::new(ServerSession::new(&tls.config)))
+        }
+    }
+
+    pub fn tcp_stream(&self) -> &TcpStream {
+        &self.tcp_stream
+    }
+
+    /// lê dados já descriptografados, retornando `Ok(0)` apenas quando a conexão foi fechada
+    pub fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let session = match &mut self.session {
+            Some(session) => session,
+
+            None => return self.tcp_stream.read(buf)
+        };
+
+        loop {
+            let n = session.read(buf)?;
+
+            if n > 0 {
+                return Ok(n);
+            }
+
+            if session.read_tls(&mut self.tcp_stream)? == 0 {
+                return Ok(0);
+            }
+
+            session
+                .process_new_packets()
+                .map_err(tls_error)?;
+
+            // respostas do handshake precisam ser enviadas mesmo sem dados da aplicação
+            write_tls(session, &mut self.tcp_stream)?;
+        }
+    }
+
+    /// escreve dados da aplicação, retornando `WouldBlock` enquanto a sessão ainda possui dados criptografados pendentes
+    pub fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let session = match &mut self.session {
+            Some(session) => session,
+
+            None => return self.tcp_stream.write(buf)
+        };
+
+        write_tls(session, &mut self.tcp_stream)?;
+
+        if session.wants_write() {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+
+        // a sessão limita quantos dados aceita antes do fim do handshake
+        let n = session.write(buf)?;
+
+        write_tls(session, &mut self.tcp_stream)?;
+
+        if n == 0 {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+
+        Ok(n)
+    }
+
+    /// envia os dados criptografados pendentes que couberem no socket
+    pub fn write_pending(&mut self) -> io::Result<()> {
+        match &mut self.session {
+            Some(session) => write_tls(session, &mut self.tcp_stream),
+
+            None => Ok(())
+        }
+    }
+
+    /// verifica se ainda existem dados criptografados esperando o socket aceitar escrita
+    pub fn has_pending(&self) -> bool {
+        self.session
+            .as_ref()
+            .map(|session| session.wants_write())
+            .unwrap_or(false)
+    }
+
+    /// avisa o outro lado que a conexão será fechada e fecha o socket
+    pub fn shutdown(&mut self) {
+        if let Some(session) = &mut self.session {
+            session.send_close_notify();
+
+            let _ = write_tls(session, &mut self.tcp_stream);
+        }
+
+        let _ = self.tcp_stream.shutdown(Shutdown::Both);
+    }
+}
+
+fn write_tls(session: &mut Box<dyn Session>, tcp_stream: &mut TcpStream) -> io::Result<()> {
+    while session.wants_write() {
+        match session.write_tls(tcp_stream) {
+            Ok(0) => break,
+            Ok(_) => {},
+
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {},
+
+            Err(err) => return Err(err)
+        }
+    }
+
+    Ok(())
+}
+
+fn invalid_name(server_name: &str) -> Error {
+    Error::Tls(format!("nome de servidor inválido '{}'", server_name))
+}
+
+fn tls_error(err: TLSError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
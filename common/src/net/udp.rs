@@ -0,0 +1,207 @@
+use std::{
+    io,
+
+    net::{
+        IpAddr,
+        Ipv4Addr,
+        Ipv6Addr,
+        SocketAddr
+    },
+
+    collections::{
+        HashMap,
+
+        hash_map::RandomState
+    },
+
+    hash::{
+        BuildHasher,
+        Hasher
+    },
+
+    time::{
+        SystemTime,
+        UNIX_EPOCH
+    },
+
+    convert::TryFrom
+};
+
+use mio::net::UdpSocket;
+
+use super::post::Error;
+
+// token da conexão (u64), chave da sequência (u64) e número de sequência (u32)
+pub(crate) const DATAGRAM_HEADER_BYTES: usize = 20;
+
+// sequências recebidas que um canal acompanha, para que o outro lado não possa esgotar a memória com chaves novas
+const MAX_RECV_SEQUENCES: usize = 1 << 16;
+
+// maior datagrama enviado, abaixo do mtu comum para evitar fragmentação
+pub(crate) const MAX_DATAGRAM_BYTES: usize = 1200;
+
+/// canal udp de um postbox, usado para mensagens que podem ser perdidas ou chegar fora de ordem
+///
+/// cada datagrama carrega o token da conexão, anunciado pelo servidor através do tcp, e um número de sequência da
+/// chave da mensagem. apenas datagramas mais novos que o último entregue com a mesma chave são aceitos, então mensagens
+/// atrasadas são descartadas em vez de sobrescreverem estados mais recentes, sem que uma chave descarte as outras
+pub(crate) struct UdpChannel {
+    socket: UdpSocket,
+    token: u64,
+
+    // o servidor só conhece o endereço do client depois do primeiro datagrama válido, o que funciona mesmo atrás de nat
+    peer: Option<SocketAddr>,
+    learn_peer: bool,
+
+    send_seq: HashMap<u64, u32>,
+    recv_seq: HashMap<u64, u32>
+}
+
+impl UdpChannel {
+    /// cria o canal do lado do servidor, com um token novo a ser anunciado ao client
+    pub fn offer(local_ip: IpAddr) -> Result<Self, Error> {
+        Ok(Self {
+            socket: UdpSocket::bind(&SocketAddr::new(local_ip, 0))?,
+            token: new_token(),
+
+            peer: None,
+            learn_peer: true,
+
+            send_seq: HashMap::new(),
+            recv_seq: HashMap::new()
+        })
+    }
+
+    /// cria o canal do lado do client, a partir da porta e do token anunciados pelo servidor
+    pub fn accept(peer: SocketAddr, token: u64) -> Result<Self, Error> {
+        let local_ip = match peer {
+            SocketAddr::V4(_) => IpAddr::from(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::from(Ipv6Addr::UNSPECIFIED)
+        };
+
+        let mut channel = Self {
+            socket: UdpSocket::bind(&SocketAddr::new(local_ip, 0))?,
+            token,
+
+            peer: Some(peer),
+            learn_peer: false,
+
+            send_seq: HashMap::new(),
+            recv_seq: HashMap::new()
+        };
+
+        // um datagrama vazio apresenta o endereço do client ao servidor
+        let _ = channel.send_raw(0, 0, &[]);
+
+        Ok(channel)
+    }
+
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    pub fn port(&self) -> Result<u16, Error> {
+        Ok(self.socket.local_addr()?.port())
+    }
+
+    pub fn token(&self) -> u64 {
+        self.token
+    }
+
+    /// envia o payload em um datagrama da sequência `key`, retornando `false` caso ele precise ir pelo tcp
+    ///
+    /// isso acontece enquanto o endereço do outro lado não é conhecido ou quando o payload não cabe em um datagrama
+    pub fn send(&mut self, key: u64, payload: &[u8]) -> bool {
+        if self.peer.is_none() || DATAGRAM_HEADER_BYTES + payload.len() > MAX_DATAGRAM_BYTES {
+            return false;
+        }
+
+        let seq = self.send_seq.entry(key).or_insert(0);
+
+        *seq = seq.wrapping_add(1);
+
+        // datagramas que não cabem no socket agora são perdidos, como qualquer outro datagrama
+        let seq = *seq;
+        let _ = self.send_raw(key, seq, payload);
+
+        true
+    }
+
+    fn send_raw(&mut self, key: u64, seq: u32, payload: &[u8]) -> io::Result<()> {
+        let peer = match self.peer {
+            Some(peer) => peer,
+
+            None => return Ok(())
+        };
+
+        let mut datagram = Vec::with_capacity(DATAGRAM_HEADER_BYTES + payload.len());
+
+        datagram.extend_from_slice(&self.token.to_le_bytes());
+        datagram.extend_from_slice(&key.to_le_bytes());
+        datagram.extend_from_slice(&seq.to_le_bytes());
+        datagram.extend_from_slice(payload);
+
+        self.socket.send_to(&datagram, &peer).map(|_| ())
+    }
+
+    /// lê um datagrama, retornando quantos bytes foram recebidos e se `buf[DATAGRAM_HEADER_BYTES..n]` deve ser entregue
+    ///
+    /// datagramas de outros endereços, com o token errado, duplicados ou atrasados em relação à sua sequência são
+    /// descartados
+    pub fn recv(&mut self, buf: &mut [u8]) -> io::Result<(usize, bool)> {
+        let (n, addr) = self.socket.recv_from(buf)?;
+
+        if n < DATAGRAM_HEADER_BYTES {
+            return Ok((n, false));
+        }
+
+        let token = u64::from_le_bytes(<[u8; 8]>::try_from(&buf[..8]).unwrap());
+        let key = u64::from_le_bytes(<[u8; 8]>::try_from(&buf[8..16]).unwrap());
+        let seq = u32::from_le_bytes(<[u8; 4]>::try_from(&buf[16..DATAGRAM_HEADER_BYTES]).unwrap());
+
+        if token != self.token {
+            return Ok((n, false));
+        }
+
+        match self.peer {
+            Some(peer) if peer != addr => return Ok((n, false)),
+
+            Some(_) => {},
+
+            None if self.learn_peer => self.peer = Some(addr),
+            None => return Ok((n, false))
+        }
+
+        // o datagrama vazio de apresentação não possui mensagem
+        if n == DATAGRAM_HEADER_BYTES {
+            return Ok((n, false));
+        }
+
+        match self.recv_seq.get_mut(&key) {
+            Some(last) if seq.wrapping_sub(*last) as i32 <= 0 => return Ok((n, false)),
+            Some(last) => *last = seq,
+
+            None if self.recv_seq.len() >= MAX_RECV_SEQUENCES => return Ok((n, false)),
+
+            None => {
+                self.recv_seq.insert(key, seq);
+            }
+        }
+
+        Ok((n, true))
+    }
+}
+
+/// gera um token difícil de adivinhar para identificar os datagramas de uma conexão
+fn new_token() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|dur| dur.as_nanos())
+            .unwrap_or(0)
+    );
+
+    hasher.finish()
+}
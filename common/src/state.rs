@@ -1,4 +1,8 @@
-use std::time::Duration;
+use std::{
+    time::Duration,
+
+    collections::HashSet
+};
 
 use shred::{
     Fetch,
@@ -31,7 +35,18 @@ use crate::{
     comp,
     sys,
 
-    terrain::TerrainMap
+    terrain::{
+        light,
+
+        Block,
+        LightMap,
+        TerrainChunk,
+        TerrainMap
+    },
+
+    volumes::vol_map::VolMapErr,
+    vol::WriteVol,
+    weather::WeatherMap
 };
 
 /// o quão rápido deve um dia do jogo ser comparado a um dia real
@@ -87,6 +102,8 @@ impl State {
         ecs_world.add_resource(Time(0.0));
         ecs_world.add_resource(DeltaTime(0.0));
         ecs_world.add_resource(TerrainMap::new());
+        ecs_world.add_resource(LightMap::new());
+        ecs_world.add_resource(WeatherMap::new());
 
         // registrar componentes comuns com o estado
         comp::register_local_components(&mut ecs_world);
@@ -185,6 +202,11 @@ impl State {
         self.ecs_world.read_resource::<TimeOfDay>().0
     }
 
+    /// altera o tempo do dia do jogo atual
+    pub fn set_time_of_day(&mut self, time_of_day: f64) {
+        self.ecs_world.write_resource::<TimeOfDay>().0 = time_of_day;
+    }
+
     /// obtém o tempo atual do jogo
     ///
     /// note que isso não deve corresponder com o tempo do dia
@@ -202,6 +224,77 @@ impl State {
         self.ecs_world.write_resource::<TerrainMap>()
     }
 
+    /// obtém uma referência para a luz do terreno, com um chunk de luz para cada chunk de terreno
+    pub fn light(&self) -> Fetch<LightMap> {
+        self.ecs_world.read_resource::<LightMap>()
+    }
+
+    /// insere um chunk no terreno, calculando sua luz e registrando os chunks alterados
+    pub fn insert_chunk(&mut self, key: Vec3<i32>, chunk: TerrainChunk) {
+        self.terrain_mut().insert(key, chunk);
+
+        let lit = light::light_chunk(&self.terrain(), &mut self.ecs_world.write_resource::<LightMap>(), key);
+
+        self.changes.new_chunks.push(key);
+        self.mark_changed(lit);
+    }
+
+    /// remove um chunk do terreno junto com a sua luz, registrando os chunks alterados
+    pub fn remove_chunk(&mut self, key: Vec3<i32>) {
+        if self.terrain_mut().remove(&key).is_none() {
+            return;
+        }
+
+        let lit = light::unlight_chunk(&self.terrain(), &mut self.ecs_world.write_resource::<LightMap>(), key);
+
+        self.changes.removed_chunks.push(key);
+        self.mark_changed(lit);
+    }
+
+    /// altera um bloco do terreno, atualizando a luz em volta dele
+    pub fn set_block(&mut self, pos: Vec3<i32>, block: Block) -> Result<(), VolMapErr> {
+        self.terrain_mut().set(pos, block)?;
+
+        let mut changed = light::relight_block(&self.terrain(), &mut self.ecs_world.write_resource::<LightMap>(), pos);
+
+        // os meshes dos chunks vizinhos também mostram as faces do bloco alterado
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    changed.insert(TerrainMap::chunk_key(pos + Vec3::new(x, y, z)));
+                }
+            }
+        }
+
+        self.mark_changed(changed);
+
+        Ok(())
+    }
+
+    /// registra chunks carregados que precisam de um novo mesh, sem repetir os que acabaram de ser inseridos
+    fn mark_changed(&mut self, keys: HashSet<Vec3<i32>>) {
+        let terrain = self.ecs_world.read_resource::<TerrainMap>();
+
+        for key in keys {
+            if terrain.get_key(key).is_some() &&
+                !self.changes.new_chunks.contains(&key) &&
+                !self.changes.changed_chunks.contains(&key)
+            {
+                self.changes.changed_chunks.push(key);
+            }
+        }
+    }
+
+    /// obtém uma referência para o clima conhecido de cada região
+    pub fn weather(&self) -> Fetch<WeatherMap> {
+        self.ecs_world.read_resource::<WeatherMap>()
+    }
+
+    /// obtém uma referência mutável para o clima de cada região
+    pub fn weather_mut(&mut self) -> FetchMut<WeatherMap> {
+        self.ecs_world.write_resource::<WeatherMap>()
+    }
+
     // executar tick individual, simulando estado de jogo pela duração recebida
     pub fn tick(&mut self, dt: Duration) {
         // primeiro, limpar todos os componentes de marcadores temporários
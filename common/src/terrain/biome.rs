@@ -1,3 +1,4 @@
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum BiomeKind {
     Void,
     Grassland,
@@ -2,9 +2,16 @@
 use vek::*;
 
 // caixote
-use crate::vol::Vox;
+use crate::{
+    vol::Vox,
 
-#[derive(Copy, Clone, Debug)]
+    terrain::light::MAX_LIGHT
+};
+
+/// tipo dos blocos de tocha, que iluminam os arredores
+pub const TORCH_KIND: u8 = 4;
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Block {
     kind: u8,
     
@@ -20,6 +27,19 @@ impl Block {
         }
     }
 
+    pub fn get_kind(&self) -> u8 {
+        self.kind
+    }
+
+    /// quanta luz o bloco emite, de 0 até `MAX_LIGHT`
+    pub fn get_emission(&self) -> u8 {
+        match self.kind {
+            TORCH_KIND => MAX_LIGHT - 1,
+
+            _ => 0
+        }
+    }
+
     pub fn get_color(&self) -> Option<Rgb<u8>> {
         if self.is_empty() {
             None
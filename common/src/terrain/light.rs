@@ -0,0 +1,556 @@
+// padrão
+use std::collections::{
+    HashSet,
+    VecDeque
+};
+
+// biblioteca
+use vek::*;
+
+// caixote
+use crate::{
+    vol::{
+        Vox,
+
+        ReadVol,
+        WriteVol
+    },
+
+    volumes::{
+        chunk::Chunk,
+
+        vol_map::{
+            VolMap,
+            ChunkNeighbourhood
+        }
+    },
+
+    terrain::{
+        TerrainChunkSize,
+        TerrainMap
+    }
+};
+
+/// nível máximo de luz, recebido diretamente do céu aberto
+pub const MAX_LIGHT: u8 = 15;
+
+/// luz que chega a um voxel, separada entre a luz do céu e a luz emitida por blocos
+///
+/// a luz do céu guarda apenas o quanto o voxel está exposto ao céu, já que a cor e a intensidade dela dependem da
+/// hora do dia
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Light {
+    sun: u8,
+    block: u8
+}
+
+impl Light {
+    pub fn new(sun: u8, block: u8) -> Self {
+        Self {
+            sun: sun.min(MAX_LIGHT),
+            block: block.min(MAX_LIGHT)
+        }
+    }
+
+    /// a luz de um lugar aberto, utilizada onde o volume de luz não é conhecido
+    pub fn full_sun() -> Self {
+        Self::new(MAX_LIGHT, 0)
+    }
+
+    pub fn get_sun(&self) -> u8 {
+        self.sun
+    }
+
+    pub fn get_block(&self) -> u8 {
+        self.block
+    }
+}
+
+impl Vox for Light {
+    fn empty() -> Self {
+        Self {
+            sun: 0,
+            block: 0
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.sun == 0 && self.block == 0
+    }
+}
+
+// tipos de luz
+pub type LightChunk = Chunk<Light, TerrainChunkSize, ()>;
+pub type LightMap = VolMap<Light, TerrainChunkSize, ()>;
+pub type LightNeighbourhood = ChunkNeighbourhood<Light, TerrainChunkSize, ()>;
+
+const NEIGHBOURS: [Vec3<i32>; 6] = [
+    Vec3 { x: -1, y: 0, z: 0 },
+    Vec3 { x: 1, y: 0, z: 0 },
+    Vec3 { x: 0, y: -1, z: 0 },
+    Vec3 { x: 0, y: 1, z: 0 },
+    Vec3 { x: 0, y: 0, z: -1 },
+    Vec3 { x: 0, y: 0, z: 1 }
+];
+
+#[derive(Copy, Clone)]
+enum Channel {
+    Sun,
+    Block
+}
+
+const CHANNELS: [Channel; 2] = [Channel::Sun, Channel::Block];
+
+impl Channel {
+    fn get(self, light: Light) -> u8 {
+        match self {
+            Channel::Sun => light.sun,
+            Channel::Block => light.block
+        }
+    }
+
+    fn with(self, light: Light, value: u8) -> Light {
+        match self {
+            Channel::Sun => Light { sun: value, ..light },
+            Channel::Block => Light { block: value, ..light }
+        }
+    }
+
+    /// a luz que um voxel com `value` passa para o vizinho na direção `dir`
+    fn spread(self, value: u8, dir: Vec3<i32>) -> u8 {
+        match self {
+            // a luz do céu desce sem enfraquecer enquanto não encontra obstáculos
+            Channel::Sun if value == MAX_LIGHT && dir == -Vec3::unit_z() => MAX_LIGHT,
+
+            _ => value.saturating_sub(1)
+        }
+    }
+}
+
+/// calcula a luz de um chunk recém inserido no terreno, retornando as chaves dos chunks cuja luz mudou
+///
+/// a luz entra e sai do chunk pelas bordas, então os vizinhos também podem mudar. um chunk acima de outro já
+/// carregado passa a fazer sombra no topo dele
+pub fn light_chunk(terrain: &TerrainMap, light: &mut LightMap, key: Vec3<i32>) -> HashSet<Vec3<i32>> {
+    let mut lighter = Lighter::new(terrain, light);
+    let mut seeds = Vec::new();
+
+    if lighter.light.get_key(key).is_some() {
+        // a versão anterior do chunk pode ter iluminado os vizinhos
+        seeds.extend(lighter.darken(chunk_positions(key)));
+    } else {
+        lighter.light.insert(key, LightChunk::filled(Light::empty(), ()));
+    }
+
+    // a luz dos vizinhos entra no chunk pela camada de voxels em volta dele
+    let (min, max) = chunk_bounds(key);
+
+    seeds.extend(positions(min, max));
+    seeds.extend(positions(min - 1, max + 1).filter(|pos| !within(*pos, min, max)));
+
+    lighter.brighten(seeds);
+
+    // o topo do chunk de baixo só continua recebendo a luz do céu onde ela atravessa o novo chunk
+    let shaded = positions(Vec3::new(min.x, min.y, min.z - 1), Vec3::new(max.x, max.y, min.z))
+        .filter(|pos| {
+            lighter.get(*pos).map(|light| light.sun == MAX_LIGHT).unwrap_or(false) &&
+            lighter.get(*pos + Vec3::unit_z()).map(|light| light.sun < MAX_LIGHT).unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+
+    let relight = lighter.darken(shaded);
+
+    lighter.brighten(relight);
+
+    lighter.changed
+}
+
+/// remove a luz de um chunk que acabou de ser removido do terreno, retornando as chaves dos chunks cuja luz mudou
+pub fn unlight_chunk(terrain: &TerrainMap, light: &mut LightMap, key: Vec3<i32>) -> HashSet<Vec3<i32>> {
+    let mut lighter = Lighter::new(terrain, light);
+
+    if lighter.light.get_key(key).is_none() {
+        return lighter.changed;
+    }
+
+    let relight = lighter.darken(chunk_positions(key));
+
+    lighter.light.remove(&key);
+
+    // sem o chunk, o topo do chunk de baixo volta a receber a luz do céu diretamente
+    let (min, max) = chunk_bounds(key);
+
+    lighter.brighten(relight
+        .into_iter()
+        .chain(positions(Vec3::new(min.x, min.y, min.z - 1), Vec3::new(max.x, max.y, min.z)))
+    );
+
+    lighter.changed
+}
+
+/// atualiza a luz em volta de um bloco que acabou de ser alterado no terreno, retornando as chaves dos chunks cuja
+/// luz mudou
+///
+/// apenas a região iluminada através do bloco é recalculada
+pub fn relight_block(terrain: &TerrainMap, light: &mut LightMap, pos: Vec3<i32>) -> HashSet<Vec3<i32>> {
+    let mut lighter = Lighter::new(terrain, light);
+
+    let relight = lighter.darken(Some(pos));
+
+    lighter.brighten(relight
+        .into_iter()
+        .chain(Some(pos))
+        .chain(NEIGHBOURS.iter().map(|dir| pos + *dir))
+    );
+
+    lighter.changed
+}
+
+/// propaga a luz pelos voxels abertos do terreno, guardando quais chunks foram alterados
+struct Lighter<'a> {
+    terrain: &'a TerrainMap,
+    light: &'a mut LightMap,
+
+    changed: HashSet<Vec3<i32>>
+}
+
+impl<'a> Lighter<'a> {
+    fn new(terrain: &'a TerrainMap, light: &'a mut LightMap) -> Self {
+        Self {
+            terrain,
+            light,
+
+            changed: HashSet::new()
+        }
+    }
+
+    fn get(&self, pos: Vec3<i32>) -> Option<Light> {
+        self.light.get(pos).ok().cloned()
+    }
+
+    fn set(&mut self, pos: Vec3<i32>, light: Light) {
+        if self.light.set(pos, light).is_ok() {
+            // o mesh de um chunk também lê a luz da camada de voxels em volta dele
+            self.changed.insert(LightMap::chunk_key(pos));
+
+            for dir in NEIGHBOURS.iter() {
+                self.changed.insert(LightMap::chunk_key(pos + *dir));
+            }
+        }
+    }
+
+    /// apenas voxels vazios de chunks carregados deixam a luz passar
+    fn is_open(&self, pos: Vec3<i32>) -> bool {
+        self.terrain
+            .get(pos)
+            .map(|block| block.is_empty())
+            .unwrap_or(false)
+    }
+
+    /// voxels abertos no topo do terreno carregado recebem a luz do céu diretamente
+    fn is_sky(&self, pos: Vec3<i32>) -> bool {
+        self.is_open(pos) && self.terrain.get_key(TerrainMap::chunk_key(pos + Vec3::unit_z())).is_none()
+    }
+
+    /// apaga a luz das posições e de toda a região iluminada através delas, retornando as posições que precisam ser
+    /// iluminadas novamente por `brighten`
+    fn darken<I: IntoIterator<Item = Vec3<i32>>>(&mut self, positions: I) -> Vec<Vec3<i32>> {
+        let mut relight = Vec::new();
+        let mut removed = [VecDeque::new(), VecDeque::new()];
+
+        for pos in positions {
+            let light = match self.get(pos) {
+                Some(light) => light,
+
+                None => continue
+            };
+
+            for (channel, queue) in CHANNELS.iter().zip(removed.iter_mut()) {
+                if channel.get(light) > 0 {
+                    queue.push_back((pos, channel.get(light)));
+                }
+            }
+
+            if !light.is_empty() {
+                self.set(pos, Light::empty());
+            }
+
+            relight.push(pos);
+        }
+
+        for (channel, queue) in CHANNELS.iter().zip(removed.iter_mut()) {
+            while let Some((pos, value)) = queue.pop_front() {
+                for dir in NEIGHBOURS.iter() {
+                    let npos = pos + *dir;
+
+                    let nlight = match self.get(npos) {
+                        Some(nlight) => nlight,
+
+                        None => continue
+                    };
+
+                    let nvalue = channel.get(nlight);
+
+                    if nvalue == 0 {
+                        continue;
+                    }
+
+                    // vizinhos mais escuros podem ter sido iluminados por essa posição, e os mais claros possuem outra
+                    // fonte que volta a iluminar a região apagada
+                    if nvalue <= channel.spread(value, *dir) {
+                        self.set(npos, channel.with(nlight, 0));
+
+                        queue.push_back((npos, nvalue));
+                    }
+
+                    relight.push(npos);
+                }
+            }
+        }
+
+        relight
+    }
+
+    /// acende as fontes de luz entre as posições e espalha a luz delas pelos voxels abertos
+    fn brighten<I: IntoIterator<Item = Vec3<i32>>>(&mut self, positions: I) {
+        let mut queue = VecDeque::new();
+
+        for pos in positions {
+            let light = match self.get(pos) {
+                Some(light) => light,
+
+                None => continue
+            };
+
+            let sun = if self.is_sky(pos) { MAX_LIGHT } else { 0 };
+            let emission = self.terrain.get(pos).map(|block| block.get_emission()).unwrap_or(0);
+
+            let lit = Light::new(light.sun.max(sun), light.block.max(emission));
+
+            if lit != light {
+                self.set(pos, lit);
+            }
+
+            if !lit.is_empty() {
+                queue.push_back(pos);
+            }
+        }
+
+        while let Some(pos) = queue.pop_front() {
+            let light = match self.get(pos) {
+                Some(light) => light,
+
+                None => continue
+            };
+
+            for dir in NEIGHBOURS.iter() {
+                let npos = pos + *dir;
+
+                if !self.is_open(npos) {
+                    continue;
+                }
+
+                let nlight = match self.get(npos) {
+                    Some(nlight) => nlight,
+
+                    None => continue
+                };
+
+                let lit = Light::new(
+                    nlight.sun.max(Channel::Sun.spread(light.sun, *dir)),
+                    nlight.block.max(Channel::Block.spread(light.block, *dir))
+                );
+
+                if lit != nlight {
+                    self.set(npos, lit);
+
+                    queue.push_back(npos);
+                }
+            }
+        }
+    }
+}
+
+/// menor posição do chunk e a posição logo após a maior, em coordenadas do mundo
+fn chunk_bounds(key: Vec3<i32>) -> (Vec3<i32>, Vec3<i32>) {
+    let size = TerrainMap::chunk_size().map(|e| e as i32);
+
+    (key * size, key * size + size)
+}
+
+fn chunk_positions(key: Vec3<i32>) -> impl Iterator<Item = Vec3<i32>> {
+    let (min, max) = chunk_bounds(key);
+
+    positions(min, max)
+}
+
+/// todas as posições entre `min` (inclusivo) e `max` (exclusivo)
+fn positions(min: Vec3<i32>, max: Vec3<i32>) -> impl Iterator<Item = Vec3<i32>> {
+    (min.x..max.x).flat_map(move |x| {
+        (min.y..max.y).flat_map(move |y| {
+            (min.z..max.z).map(move |z| Vec3::new(x, y, z))
+        })
+    })
+}
+
+fn within(pos: Vec3<i32>, min: Vec3<i32>, max: Vec3<i32>) -> bool {
+    pos.map2(min, |e, min| e >= min).reduce_and() && pos.map2(max, |e, max| e < max).reduce_and()
+}
+
+// testes
+
+/// 2x2x2 chunks de chão plano até `z = 10`, com uma caverna fechada embaixo dele
+#[cfg(test)]
+fn cave_terrain() -> TerrainMap {
+    use crate::terrain::{
+        Block,
+        TerrainChunk,
+        TerrainChunkMeta
+    };
+
+    let mut terrain = TerrainMap::new();
+
+    for x in 0..=1 {
+        for y in 0..=1 {
+            for z in 0..=1 {
+                terrain.insert(Vec3::new(x, y, z), TerrainChunk::filled(Block::empty(), TerrainChunkMeta::void()));
+            }
+        }
+    }
+
+    for pos in positions(Vec3::zero(), Vec3::new(64, 64, 10)) {
+        let cave = within(pos, Vec3::new(4, 4, 2), Vec3::new(40, 12, 6));
+
+        if !cave {
+            terrain.set(pos, Block::new(1, Rgb::new(120, 80, 40))).unwrap();
+        }
+    }
+
+    terrain
+}
+
+/// ilumina o terreno inserindo seus chunks na ordem fornecida
+#[cfg(test)]
+fn light_in_order(full: &TerrainMap, order: &[Vec3<i32>]) -> (TerrainMap, LightMap) {
+    let mut terrain = TerrainMap::new();
+    let mut light = LightMap::new();
+
+    for key in order {
+        terrain.insert(*key, (**full.get_key(*key).unwrap()).clone());
+
+        light_chunk(&terrain, &mut light, *key);
+    }
+
+    (terrain, light)
+}
+
+#[cfg(test)]
+fn all_keys() -> Vec<Vec3<i32>> {
+    positions(Vec3::zero(), Vec3::broadcast(2)).collect()
+}
+
+#[cfg(test)]
+fn assert_same_light(a: &LightMap, b: &LightMap) {
+    for pos in positions(Vec3::zero(), Vec3::broadcast(64)) {
+        assert_eq!(a.get(pos).unwrap(), b.get(pos).unwrap(), "{:?}", pos);
+    }
+}
+
+#[test]
+fn sky_and_caves() {
+    let (_, light) = light_in_order(&cave_terrain(), &all_keys());
+
+    // o céu aberto ilumina tudo acima do chão, inclusive ao lado de paredes
+    assert_eq!(light.get(Vec3::new(20, 20, 10)).unwrap().get_sun(), MAX_LIGHT);
+    assert_eq!(light.get(Vec3::new(63, 0, 40)).unwrap().get_sun(), MAX_LIGHT);
+
+    // a caverna fechada e os blocos sólidos ficam escuros
+    assert_eq!(light.get(Vec3::new(8, 8, 3)).unwrap(), Light::empty());
+    assert_eq!(light.get(Vec3::new(20, 20, 5)).unwrap(), Light::empty());
+}
+
+#[test]
+fn insertion_order_does_not_matter() {
+    let full = cave_terrain();
+
+    let mut order = all_keys();
+
+    let (_, forward) = light_in_order(&full, &order);
+
+    // inserir de cima para baixo faz o chão receber a sombra depois de já ter sido iluminado
+    order.reverse();
+
+    let (_, backward) = light_in_order(&full, &order);
+
+    assert_same_light(&forward, &backward);
+}
+
+#[test]
+fn incremental_block_changes() {
+    use crate::terrain::{
+        Block,
+        block::TORCH_KIND
+    };
+
+    let (mut terrain, mut light) = light_in_order(&cave_terrain(), &all_keys());
+
+    // cavar um poço até a caverna deixa a luz do céu descer por ele
+    for z in 6..10 {
+        terrain.set(Vec3::new(8, 8, z), Block::empty()).unwrap();
+
+        relight_block(&terrain, &mut light, Vec3::new(8, 8, z));
+    }
+
+    assert_eq!(light.get(Vec3::new(8, 8, 2)).unwrap().get_sun(), MAX_LIGHT);
+    assert_eq!(light.get(Vec3::new(10, 8, 2)).unwrap().get_sun(), MAX_LIGHT - 2);
+
+    // uma tocha ilumina a caverna, enfraquecendo a cada voxel
+    terrain.set(Vec3::new(30, 8, 2), Block::new(TORCH_KIND, Rgb::new(255, 200, 100))).unwrap();
+
+    let changed = relight_block(&terrain, &mut light, Vec3::new(30, 8, 2));
+
+    assert!(changed.contains(&Vec3::zero()));
+    assert_eq!(light.get(Vec3::new(31, 8, 2)).unwrap().get_block(), MAX_LIGHT - 2);
+    assert_eq!(light.get(Vec3::new(33, 8, 2)).unwrap().get_block(), MAX_LIGHT - 4);
+
+    // o resultado é o mesmo de iluminar o terreno alterado do zero
+    let (_, fresh) = light_in_order(&terrain, &all_keys());
+
+    assert_same_light(&light, &fresh);
+
+    // tampar o poço e remover a tocha devolvem a escuridão
+    terrain.set(Vec3::new(8, 8, 9), Block::new(1, Rgb::new(120, 80, 40))).unwrap();
+    relight_block(&terrain, &mut light, Vec3::new(8, 8, 9));
+
+    terrain.set(Vec3::new(30, 8, 2), Block::empty()).unwrap();
+    relight_block(&terrain, &mut light, Vec3::new(30, 8, 2));
+
+    assert_eq!(light.get(Vec3::new(8, 8, 2)).unwrap(), Light::empty());
+    assert_eq!(light.get(Vec3::new(31, 8, 2)).unwrap(), Light::empty());
+
+    let (_, fresh) = light_in_order(&terrain, &all_keys());
+
+    assert_same_light(&light, &fresh);
+}
+
+#[test]
+fn unloading_restores_sky() {
+    let full = cave_terrain();
+    let (mut terrain, mut light) = light_in_order(&full, &all_keys());
+
+    // sem os chunks de cima, o topo dos chunks de baixo fica exposto ao céu
+    for key in all_keys().into_iter().filter(|key| key.z == 1) {
+        terrain.remove(&key);
+
+        unlight_chunk(&terrain, &mut light, key);
+    }
+
+    let bottom = all_keys().into_iter().filter(|key| key.z == 0).collect::<Vec<_>>();
+    let (_, fresh) = light_in_order(&full, &bottom);
+
+    for pos in positions(Vec3::zero(), Vec3::new(64, 64, 32)) {
+        assert_eq!(light.get(pos).unwrap(), fresh.get(pos).unwrap(), "{:?}", pos);
+    }
+
+    assert!(light.get(Vec3::new(0, 0, 40)).is_err());
+}
@@ -1,10 +1,17 @@
 pub mod block;
 pub mod biome;
+pub mod light;
 
 // re-exportações
 pub use self::{
     block::Block,
-    biome::BiomeKind
+    biome::BiomeKind,
+    light::{
+        Light,
+        LightChunk,
+        LightMap,
+        LightNeighbourhood
+    }
 };
 
 // biblioteca
@@ -15,7 +22,10 @@ use crate::{
     vol::VolSize,
     
     volumes::{
-        vol_map::VolMap,
+        vol_map::{
+            VolMap,
+            ChunkNeighbourhood
+        },
         chunk::Chunk
     }
 };
@@ -28,6 +38,7 @@ impl VolSize for TerrainChunkSize {
 }
 
 // terrainchunkmeta
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TerrainChunkMeta {
     biome: BiomeKind
 }
@@ -43,3 +54,4 @@ impl TerrainChunkMeta {
 // tipo de terreno
 pub type TerrainChunk = Chunk<Block, TerrainChunkSize, TerrainChunkMeta>;
 pub type TerrainMap = VolMap<Block, TerrainChunkSize, TerrainChunkMeta>;
+pub type TerrainNeighbourhood = ChunkNeighbourhood<Block, TerrainChunkSize, TerrainChunkMeta>;
@@ -1 +1,8 @@
+/// gerador pseudo-aleatório simples e determinístico, usado pelos testes de entrada arbitrária
+///
+/// avança `seed` e retorna os 31 bits mais altos, a mesma semente sempre produz a mesma sequência
+pub fn test_rng(seed: &mut u64) -> u64 {
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
 
+    *seed >> 33
+}
@@ -1,5 +1,8 @@
 // padrão
-use std::marker::PhantomData;
+use std::{
+    fmt,
+    marker::PhantomData
+};
 
 // biblioteca
 use vek::*;
@@ -26,12 +29,34 @@ pub enum ChunkErr {
 // v = voxel
 // s = tamanho (size)
 // m = metadata de chunk
+#[derive(Serialize, Deserialize)]
 pub struct Chunk<V: Vox, S: VolSize, M> {
     vox: Vec<V>,
     meta: M,
     phantom: PhantomData<S>
 }
 
+// implementado manualmente, já que `S` é apenas um marcador de tamanho e não precisa ser clonável
+impl<V: Vox + Clone, S: VolSize, M: Clone> Clone for Chunk<V, S, M> {
+    fn clone(&self) -> Self {
+        Self {
+            vox: self.vox.clone(),
+            meta: self.meta.clone(),
+            phantom: PhantomData
+        }
+    }
+}
+
+// os voxels são omitidos, já que um chunk possui dezenas de milhares deles
+impl<V: Vox, S: VolSize, M: fmt::Debug> fmt::Debug for Chunk<V, S, M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Chunk")
+            .field("size", &S::SIZE)
+            .field("meta", &self.meta)
+            .finish()
+    }
+}
+
 impl<V: Vox, S: VolSize, M> Chunk<V, S, M> {
     /// utilizado para transformar a posição de voxel em um volume no index correspondente no array do voxel
     #[inline(always)]
@@ -84,7 +109,7 @@ impl<V: Vox, S: VolSize, M> WriteVol for Chunk<V, S, M> {
     }
 }
 
-impl<V: Vox: Clone, S: VolSize, M> Chunk<V, S, M> {
+impl<V: Vox + Clone, S: VolSize, M> Chunk<V, S, M> {
     /// cria um novo chunk com as dimensões fornecidas e todos os voxels alinhados com duplicáveis no voxel fornecido
     pub fn filled(vox: V, meta: M) -> Self {
         Self {
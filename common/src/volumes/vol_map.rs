@@ -1,5 +1,8 @@
 // padrão
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::Arc
+};
 
 // biblioteca
 use vek::*;
@@ -41,14 +44,18 @@ pub enum VolMapErr {
 // v = voxel
 // s = tamanho (size)
 // m = metadata de chunk
+//
+// os chunks são compartilhados por `Arc`, para que possam ser lidos fora do mapa (ex: por threads de meshing) sem
+// cópia. alterações em um chunk compartilhado clonam o chunk antes de escrever
 pub struct VolMap<V: Vox, S: VolSize, M> {
-    chunks: HashMap<Vec3<i32>, Chunk<V, S, M>>
+    chunks: HashMap<Vec3<i32>, Arc<Chunk<V, S, M>>>
 }
 
 impl<V: Vox, S: VolSize, M> VolMap<V, S, M> {
+    /// a chave do chunk que contém a posição fornecida
     #[inline(always)]
 
-    fn chunk_key(pos: Vec3<i32>) -> Vec3<i32> {
+    pub fn chunk_key(pos: Vec3<i32>) -> Vec3<i32> {
         pos.map2(S::SIZE, |e, sz| e.div_euclid(sz as i32))
     }
 
@@ -118,7 +125,7 @@ impl<V: Vox + Clone, S: VolSize, M> SampleVol for VolMap<V, S, M> {
     }
 }
 
-impl<V: Vox, S: VolSize, M> WriteVol for VolMap<V, S, M> {    
+impl<V: Vox + Clone, S: VolSize, M: Clone> WriteVol for VolMap<V, S, M> {    
     #[inline(always)]
 
     fn set(&mut self, pos: Vec3<i32>, vox: V) -> Result<(), VolMapErr> {
@@ -130,7 +137,7 @@ impl<V: Vox, S: VolSize, M> WriteVol for VolMap<V, S, M> {
             .and_then(|chunk| {
                 let co = Self::chunk_offs(pos);
 
-                chunk.set(co, vox).map_err(|err| VolMapErr::ChunkErr(err))
+                Arc::make_mut(chunk).set(co, vox).map_err(|err| VolMapErr::ChunkErr(err))
             })
     }
 }
@@ -146,11 +153,179 @@ impl<V: Vox, S: VolSize, M> VolMap<V, S, M> {
         S::SIZE
     }
 
-    pub fn insert(&mut self, key: Vec3<i32>, chunk: Chunk<V, S, M>) -> Option<Chunk<V, S, M>> {
-        self.chunks.insert(key, chunk)
+    pub fn insert(&mut self, key: Vec3<i32>, chunk: Chunk<V, S, M>) -> Option<Arc<Chunk<V, S, M>>> {
+        self.chunks.insert(key, Arc::new(chunk))
+    }
+
+    pub fn get_key(&self, key: Vec3<i32>) -> Option<&Arc<Chunk<V, S, M>>> {
+        self.chunks.get(&key)
+    }
+
+    /// posições de todos os chunks presentes no mapa
+    pub fn keys(&self) -> impl Iterator<Item = Vec3<i32>> + '_ {
+        self.chunks.keys().cloned()
     }
 
-    pub fn remove(&mut self, key: &Vec3<i32>) -> Option<Chunk<V, S, M>> {
+    pub fn remove(&mut self, key: &Vec3<i32>) -> Option<Arc<Chunk<V, S, M>>> {
         self.chunks.remove(key)
     }
+
+    /// obtém o chunk em `key` junto dos seus vizinhos, sem copiar nenhum voxel
+    ///
+    /// assim como `sample`, falha com `NoSuchChunk` caso algum dos chunks ainda não exista
+    pub fn neighbourhood(&self, key: Vec3<i32>) -> Result<ChunkNeighbourhood<V, S, M>, VolMapErr> {
+        let mut chunks = Vec::with_capacity(27);
+
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    chunks.push(self.chunks
+                        .get(&(key + Vec3::new(x, y, z)))
+                        .ok_or(VolMapErr::NoSuchChunk)?
+                        .clone()
+                    );
+                }
+            }
+        }
+
+        Ok(ChunkNeighbourhood {
+            key,
+            chunks
+        })
+    }
+}
+
+/// um chunk e a borda de um voxel ao seu redor, lida diretamente dos chunks vizinhos compartilhados com o `VolMap`
+///
+/// o espaço coordenado é o mesmo do sample dessa região: a posição `(0, 0, 0)` corresponde ao voxel anterior ao canto
+/// mínimo do chunk central, e posições fora da borda não são acessíveis
+pub struct ChunkNeighbourhood<V: Vox, S: VolSize, M> {
+    key: Vec3<i32>,
+
+    // os 3x3x3 chunks ao redor do central, ordenados por x, y e z
+    chunks: Vec<Arc<Chunk<V, S, M>>>
+}
+
+impl<V: Vox, S: VolSize, M> ChunkNeighbourhood<V, S, M> {
+    /// posição do chunk central no mapa
+    pub fn key(&self) -> Vec3<i32> {
+        self.key
+    }
+}
+
+impl<V: Vox, S: VolSize, M> BaseVol for ChunkNeighbourhood<V, S, M> {
+    type Vox = V;
+
+    type Err = VolMapErr;
+}
+
+impl<V: Vox, S: VolSize, M> SizedVol for ChunkNeighbourhood<V, S, M> {
+    #[inline(always)]
+    fn get_size(&self) -> Vec3<u32> { S::SIZE + 2 }
+}
+
+impl<V: Vox, S: VolSize, M> ReadVol for ChunkNeighbourhood<V, S, M> {
+    #[inline(always)]
+
+    fn get(&self, pos: Vec3<i32>) -> Result<&V, VolMapErr> {
+        if
+            !pos.map(|e| e >= 0).reduce_and() ||
+            !pos.map2(self.get_size(), |e, sz| e < sz as i32).reduce_and()
+        {
+            return Err(VolMapErr::ChunkErr(ChunkErr::OutOfBounds));
+        }
+
+        // posição relativa ao canto mínimo do chunk central
+        let pos = pos - 1;
+        let ck = VolMap::<V, S, M>::chunk_key(pos) + 1;
+
+        self.chunks[(ck.x * 9 + ck.y * 3 + ck.z) as usize]
+            .get(VolMap::<V, S, M>::chunk_offs(pos))
+            .map_err(|err| VolMapErr::ChunkErr(err))
+    }
+}
+
+// testes
+
+#[cfg(test)]
+struct TestChunkSize;
+
+#[cfg(test)]
+impl VolSize for TestChunkSize {
+    const SIZE: Vec3<u32> = Vec3 { x: 4, y: 5, z: 6 };
+}
+
+#[cfg(test)]
+fn test_map() -> VolMap<crate::terrain::Block, TestChunkSize, ()> {
+    use crate::terrain::Block;
+
+    let mut map = VolMap::new();
+
+    for x in -2..=2 {
+        for y in -2..=2 {
+            for z in -2..=2 {
+                map.insert(Vec3::new(x, y, z), Chunk::filled(Block::empty(), ()));
+            }
+        }
+    }
+
+    // cada voxel recebe uma cor diferente, derivada da posição no mundo
+    for x in -8..12 {
+        for y in -10..15 {
+            for z in -12..18 {
+                let col = Rgb::new(x as u8, y as u8, z as u8);
+
+                map.set(Vec3::new(x, y, z), Block::new(1, col)).unwrap();
+            }
+        }
+    }
+
+    map
+}
+
+#[test]
+fn neighbourhood_matches_sample() {
+    let map = test_map();
+
+    for &key in [Vec3::zero(), Vec3::new(-1, 0, 1), Vec3::new(1, -1, -1)].iter() {
+        let aabb = Aabb {
+            min: key.map2(TestChunkSize::SIZE, |e, sz| e * sz as i32 - 1),
+            max: key.map2(TestChunkSize::SIZE, |e, sz| (e + 1) * sz as i32 + 1)
+        };
+
+        let sample = map.sample(aabb).unwrap();
+        let neighbourhood = map.neighbourhood(key).unwrap();
+
+        assert_eq!(neighbourhood.key(), key);
+        assert_eq!(neighbourhood.get_size(), sample.get_size());
+
+        for pos in sample.iter_positions() {
+            assert_eq!(
+                neighbourhood.get(pos).unwrap().get_color(),
+                sample.get(pos).unwrap().get_color()
+            );
+        }
+
+        assert!(neighbourhood.get(Vec3::new(-1, 0, 0)).is_err());
+        assert!(neighbourhood.get(sample.get_size().map(|e| e as i32)).is_err());
+    }
+
+    // os chunks da borda do mapa não possuem todos os vizinhos
+    assert!(map.neighbourhood(Vec3::new(2, 0, 0)).is_err());
+}
+
+#[test]
+fn neighbourhood_copy_on_write() {
+    use crate::terrain::Block;
+
+    let mut map = test_map();
+
+    let neighbourhood = map.neighbourhood(Vec3::zero()).unwrap();
+    let before = neighbourhood.get(Vec3::one()).unwrap().get_color();
+
+    // o mapa pode ser alterado enquanto a vizinhança ainda está em uso, sem afetá-la
+    map.set(Vec3::zero(), Block::new(1, Rgb::new(200, 200, 200))).unwrap();
+
+    assert_eq!(neighbourhood.get(Vec3::one()).unwrap().get_color(), before);
+    assert_eq!(map.get(Vec3::zero()).unwrap().get_color(), Some(Rgb::new(200, 200, 200)));
 }
@@ -0,0 +1,150 @@
+// padrão
+use std::collections::HashMap;
+
+// biblioteca
+use vek::*;
+
+/// tamanho horizontal, em blocos, de uma região de clima
+///
+/// o servidor simula o clima de cada região separadamente, e todo ponto dentro dela possui o mesmo clima
+pub const WEATHER_REGION_SIZE: i32 = 256;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    Snow,
+    Fog
+}
+
+pub const ALL_WEATHER_KINDS: [WeatherKind; 4] = [
+    WeatherKind::Clear,
+    WeatherKind::Rain,
+    WeatherKind::Snow,
+    WeatherKind::Fog
+];
+
+impl WeatherKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "limpo",
+            WeatherKind::Rain => "chuva",
+            WeatherKind::Snow => "neve",
+            WeatherKind::Fog => "neblina"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ALL_WEATHER_KINDS.iter().cloned().find(|kind| kind.name() == name)
+    }
+}
+
+/// o clima de uma região, com a intensidade entre 0 (nenhum efeito) e 1
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Weather {
+    pub kind: WeatherKind,
+    pub intensity: f32
+}
+
+impl Weather {
+    pub fn new(kind: WeatherKind, intensity: f32) -> Self {
+        Self {
+            kind,
+            intensity: intensity.max(0.0).min(1.0)
+        }
+    }
+
+    pub fn clear() -> Self {
+        Self::new(WeatherKind::Clear, 0.0)
+    }
+
+    /// o quanto o céu está encoberto, usado para escurecer a luz do sol e acinzentar o céu
+    pub fn overcast(&self) -> f32 {
+        match self.kind {
+            WeatherKind::Clear => 0.0,
+
+            _ => self.intensity
+        }
+    }
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self::clear()
+    }
+}
+
+/// o clima conhecido de cada região, recurso do estado tanto no servidor quanto no client
+///
+/// regiões ausentes possuem clima limpo
+#[derive(Clone, Debug, Default)]
+pub struct WeatherMap {
+    regions: HashMap<Vec2<i32>, Weather>
+}
+
+impl WeatherMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a região que contém uma posição do mundo
+    pub fn region_of(pos: Vec3<f32>) -> Vec2<i32> {
+        Vec2::new(pos.x, pos.y).map(|e| (e.floor() as i32).div_euclid(WEATHER_REGION_SIZE))
+    }
+
+    pub fn get(&self, region: Vec2<i32>) -> Weather {
+        self.regions.get(&region).cloned().unwrap_or_default()
+    }
+
+    /// o clima em uma posição do mundo
+    pub fn at(&self, pos: Vec3<f32>) -> Weather {
+        self.get(Self::region_of(pos))
+    }
+
+    /// altera o clima de uma região, retornando o clima anterior
+    pub fn set(&mut self, region: Vec2<i32>, weather: Weather) -> Weather {
+        self.regions.insert(region, weather).unwrap_or_default()
+    }
+
+    /// esquece o clima de uma região, que volta a ser limpo, retornando o clima anterior
+    pub fn remove(&mut self, region: Vec2<i32>) -> Weather {
+        self.regions.remove(&region).unwrap_or_default()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2<i32>, Weather)> + '_ {
+        self.regions.iter().map(|(region, weather)| (*region, *weather))
+    }
+}
+
+// testes
+
+#[test]
+fn weather_regions() {
+    assert_eq!(WeatherMap::region_of(Vec3::new(0.0, 0.0, 50.0)), Vec2::new(0, 0));
+    assert_eq!(WeatherMap::region_of(Vec3::new(255.9, 256.0, 0.0)), Vec2::new(0, 1));
+
+    // posições negativas pertencem à região anterior, sem uma região dupla em volta da origem
+    assert_eq!(WeatherMap::region_of(Vec3::new(-0.5, -256.0, 0.0)), Vec2::new(-1, -1));
+    assert_eq!(WeatherMap::region_of(Vec3::new(-256.5, 0.0, 0.0)), Vec2::new(-2, 0));
+}
+
+#[test]
+fn weather_map_lookup() {
+    let mut map = WeatherMap::new();
+
+    assert_eq!(map.at(Vec3::new(10.0, 10.0, 0.0)), Weather::clear());
+
+    let rain = Weather::new(WeatherKind::Rain, 0.7);
+
+    assert_eq!(map.set(Vec2::new(0, 0), rain), Weather::clear());
+    assert_eq!(map.at(Vec3::new(10.0, 10.0, 0.0)), rain);
+    assert_eq!(map.at(Vec3::new(-10.0, 10.0, 0.0)), Weather::clear());
+
+    assert_eq!(map.remove(Vec2::new(0, 0)), rain);
+    assert_eq!(map.at(Vec3::new(10.0, 10.0, 0.0)), Weather::clear());
+    assert_eq!(map.iter().count(), 0);
+
+    // intensidades fora do intervalo são limitadas
+    assert_eq!(Weather::new(WeatherKind::Fog, 3.0).intensity, 1.0);
+    assert_eq!(Weather::new(WeatherKind::Clear, 0.8).overcast(), 0.0);
+}
@@ -1,17 +1,90 @@
-use std::time::Duration;
+use std::{
+    env,
 
-use log::info;
+    io::{
+        self,
+
+        BufRead
+    },
+
+    sync::mpsc,
+    process,
+    thread,
+    time::Duration
+};
+
+use log::{
+    info,
+    error
+};
 
 use server::{
     Input,
     Event,
-    Server
+    Server,
+    ServerSettings
+};
+
+use common::{
+    clock::Clock,
+    msg::chat
 };
 
-use common::clock::Clock;
+// arquivo de configurações utilizado quando nenhum é fornecido
+const DEFAULT_SETTINGS_FILE: &str = "servidor.cfg";
+
+// contagem regressiva quando o servidor recebe sigint ou sigterm, anunciada aos jogadores
+const SIGNAL_SHUTDOWN_COUNTDOWN: Duration = Duration::from_secs(5);
+
+/// lê os comandos digitados no console em uma thread separada, para não bloquear o tick do servidor
+fn spawn_console() -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let stdin = io::stdin();
+
+        for line in stdin.lock().lines() {
+            match line {
+                Ok(line) => if !line.trim().is_empty() && tx.send(line).is_err() {
+                    break;
+                },
+
+                Err(_) => break
+            }
+        }
+    });
+
+    rx
+}
+
+/// carrega as configurações do servidor a partir dos argumentos
+///
+/// uso: server-cli [arquivo de configurações] [--chave=valor ...]
+///
+/// as opções `--chave=valor` são aplicadas depois do arquivo, substituindo os valores dele
+fn load_settings<I: Iterator<Item = String>>(args: I) -> Result<ServerSettings, server::Error> {
+    let (options, files): (Vec<_>, Vec<_>) = args.partition(|arg| arg.starts_with("--"));
+
+    if files.len() > 1 {
+        return Err(server::Error::Other(String::from("apenas um arquivo de configurações pode ser fornecido")));
+    }
+
+    let mut settings = ServerSettings::load(files.first().map(String::as_str).unwrap_or(DEFAULT_SETTINGS_FILE))?;
+
+    for option in &options {
+        let mut parts = option[2..].splitn(2, '=');
+
+        match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) => settings.set(key, value)?,
+
+            _ => return Err(server::Error::Other(format!("opção '{}' inválida, esperado '--chave=valor'", option)))
+        }
+    }
+
+    settings.validate()?;
 
-// tps
-const TPS: u64 = 30;
+    Ok(settings)
+}
 
 fn main() {
     // logging inicial
@@ -22,20 +95,78 @@ fn main() {
     // configurar clock de fps
     let mut clock = Clock::new();
 
+    let settings = match load_settings(env::args().skip(1)) {
+        Ok(settings) => settings,
+
+        Err(err) => {
+            error!("configurações inválidas: {:?}", err);
+
+            process::exit(1);
+        }
+    };
+
     // criar servidor
-    let mut server = Server::new()
+    let mut server = Server::with_settings(settings)
         .expect("falha ao criar instância de servidor");
 
-    loop {
-        let events = server.tick(Input::default(), clock.get_last_delta())
-            .expect("falha ao tickar o servidor");
+    let tick_rate = server.settings().tick_rate;
+
+    info!("servidor '{}' iniciado em {}", server.settings().name, server.settings().address);
+    info!("digite 'help' para ver os comandos disponíveis");
+
+    let console = spawn_console();
+
+    // sigint (ctrl+c) e sigterm iniciam um desligamento gracioso; um segundo sinal desliga imediatamente
+    let (signal_tx, signal_rx) = mpsc::channel();
+
+    ctrlc::set_handler(move || {
+        let _ = signal_tx.send(());
+    })
+        .expect("falha ao registrar o handler de sinais");
+
+    // falhas durante a execução ainda desligam o servidor, mas terminam o processo com erro
+    let mut failed = false;
+
+    'running: loop {
+        for () in signal_rx.try_iter() {
+            if server.is_shutting_down() {
+                info!("sinal recebido novamente, desligando imediatamente...");
+
+                break 'running;
+            }
+
+            info!("sinal recebido, desligando em {} segundos...", SIGNAL_SHUTDOWN_COUNTDOWN.as_secs());
+
+            server.shutdown_in(SIGNAL_SHUTDOWN_COUNTDOWN);
+        }
+
+        let input = Input {
+            commands: console.try_iter().collect()
+        };
+
+        let events = match server.tick(input, clock.get_last_delta()) {
+            Ok(events) => events,
+
+            Err(err) => {
+                error!("falha ao tickar o servidor: {:?}", err);
+
+                failed = true;
+
+                break 'running;
+            }
+        };
 
         for event in events {
             match event {
-                Event::ClientConnected { ecs_entity } => info!("cliente {} conectado!", uid),
-                Event::ClientDisconnected { ecs_entity } => info!("cliente {} desconectado!", uid),
+                Event::ClientConnected { ecs_entity } => info!("cliente {:?} conectado!", ecs_entity),
+                Event::ClientDisconnected { ecs_entity, reason } => info!("cliente {:?} desconectado: {}", ecs_entity, reason),
 
-                Event::Chat { ecs_entity, msg } => info!("[cliente {}] {}", uid, msg)
+                Event::Chat { username, channel, msg, .. } => info!("{}", chat::format_chat_msg(&channel, Some(&username), &msg)),
+
+                Event::ConsoleResponse(Ok(response)) => println!("{}", response),
+                Event::ConsoleResponse(Err(err)) => println!("erro: {}", err),
+
+                Event::Shutdown => break 'running
             }
         }
 
@@ -43,6 +174,42 @@ fn main() {
         server.cleanup();
 
         // esperar pelo próximo tick
-        clock.tick(Duration::from_millis(1000 / TPS));
+        clock.tick(Duration::from_millis(1000 / tick_rate));
+    }
+
+    info!("desligando o servidor...");
+
+    if let Err(err) = server.shutdown() {
+        error!("falha ao desligar o servidor: {:?}", err);
+
+        failed = true;
+    }
+
+    if failed {
+        process::exit(1);
     }
+
+    info!("servidor desligado");
+}
+
+// testes
+
+#[test]
+fn settings_from_args() {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>().into_iter();
+
+    let settings = load_settings(args(&[
+        "lestinia_server_cli_inexistente.cfg",
+        "--tick_rate=60",
+        "--motd=olá = mundo",
+        "--max_connections_per_ip=nenhum"
+    ])).unwrap();
+
+    assert_eq!(settings.tick_rate, 60);
+    assert_eq!(settings.motd, "olá = mundo");
+    assert_eq!(settings.connection_limits.max_per_ip, None);
+
+    assert!(load_settings(args(&["lestinia_server_cli_inexistente.cfg", "--tick_rate=0"])).is_err());
+    assert!(load_settings(args(&["lestinia_server_cli_inexistente.cfg", "--compression"])).is_err());
+    assert!(load_settings(args(&["a.cfg", "b.cfg"])).is_err());
 }
@@ -0,0 +1,497 @@
+use std::{
+	fs,
+
+	net::IpAddr,
+	num::NonZeroU32,
+
+	collections::{
+		HashMap,
+		HashSet
+	},
+
+	time::{
+		SystemTime,
+		UNIX_EPOCH
+	}
+};
+
+use ring::{
+	pbkdf2,
+
+	rand::{
+		SecureRandom,
+		SystemRandom
+	}
+};
+
+use common::msg::MAX_PASSWORD_LEN;
+
+use crate::Error;
+
+// iterações do pbkdf2. o hash é calculado na thread principal durante o login, então o custo é mantido baixo
+const PBKDF2_ITERATIONS: u32 = 10_000;
+
+const SALT_BYTES: usize = 16;
+const HASH_BYTES: usize = 32;
+
+/// ação privilegiada que um cargo pode ou não realizar
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Capability {
+	Teleport,
+	SetTime,
+	SetWeather,
+	Broadcast,
+	Kick,
+	Ban,
+	ManageWhitelist,
+	ManageRoles,
+	Spawn,
+	Give,
+	Save,
+	Stop
+}
+
+/// cargo de um jogador, que define as capacidades que ele possui
+///
+/// os cargos são ordenados do menor para o maior
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Role {
+	Player,
+	Moderator,
+	Admin
+}
+
+pub const ALL_ROLES: [Role; 3] = [Role::Player, Role::Moderator, Role::Admin];
+
+impl Role {
+	pub fn name(&self) -> &'static str {
+		match self {
+			Role::Player => "jogador",
+			Role::Moderator => "moderador",
+			Role::Admin => "admin"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Self> {
+		ALL_ROLES.iter().cloned().find(|role| role.name() == name)
+	}
+
+	pub fn capabilities(&self) -> &'static [Capability] {
+		match self {
+			Role::Player => &[],
+
+			Role::Moderator => &[
+				Capability::Teleport,
+				Capability::SetTime,
+				Capability::SetWeather,
+				Capability::Broadcast,
+				Capability::Kick,
+				Capability::Ban
+			],
+
+			Role::Admin => &[
+				Capability::Teleport,
+				Capability::SetTime,
+				Capability::SetWeather,
+				Capability::Broadcast,
+				Capability::Kick,
+				Capability::Ban,
+				Capability::ManageWhitelist,
+				Capability::ManageRoles,
+				Capability::Spawn,
+				Capability::Give,
+				Capability::Save,
+				Capability::Stop
+			]
+		}
+	}
+
+	pub fn has(&self, capability: Capability) -> bool {
+		self.capabilities().contains(&capability)
+	}
+}
+
+/// alvo de um banimento
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BanTarget {
+	Name(String),
+	Ip(IpAddr)
+}
+
+impl BanTarget {
+	/// o mesmo alvo, com o nome na forma em que é guardado
+	fn normalized(&self) -> Self {
+		match self {
+			BanTarget::Name(name) => BanTarget::Name(name_key(name)),
+			BanTarget::Ip(ip) => BanTarget::Ip(*ip)
+		}
+	}
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ban {
+	pub target: BanTarget,
+	pub reason: String,
+
+	/// momento em que o banimento expira, em segundos desde a época unix. `None` para banimentos permanentes
+	pub expires_at: Option<u64>
+}
+
+impl Ban {
+	pub fn is_expired(&self, now: u64) -> bool {
+		self.expires_at.map(|expires_at| now >= expires_at).unwrap_or(false)
+	}
+
+	/// descreve o banimento para o jogador banido
+	pub fn describe(&self, now: u64) -> String {
+		match self.expires_at {
+			Some(expires_at) => format!(
+				"{} (expira em {} minutos)",
+				self.reason,
+				(expires_at.saturating_sub(now) + 59) / 60
+			),
+
+			None => self.reason.clone()
+		}
+	}
+}
+
+/// conta de um nome de usuário registrado, protegida por senha
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Account {
+	salt: Vec<u8>,
+	hash: Vec<u8>
+}
+
+impl Account {
+	pub fn new(password: &str) -> Self {
+		let mut salt = vec![0; SALT_BYTES];
+
+		// sem uma fonte de aleatoriedade o sal apenas deixa de ser único, e a senha continua protegida
+		let _ = SystemRandom::new().fill(&mut salt);
+
+		let mut hash = vec![0; HASH_BYTES];
+
+		pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations(), &salt, password.as_bytes(), &mut hash);
+
+		Self {
+			salt,
+			hash
+		}
+	}
+
+	pub fn verify(&self, password: &str) -> bool {
+		pbkdf2::verify(pbkdf2::PBKDF2_HMAC_SHA256, iterations(), &self.salt, password.as_bytes(), &self.hash).is_ok()
+	}
+}
+
+fn iterations() -> NonZeroU32 {
+	NonZeroU32::new(PBKDF2_ITERATIONS).unwrap()
+}
+
+/// resultado da verificação da senha de um login
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Authentication {
+	/// o nome não está registrado e nenhuma senha foi enviada
+	Guest,
+
+	/// a senha confere com a conta do nome
+	Verified,
+
+	/// o nome não estava registrado, e a senha enviada passou a protegê-lo
+	Registered,
+
+	/// o nome está registrado e a senha está ausente ou não confere
+	Refused
+}
+
+/// forma em que um nome de usuário é guardado e comparado
+///
+/// nomes que diferem apenas em maiúsculas e minúsculas pertencem ao mesmo jogador, como no handshake
+fn name_key(username: &str) -> String {
+	username.to_ascii_lowercase()
+}
+
+/// obtém o tempo real atual, em segundos desde a época unix
+pub fn unix_now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|dur| dur.as_secs())
+		.unwrap_or(0)
+}
+
+/// contas, cargos, banimentos e whitelist do servidor, salvos em disco a cada alteração
+///
+/// os nomes de usuário são guardados em minúsculas
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AccessList {
+	/// contas dos nomes de usuário registrados
+	pub accounts: HashMap<String, Account>,
+
+	/// cargos atribuídos por nome de usuário. jogadores ausentes, ou cujo nome não está registrado, possuem
+	/// o cargo `Role::Player`
+	pub roles: HashMap<String, Role>,
+
+	pub bans: Vec<Ban>,
+
+	/// quando ativada, apenas jogadores da whitelist podem entrar
+	pub whitelist_enabled: bool,
+	pub whitelist: HashSet<String>
+}
+
+impl AccessList {
+	/// carrega a lista do arquivo fornecido, ou uma lista vazia caso ele ainda não exista
+	pub fn load(path: &str) -> Result<Self, Error> {
+		match fs::read(path) {
+			Ok(bytes) => bincode::deserialize(&bytes)
+				.map_err(|err| Error::Other(format!("arquivo de acesso '{}' inválido: {:?}", path, err))),
+
+			Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+
+			Err(err) => Err(Error::Other(format!("falha ao ler o arquivo de acesso '{}': {:?}", path, err)))
+		}
+	}
+
+	pub fn save(&self, path: &str) -> Result<(), Error> {
+		let bytes = bincode::serialize(self)
+			.map_err(|err| Error::Other(format!("falha ao serializar o arquivo de acesso: {:?}", err)))?;
+
+		fs::write(path, bytes)
+			.map_err(|err| Error::Other(format!("falha ao escrever o arquivo de acesso '{}': {:?}", path, err)))
+	}
+
+	pub fn is_registered(&self, username: &str) -> bool {
+		self.accounts.contains_key(&name_key(username))
+	}
+
+	/// verifica a senha de um login, registrando o nome caso ele ainda não possua conta
+	///
+	/// nomes registrados só podem ser utilizados com a senha deles, então um jogador conectado com um nome
+	/// registrado sempre é o dono da conta
+	pub fn authenticate(&mut self, username: &str, password: Option<&str>) -> Authentication {
+		let key = name_key(username);
+
+		match (self.accounts.get(&key), password) {
+			(_, Some(password)) if password.len() > MAX_PASSWORD_LEN => Authentication::Refused,
+
+			(Some(account), Some(password)) if account.verify(password) => Authentication::Verified,
+			(Some(_), _) => Authentication::Refused,
+
+			(None, Some(password)) => {
+				self.accounts.insert(key, Account::new(password));
+
+				Authentication::Registered
+			},
+
+			(None, None) => Authentication::Guest
+		}
+	}
+
+	/// obtém o cargo de um jogador, que só vale para nomes registrados, já que qualquer um pode utilizar
+	/// um nome sem conta
+	pub fn role(&self, username: &str) -> Role {
+		if !self.is_registered(username) {
+			return Role::Player;
+		}
+
+		self.roles.get(&name_key(username)).cloned().unwrap_or(Role::Player)
+	}
+
+	/// altera o cargo de um jogador, retornando `false` caso o nome não esteja registrado
+	///
+	/// sem conta, o primeiro a entrar com o nome e qualquer senha se tornaria o dono dele e receberia o cargo, então
+	/// apenas `Role::Player` pode ser atribuído a nomes sem conta
+	pub fn set_role(&mut self, username: &str, role: Role) -> bool {
+		let key = name_key(username);
+
+		match role {
+			Role::Player => { self.roles.remove(&key); },
+
+			_ if !self.accounts.contains_key(&key) => return false,
+
+			role => { self.roles.insert(key, role); }
+		}
+
+		true
+	}
+
+	/// adiciona um banimento, substituindo qualquer outro com o mesmo alvo
+	pub fn ban(&mut self, mut ban: Ban) {
+		ban.target = ban.target.normalized();
+
+		self.bans.retain(|other| other.target != ban.target);
+		self.bans.push(ban);
+	}
+
+	/// remove o banimento do alvo fornecido, retornando `false` caso ele não estivesse banido
+	pub fn unban(&mut self, target: &BanTarget) -> bool {
+		let target = target.normalized();
+		let len = self.bans.len();
+
+		self.bans.retain(|ban| ban.target != target);
+
+		self.bans.len() != len
+	}
+
+	/// encontra um banimento ainda válido para o nome ou endereço fornecidos
+	pub fn find_ban(&self, username: &str, ip: Option<IpAddr>, now: u64) -> Option<&Ban> {
+		let key = name_key(username);
+
+		self.bans
+			.iter()
+			.filter(|ban| !ban.is_expired(now))
+			.find(|ban| match &ban.target {
+				BanTarget::Name(name) => *name == key,
+				BanTarget::Ip(banned_ip) => Some(*banned_ip) == ip
+			})
+	}
+
+	/// remove os banimentos que já expiraram
+	pub fn remove_expired(&mut self, now: u64) {
+		self.bans.retain(|ban| !ban.is_expired(now));
+	}
+
+	pub fn is_whitelisted(&self, username: &str) -> bool {
+		!self.whitelist_enabled || self.whitelist.contains(&name_key(username))
+	}
+
+	pub fn whitelist_add(&mut self, username: &str) {
+		self.whitelist.insert(name_key(username));
+	}
+
+	/// remove um nome da whitelist, retornando `false` caso ele não estivesse nela
+	pub fn whitelist_remove(&mut self, username: &str) -> bool {
+		self.whitelist.remove(&name_key(username))
+	}
+}
+
+// testes
+
+#[cfg(test)]
+fn ban(target: BanTarget, expires_at: Option<u64>) -> Ban {
+	Ban {
+		target,
+		reason: String::from("teste"),
+		expires_at
+	}
+}
+
+#[test]
+fn roles_need_a_registered_name() {
+	let mut access = AccessList::default();
+
+	// sem conta, o primeiro a entrar com o nome e qualquer senha receberia o cargo
+	assert!(!access.set_role("admin", Role::Admin));
+	assert_eq!(access.role("admin"), Role::Player);
+
+	assert_eq!(access.authenticate("admin", Some("senha")), Authentication::Registered);
+	assert_eq!(access.role("admin"), Role::Player);
+
+	assert!(access.set_role("admin", Role::Admin));
+	assert_eq!(access.role("admin"), Role::Admin);
+	assert_eq!(access.role("ADMIN"), Role::Admin);
+	assert_eq!(access.role("outro"), Role::Player);
+
+	// os cargos são ordenados do menor para o maior
+	assert!(Role::Player < Role::Moderator && Role::Moderator < Role::Admin);
+
+	assert!(access.role("admin").has(Capability::Stop));
+	assert!(!Role::Moderator.has(Capability::Stop));
+	assert!(Role::Moderator.has(Capability::Kick));
+	assert!(Role::Player.capabilities().is_empty());
+
+	// voltar para jogador remove o cargo
+	assert!(access.set_role("Admin", Role::Player));
+
+	assert_eq!(access.role("admin"), Role::Player);
+	assert!(access.roles.is_empty());
+}
+
+#[test]
+fn authentication() {
+	let mut access = AccessList::default();
+
+	assert_eq!(access.authenticate("alice", None), Authentication::Guest);
+	assert!(!access.is_registered("alice"));
+
+	assert_eq!(access.authenticate("alice", Some("segredo")), Authentication::Registered);
+	assert_eq!(access.authenticate("alice", Some("segredo")), Authentication::Verified);
+
+	// depois de registrado, o nome não pode ser utilizado sem a senha correta
+	assert_eq!(access.authenticate("alice", Some("errada")), Authentication::Refused);
+	assert_eq!(access.authenticate("alice", None), Authentication::Refused);
+
+	// nem registrado novamente com outras maiúsculas
+	assert_eq!(access.authenticate("Alice", Some("outra")), Authentication::Refused);
+	assert_eq!(access.authenticate("ALICE", Some("segredo")), Authentication::Verified);
+	assert_eq!(access.accounts.len(), 1);
+
+	assert_eq!(access.authenticate("bob", Some(&"a".repeat(MAX_PASSWORD_LEN + 1))), Authentication::Refused);
+	assert!(!access.is_registered("bob"));
+}
+
+#[test]
+fn ban_expiry() {
+	let mut access = AccessList::default();
+
+	let ip: IpAddr = "10.0.0.1".parse().unwrap();
+
+	access.ban(ban(BanTarget::Name(String::from("alice")), Some(1000)));
+	access.ban(ban(BanTarget::Ip(ip), None));
+
+	assert!(access.find_ban("alice", None, 999).is_some());
+	assert!(access.find_ban("alice", None, 1000).is_none());
+	assert!(access.find_ban("bob", None, 0).is_none());
+
+	// banimentos de ip valem para qualquer nome, e banimentos permanentes nunca expiram
+	assert!(access.find_ban("bob", Some(ip), u64::max_value()).is_some());
+
+	assert_eq!(access.find_ban("alice", None, 940).unwrap().describe(940), "teste (expira em 1 minutos)");
+
+	// banir o mesmo alvo novamente substitui o banimento anterior
+	access.ban(ban(BanTarget::Name(String::from("alice")), Some(5000)));
+
+	assert_eq!(access.bans.len(), 2);
+	assert!(access.find_ban("alice", None, 4000).is_some());
+
+	access.remove_expired(5000);
+
+	assert_eq!(access.bans.len(), 1);
+	assert!(access.unban(&BanTarget::Ip(ip)));
+	assert!(!access.unban(&BanTarget::Ip(ip)));
+}
+
+#[test]
+fn bans_ignore_case() {
+	let mut access = AccessList::default();
+
+	access.ban(ban(BanTarget::Name(String::from("griefer")), None));
+
+	// o mesmo jogador não volta trocando as maiúsculas do nome
+	assert!(access.find_ban("Griefer", None, 0).is_some());
+	assert!(access.find_ban("GRIEFER", None, 0).is_some());
+
+	access.ban(ban(BanTarget::Name(String::from("Griefer")), Some(100)));
+
+	assert_eq!(access.bans.len(), 1);
+	assert!(access.unban(&BanTarget::Name(String::from("GriEfEr"))));
+	assert!(access.find_ban("griefer", None, 0).is_none());
+}
+
+#[test]
+fn whitelist() {
+	let mut access = AccessList::default();
+
+	// com a whitelist desativada, todos podem entrar
+	assert!(access.is_whitelisted("alice"));
+
+	access.whitelist_enabled = true;
+	access.whitelist_add("alice");
+
+	assert!(access.is_whitelisted("alice"));
+	assert!(access.is_whitelisted("Alice"));
+	assert!(!access.is_whitelisted("bob"));
+
+	assert!(access.whitelist_remove("ALICE"));
+	assert!(!access.is_whitelisted("alice"));
+}
@@ -5,18 +5,73 @@ use common::{
 	
 	msg::{
 		ServerMsg,
-		ClientMsg
+		ClientMsg,
+		DisconnectReason,
+		ChatChannel
 	},
 
 	net::PostBox
 };
 
-use crate::Error;
+use crate::{
+	Error,
+
+	access::Role
+};
 
 pub struct Client {
 	pub ecs_entity: EcsEntity,
+	pub username: String,
+	pub role: Role,
+	pub postbox: PostBox<ServerMsg, ClientMsg>,
+	pub last_ping: f64,
+
+	// quantas mensagens de chat o client ainda pode enviar antes de ser limitado, e quando isso foi calculado
+	pub chat_allowance: f64,
+	pub last_chat: f64,
+
+	// preenchido quando o servidor decide desconectar o client, que é removido no próximo tick
+	pub disconnect: Option<DisconnectReason>
+}
+
+impl Client {
+	/// envia uma mensagem do sistema apenas para esse client
+	pub fn notify_system(&mut self, msg: String) {
+		self.postbox.send(ServerMsg::Chat {
+			channel: ChatChannel::System,
+			sender: None,
+			msg
+		});
+	}
+
+	/// consome uma mensagem da cota de chat do client, que é recuperada a `rate` mensagens por segundo
+	/// até o máximo de `burst`
+	///
+	/// retorna `false` caso o client esteja enviando mensagens rápido demais
+	pub fn take_chat_allowance(&mut self, time: f64, rate: f64, burst: f64) -> bool {
+		self.chat_allowance = (self.chat_allowance + (time - self.last_chat) * rate).min(burst);
+		self.last_chat = time;
+
+		if self.chat_allowance >= 1.0 {
+			self.chat_allowance -= 1.0;
+
+			true
+		} else {
+			false
+		}
+	}
+}
+
+/// uma conexão que ainda não completou o handshake e, portanto, não possui entidade
+pub struct PendingClient {
 	pub postbox: PostBox<ServerMsg, ClientMsg>,
-	pub last_ping: f64
+	pub connected_at: f64,
+
+	// preenchido quando o client pede para entrar como jogador
+	pub username: Option<String>,
+
+	// compressão negociada durante o handshake
+	pub compression: bool
 }
 
 pub struct Clients {
@@ -30,20 +85,94 @@ impl Clients {
 		}
 	}
 
+	pub fn len(&self) -> usize {
+		self.clients.len()
+	}
+
 	pub fn add(&mut self, client: Client) {
 		self.clients.push(client);
 	}
 
+	pub fn iter(&self) -> impl Iterator<Item=&Client> {
+		self.clients.iter()
+	}
+
+	pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut Client> {
+		self.clients.iter_mut()
+	}
+
+	pub fn get(&self, ecs_entity: EcsEntity) -> Option<&Client> {
+		self.clients.iter().find(|client| client.ecs_entity == ecs_entity)
+	}
+
+	pub fn get_mut(&mut self, ecs_entity: EcsEntity) -> Option<&mut Client> {
+		self.clients.iter_mut().find(|client| client.ecs_entity == ecs_entity)
+	}
+
+	/// encontra um client conectado por meio do nome de usuário, sem diferenciar maiúsculas e minúsculas
+	pub fn find_by_username(&mut self, username: &str) -> Option<&mut Client> {
+		self.clients.iter_mut().find(|client| client.username.eq_ignore_ascii_case(username))
+	}
+
+	/// verifica se já existe um client conectado com o nome fornecido, sem diferenciar maiúsculas e minúsculas
+	/// para que um jogador não se passe por outro
+	pub fn is_username_taken(&self, username: &str) -> bool {
+		self.clients.iter().any(|client| client.username.eq_ignore_ascii_case(username))
+	}
+
 	pub fn remove_if<F: FnMut(&mut Client) -> bool>(&mut self, f: F) {
 		self.clients.drain_filter(f);
 	}
 
+	/// avisa o client do motivo da desconexão e o marca para ser removido no próximo tick
+	pub fn disconnect(&mut self, ecs_entity: EcsEntity, reason: DisconnectReason) -> bool {
+		match self.get_mut(ecs_entity) {
+			Some(client) => {
+				client.postbox.send(ServerMsg::Disconnect {
+					reason: reason.clone()
+				});
+
+				client.disconnect = Some(reason);
+
+				true
+			},
+
+			None => false
+		}
+	}
+
+	/// avisa todos os clients do motivo da desconexão e os marca para serem removidos no próximo tick
+	pub fn disconnect_all(&mut self, reason: DisconnectReason) {
+		for client in &mut self.clients {
+			client.postbox.send(ServerMsg::Disconnect {
+				reason: reason.clone()
+			});
+
+			client.disconnect = Some(reason.clone());
+		}
+	}
+
+	/// começa a encerrar as conexões de todos os clients, enviando as mensagens na fila de todos ao mesmo tempo
+	pub fn close_all(&mut self) {
+		for client in &mut self.clients {
+			client.postbox.close();
+		}
+	}
+
 	pub fn notify_all(&mut self, msg: ServerMsg) {
 		for client in &mut self.clients {
 			client.postbox.send(msg.clone());
 		}
 	}
 
+	pub fn notify_if<F: FnMut(&Client) -> bool>(&mut self, mut f: F, msg: ServerMsg) {
+		for client in &mut self.clients {
+			if f(client) {
+				client.postbox.send(msg.clone());
+			}
+		}
+	}
+
 	pub fn notify_all_except(&mut self, ecs_entity: EcsEntity, msg: ServerMsg) {
 		for client in &mut self.clients {
 			if client.ecs_entity != ecs_entity {
@@ -0,0 +1,971 @@
+//! comandos executados por mensagens do chat começando com `/`
+//!
+//! cada comando declara os tipos dos seus argumentos e a capacidade necessária para utilizá-lo. a resposta
+//! de um comando é enviada apenas para quem o executou
+
+use std::time::Duration;
+
+use specs::{
+	Builder,
+
+	Entity as EcsEntity
+};
+
+use vek::*;
+
+use common::{
+	comp::{
+		self,
+
+		character
+	},
+
+	weather::{
+		Weather,
+		WeatherKind,
+		WeatherMap,
+
+		ALL_WEATHER_KINDS
+	}
+};
+
+use crate::{
+	Server,
+
+	access::{
+		self,
+
+		Ban,
+		BanTarget,
+		Capability,
+		Role,
+
+		ALL_ROLES
+	}
+};
+
+/// tipo de um argumento de comando
+#[derive(Copy, Clone, Debug)]
+pub enum ArgKind {
+	Int,
+	Float,
+
+	/// uma única palavra
+	Word,
+
+	/// o nome de um jogador conectado
+	Player,
+
+	/// todo o restante da linha
+	Text
+}
+
+pub struct ArgSpec {
+	pub name: &'static str,
+	pub kind: ArgKind,
+	pub optional: bool
+}
+
+const fn arg(name: &'static str, kind: ArgKind) -> ArgSpec {
+	ArgSpec { name, kind, optional: false }
+}
+
+const fn opt(name: &'static str, kind: ArgKind) -> ArgSpec {
+	ArgSpec { name, kind, optional: true }
+}
+
+/// um argumento já convertido para o tipo declarado
+#[derive(Clone, Debug)]
+pub enum Arg {
+	Int(i64),
+	Float(f32),
+	Word(String),
+	Player(EcsEntity),
+	Text(String)
+}
+
+/// executa um comando, recebendo quem o executou (`None` para o console do servidor) e os argumentos
+/// na ordem em que foram declarados, com `None` para argumentos opcionais omitidos
+pub type CommandHandler = fn(&mut Server, Option<EcsEntity>, &[Option<Arg>]) -> Result<String, String>;
+
+pub struct ChatCommand {
+	pub keyword: &'static str,
+	pub args: &'static [ArgSpec],
+	pub description: &'static str,
+	/// capacidade necessária para utilizar o comando, `None` para comandos disponíveis a todos
+	pub capability: Option<Capability>,
+	pub handler: CommandHandler
+}
+
+impl ChatCommand {
+	/// descreve como o comando deve ser utilizado, ex: `/kick <jogador> [motivo]`
+	pub fn usage(&self) -> String {
+		let mut usage = format!("/{}", self.keyword);
+
+		for spec in self.args {
+			if spec.optional {
+				usage.push_str(&format!(" [{}]", spec.name));
+			} else {
+				usage.push_str(&format!(" <{}>", spec.name));
+			}
+		}
+
+		usage
+	}
+
+	/// verifica se quem executa o comando possui a capacidade necessária
+	pub fn is_allowed(&self, server: &Server, issuer: Option<EcsEntity>) -> bool {
+		self.capability
+			.map(|capability| server.has_capability(issuer, capability))
+			.unwrap_or(true)
+	}
+
+	/// converte os argumentos escritos nos tipos declarados pelo comando
+	fn parse_args(&self, server: &Server, input: &str) -> Result<Vec<Option<Arg>>, String> {
+		let mut rest = input.trim();
+		let mut args = Vec::with_capacity(self.args.len());
+
+		for spec in self.args {
+			if rest.is_empty() {
+				if spec.optional {
+					args.push(None);
+
+					continue;
+				} else {
+					return Err(format!("argumento '{}' faltando, uso: {}", spec.name, self.usage()));
+				}
+			}
+
+			// argumentos de texto consomem o restante da linha
+			let word = match spec.kind {
+				ArgKind::Text => {
+					let text = rest;
+
+					rest = "";
+
+					text
+				},
+
+				_ => {
+					let mut split = rest.splitn(2, char::is_whitespace);
+					let word = split.next().unwrap_or("");
+
+					rest = split.next().unwrap_or("").trim_start();
+
+					word
+				}
+			};
+
+			let invalid = || format!("argumento '{}' inválido: '{}', uso: {}", spec.name, word, self.usage());
+
+			args.push(Some(match spec.kind {
+				ArgKind::Int => Arg::Int(word.parse().map_err(|_| invalid())?),
+				// `NaN` e `inf` também são lidos como `f32`, mas nenhum comando aceita valores infinitos
+				ArgKind::Float => Arg::Float(
+					word.parse::<f32>()
+						.ok()
+						.filter(|value| value.is_finite())
+						.ok_or_else(invalid)?
+				),
+				ArgKind::Word => Arg::Word(word.to_string()),
+				ArgKind::Text => Arg::Text(word.to_string()),
+
+				ArgKind::Player => Arg::Player(
+					server.clients
+						.iter()
+						.find(|client| client.username.eq_ignore_ascii_case(word))
+						.map(|client| client.ecs_entity)
+						.ok_or_else(|| format!("jogador '{}' não encontrado", word))?
+				)
+			}));
+		}
+
+		if rest.is_empty() {
+			Ok(args)
+		} else {
+			Err(format!("argumentos demais, uso: {}", self.usage()))
+		}
+	}
+}
+
+/// todos os comandos disponíveis
+pub const COMMANDS: &[ChatCommand] = &[
+	ChatCommand {
+		keyword: "help",
+		args: &[opt("comando", ArgKind::Word)],
+		description: "lista os comandos disponíveis ou mostra como utilizar um comando",
+		capability: None,
+		handler: handle_help
+	},
+
+	ChatCommand {
+		keyword: "list",
+		args: &[],
+		description: "lista os jogadores conectados",
+		capability: None,
+		handler: handle_list
+	},
+
+	ChatCommand {
+		keyword: "tp",
+		args: &[arg("x", ArgKind::Float), arg("y", ArgKind::Float), arg("z", ArgKind::Float), opt("jogador", ArgKind::Player)],
+		description: "teleporta você ou outro jogador para a posição fornecida",
+		capability: Some(Capability::Teleport),
+		handler: handle_tp
+	},
+
+	ChatCommand {
+		keyword: "time",
+		args: &[opt("hora", ArgKind::Float)],
+		description: "mostra ou altera a hora do dia",
+		capability: Some(Capability::SetTime),
+		handler: handle_time
+	},
+
+	ChatCommand {
+		keyword: "weather",
+		args: &[opt("clima|auto", ArgKind::Word), opt("intensidade", ArgKind::Float)],
+		description: "mostra ou altera o clima da sua região",
+		capability: Some(Capability::SetWeather),
+		handler: handle_weather
+	},
+
+	ChatCommand {
+		keyword: "kick",
+		args: &[arg("jogador", ArgKind::Player), opt("motivo", ArgKind::Text)],
+		description: "expulsa um jogador do servidor",
+		capability: Some(Capability::Kick),
+		handler: handle_kick
+	},
+
+	ChatCommand {
+		keyword: "ban",
+		args: &[arg("nome", ArgKind::Word), arg("minutos", ArgKind::Int), opt("motivo", ArgKind::Text)],
+		description: "bane um jogador pelo nome, por alguns minutos ou permanentemente com 0",
+		capability: Some(Capability::Ban),
+		handler: handle_ban
+	},
+
+	ChatCommand {
+		keyword: "banip",
+		args: &[arg("jogador ou ip", ArgKind::Word), arg("minutos", ArgKind::Int), opt("motivo", ArgKind::Text)],
+		description: "bane um endereço ip, por alguns minutos ou permanentemente com 0",
+		capability: Some(Capability::Ban),
+		handler: handle_ban_ip
+	},
+
+	ChatCommand {
+		keyword: "unban",
+		args: &[arg("nome", ArgKind::Word)],
+		description: "remove o banimento de um nome",
+		capability: Some(Capability::Ban),
+		handler: handle_unban
+	},
+
+	ChatCommand {
+		keyword: "unbanip",
+		args: &[arg("ip", ArgKind::Word)],
+		description: "remove o banimento de um endereço ip",
+		capability: Some(Capability::Ban),
+		handler: handle_unban_ip
+	},
+
+	ChatCommand {
+		keyword: "whitelist",
+		args: &[arg("on|off|add|remove|list", ArgKind::Word), opt("nome", ArgKind::Word)],
+		description: "gerencia a whitelist do servidor",
+		capability: Some(Capability::ManageWhitelist),
+		handler: handle_whitelist
+	},
+
+	ChatCommand {
+		keyword: "role",
+		args: &[arg("nome", ArgKind::Word), opt("cargo", ArgKind::Word)],
+		description: "mostra ou altera o cargo de um jogador",
+		capability: Some(Capability::ManageRoles),
+		handler: handle_role
+	},
+
+	ChatCommand {
+		keyword: "say",
+		args: &[arg("mensagem", ArgKind::Text)],
+		description: "envia um anúncio do servidor para todos os jogadores",
+		capability: Some(Capability::Broadcast),
+		handler: handle_say
+	},
+
+	ChatCommand {
+		keyword: "spawn",
+		args: &[opt("quantidade", ArgKind::Int)],
+		description: "cria personagens na sua posição",
+		capability: Some(Capability::Spawn),
+		handler: handle_spawn
+	},
+
+	ChatCommand {
+		keyword: "give",
+		args: &[arg("arma", ArgKind::Word), opt("jogador", ArgKind::Player)],
+		description: "equipa uma arma em você ou em outro jogador",
+		capability: Some(Capability::Give),
+		handler: handle_give
+	},
+
+	ChatCommand {
+		keyword: "save",
+		args: &[],
+		description: "salva o estado do mundo",
+		capability: Some(Capability::Save),
+		handler: handle_save
+	},
+
+	ChatCommand {
+		keyword: "stop",
+		args: &[opt("segundos", ArgKind::Int)],
+		description: "desliga o servidor após uma contagem regressiva",
+		capability: Some(Capability::Stop),
+		handler: handle_stop
+	}
+];
+
+// número máximo de personagens criados por um único `/spawn`
+const MAX_SPAWN_AMOUNT: i64 = 16;
+
+// contagem regressiva padrão do `/stop`, em segundos
+const DEFAULT_SHUTDOWN_COUNTDOWN: u64 = 10;
+
+// duração máxima de um banimento temporário, cerca de 100 anos
+const MAX_BAN_MINUTES: i64 = 100 * 365 * 24 * 60;
+
+/// executa uma linha de comando, com ou sem a `/` inicial, em nome de quem a enviou
+pub fn execute(server: &mut Server, issuer: Option<EcsEntity>, input: &str) -> Result<String, String> {
+	let input = input.trim();
+	let input = input.trim_start_matches('/');
+
+	let mut split = input.splitn(2, char::is_whitespace);
+
+	let keyword = split.next().unwrap_or("");
+	let args = split.next().unwrap_or("");
+
+	let cmd = COMMANDS
+		.iter()
+		.find(|cmd| cmd.keyword == keyword)
+		.ok_or_else(|| format!("comando desconhecido '/{}', utilize /help", keyword))?;
+
+	if !cmd.is_allowed(server, issuer) {
+		return Err(format!("você não tem permissão para utilizar /{}", cmd.keyword));
+	}
+
+	let args = cmd.parse_args(server, args)?;
+
+	(cmd.handler)(server, issuer, &args)
+}
+
+/// obtém o alvo de um comando: o jogador fornecido ou, caso omitido, quem o executou
+fn target_or_issuer(target: Option<&Arg>, issuer: Option<EcsEntity>) -> Result<EcsEntity, String> {
+	match (target, issuer) {
+		(Some(Arg::Player(target)), _) => Ok(*target),
+		(_, Some(issuer)) => Ok(issuer),
+
+		_ => Err(String::from("é necessário fornecer um jogador quando o comando é executado pelo console"))
+	}
+}
+
+fn handle_help(server: &mut Server, issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	match &args[0] {
+		Some(Arg::Word(keyword)) => COMMANDS
+			.iter()
+			.find(|cmd| cmd.keyword == keyword.trim_start_matches('/'))
+			.map(|cmd| format!("{} - {}", cmd.usage(), cmd.description))
+			.ok_or_else(|| format!("comando desconhecido '/{}'", keyword)),
+
+		_ => Ok(COMMANDS
+			.iter()
+			.filter(|cmd| cmd.is_allowed(server, issuer))
+			.map(|cmd| format!("{} - {}", cmd.usage(), cmd.description))
+			.collect::<Vec<_>>()
+			.join("\n"))
+	}
+}
+
+fn handle_list(server: &mut Server, _issuer: Option<EcsEntity>, _args: &[Option<Arg>]) -> Result<String, String> {
+	let names = server.clients
+		.iter()
+		.map(|client| client.username.as_str())
+		.collect::<Vec<_>>();
+
+	Ok(format!(
+		"{}/{} jogadores conectados: {}",
+		names.len(),
+		server.settings.max_players,
+		names.join(", ")
+	))
+}
+
+fn handle_tp(server: &mut Server, issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	let target = target_or_issuer(args[3].as_ref(), issuer)?;
+
+	let pos = match (&args[0], &args[1], &args[2]) {
+		(Some(Arg::Float(x)), Some(Arg::Float(y)), Some(Arg::Float(z))) => Vec3::new(*x, *y, *z),
+
+		_ => unreachable!("argumentos já validados")
+	};
+
+	server.state.write_component(target, comp::phys::Pos(pos));
+
+	// forçar a atualização para que o próprio jogador também receba a nova posição
+	server.state.write_component(target, comp::phys::UpdateKind::Force);
+
+	Ok(format!("teleportado para {}, {}, {}", pos.x, pos.y, pos.z))
+}
+
+fn handle_time(server: &mut Server, _issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+	let time_of_day = server.state.get_time_of_day();
+
+	match &args[0] {
+		Some(Arg::Float(hour)) if *hour >= 0.0 && *hour < 24.0 => {
+			// manter o número de dias já passados, alterando apenas a hora
+			let day = (time_of_day / SECONDS_PER_DAY).floor();
+
+			server.set_time_of_day(day * SECONDS_PER_DAY + *hour as f64 * 3600.0);
+
+			Ok(format!("hora alterada para {:.2}", hour))
+		},
+
+		Some(_) => Err(String::from("a hora deve estar entre 0 e 24")),
+
+		None => Ok(format!("são {:.2} horas", (time_of_day % SECONDS_PER_DAY) / 3600.0))
+	}
+}
+
+fn handle_weather(server: &mut Server, issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	// o console altera a região em volta da origem
+	let region = issuer
+		.and_then(|issuer| server.state.read_storage::<comp::phys::Pos>().get(issuer).cloned())
+		.map(|pos| WeatherMap::region_of(pos.0))
+		.unwrap_or(Vec2::zero());
+
+	let intensity = match &args[1] {
+		Some(Arg::Float(intensity)) if *intensity >= 0.0 && *intensity <= 1.0 => *intensity,
+		Some(_) => return Err(String::from("a intensidade deve estar entre 0 e 1")),
+
+		None => 1.0
+	};
+
+	match &args[0] {
+		Some(Arg::Word(name)) if name == "auto" => {
+			server.set_weather(region, None);
+
+			Ok(String::from("o clima da região voltou a ser simulado"))
+		},
+
+		Some(Arg::Word(name)) => {
+			let kind = WeatherKind::from_name(name)
+				.ok_or_else(|| format!(
+					"clima desconhecido '{}', disponíveis: {}, auto",
+					name,
+					ALL_WEATHER_KINDS.iter().map(|kind| kind.name()).collect::<Vec<_>>().join(", ")
+				))?;
+
+			server.set_weather(region, Some(Weather::new(kind, intensity)));
+
+			Ok(format!("clima alterado para {} com intensidade {:.2}", kind.name(), intensity))
+		},
+
+		_ => {
+			let weather = server.weather(region);
+
+			Ok(format!("clima atual: {} com intensidade {:.2}", weather.kind.name(), weather.intensity))
+		}
+	}
+}
+
+/// recusa ações contra jogadores com cargo igual ou maior que o de quem as executa. o console pode agir contra todos
+fn check_outranks(server: &Server, issuer: Option<EcsEntity>, target: &str, target_role: Role) -> Result<(), String> {
+	let issuer_role = match issuer {
+		Some(issuer) => server.clients
+			.get(issuer)
+			.map(|client| client.role)
+			.unwrap_or(Role::Player),
+
+		None => return Ok(())
+	};
+
+	if target_role >= issuer_role {
+		Err(format!("{} é {} e não pode ser alvo do seu cargo", target, target_role.name()))
+	} else {
+		Ok(())
+	}
+}
+
+fn handle_kick(server: &mut Server, issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	let target = match &args[0] {
+		Some(Arg::Player(target)) => *target,
+
+		_ => unreachable!("argumentos já validados")
+	};
+
+	if let Some(client) = server.clients.get(target) {
+		check_outranks(server, issuer, &client.username, client.role)?;
+	}
+
+	let reason = match &args[1] {
+		Some(Arg::Text(reason)) => reason.clone(),
+
+		_ => String::from("sem motivo")
+	};
+
+	let username = server.clients
+		.get(target)
+		.map(|client| client.username.clone())
+		.unwrap_or_default();
+
+	server.kick(target, reason);
+
+	Ok(format!("{} foi expulso", username))
+}
+
+/// converte a duração de um banimento em minutos no momento em que ele expira, sendo 0 permanente
+fn ban_expiry(minutes: &Option<Arg>) -> Result<Option<u64>, String> {
+	match minutes {
+		Some(Arg::Int(0)) => Ok(None),
+
+		Some(Arg::Int(minutes)) if *minutes > 0 && *minutes <= MAX_BAN_MINUTES => (*minutes as u64)
+			.checked_mul(60)
+			.map(|secs| Some(access::unix_now().saturating_add(secs)))
+			.ok_or_else(|| String::from("duração do banimento fora do limite")),
+
+		Some(Arg::Int(minutes)) if *minutes > MAX_BAN_MINUTES => Err(format!(
+			"a duração máxima de um banimento é de {} minutos, utilize 0 para um banimento permanente",
+			MAX_BAN_MINUTES
+		)),
+
+		_ => Err(String::from("a duração deve ser um número positivo de minutos, ou 0 para um banimento permanente"))
+	}
+}
+
+fn ban_reason(reason: &Option<Arg>) -> String {
+	match reason {
+		Some(Arg::Text(reason)) => reason.clone(),
+
+		_ => String::from("sem motivo")
+	}
+}
+
+fn handle_ban(server: &mut Server, issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	let name = match &args[0] {
+		Some(Arg::Word(name)) => name.clone(),
+
+		_ => unreachable!("argumentos já validados")
+	};
+
+	check_outranks(server, issuer, &name, server.access().role(&name))?;
+
+	let ban = Ban {
+		target: BanTarget::Name(name.clone()),
+		reason: ban_reason(&args[2]),
+		expires_at: ban_expiry(&args[1])?
+	};
+
+	server.modify_access(|access| access.ban(ban))
+		.map_err(|err| format!("falha ao salvar o banimento: {:?}", err))?;
+
+	Ok(format!("{} foi banido", name))
+}
+
+fn handle_ban_ip(server: &mut Server, issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	let target = match &args[0] {
+		Some(Arg::Word(target)) => target.clone(),
+
+		_ => unreachable!("argumentos já validados")
+	};
+
+	// aceitar tanto o nome de um jogador conectado quanto um endereço
+	let ip = server.clients
+		.iter()
+		.find(|client| client.username.eq_ignore_ascii_case(&target))
+		.and_then(|client| client.postbox.peer_addr())
+		.map(|addr| addr.ip())
+		.or_else(|| target.parse().ok())
+		.ok_or_else(|| format!("'{}' não é um jogador conectado nem um endereço ip", target))?;
+
+	// o banimento desconecta todos os jogadores do endereço
+	for client in server.clients.iter().filter(|client| client.postbox.peer_addr().map(|addr| addr.ip()) == Some(ip)) {
+		check_outranks(server, issuer, &client.username, client.role)?;
+	}
+
+	let ban = Ban {
+		target: BanTarget::Ip(ip),
+		reason: ban_reason(&args[2]),
+		expires_at: ban_expiry(&args[1])?
+	};
+
+	server.modify_access(|access| access.ban(ban))
+		.map_err(|err| format!("falha ao salvar o banimento: {:?}", err))?;
+
+	Ok(format!("{} foi banido", ip))
+}
+
+fn handle_unban(server: &mut Server, _issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	let name = match &args[0] {
+		Some(Arg::Word(name)) => name.clone(),
+
+		_ => unreachable!("argumentos já validados")
+	};
+
+	let mut found = false;
+
+	server.modify_access(|access| found = access.unban(&BanTarget::Name(name.clone())))
+		.map_err(|err| format!("falha ao salvar o banimento: {:?}", err))?;
+
+	if found {
+		Ok(format!("{} não está mais banido", name))
+	} else {
+		Err(format!("{} não está banido", name))
+	}
+}
+
+fn handle_unban_ip(server: &mut Server, _issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	let ip = match &args[0] {
+		Some(Arg::Word(ip)) => ip.parse().map_err(|_| format!("endereço ip inválido: '{}'", ip))?,
+
+		_ => unreachable!("argumentos já validados")
+	};
+
+	let mut found = false;
+
+	server.modify_access(|access| found = access.unban(&BanTarget::Ip(ip)))
+		.map_err(|err| format!("falha ao salvar o banimento: {:?}", err))?;
+
+	if found {
+		Ok(format!("{} não está mais banido", ip))
+	} else {
+		Err(format!("{} não está banido", ip))
+	}
+}
+
+fn handle_whitelist(server: &mut Server, _issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	let name = match &args[1] {
+		Some(Arg::Word(name)) => Some(name.clone()),
+
+		_ => None
+	};
+
+	let response = match (&args[0], name) {
+		(Some(Arg::Word(action)), None) if action == "list" => {
+			let access = server.access();
+
+			let mut names = access.whitelist.iter().map(|name| name.as_str()).collect::<Vec<_>>();
+
+			names.sort();
+
+			return Ok(format!(
+				"whitelist {}: {}",
+				if access.whitelist_enabled { "ativada" } else { "desativada" },
+				names.join(", ")
+			));
+		},
+
+		(Some(Arg::Word(action)), None) if action == "on" => {
+			server.modify_access(|access| access.whitelist_enabled = true)
+				.map(|()| String::from("whitelist ativada"))
+		},
+
+		(Some(Arg::Word(action)), None) if action == "off" => {
+			server.modify_access(|access| access.whitelist_enabled = false)
+				.map(|()| String::from("whitelist desativada"))
+		},
+
+		(Some(Arg::Word(action)), Some(name)) if action == "add" => {
+			server.modify_access(|access| access.whitelist_add(&name))
+				.map(|()| format!("{} adicionado à whitelist", name))
+		},
+
+		(Some(Arg::Word(action)), Some(name)) if action == "remove" => {
+			server.modify_access(|access| { access.whitelist_remove(&name); })
+				.map(|()| format!("{} removido da whitelist", name))
+		},
+
+		_ => return Err(String::from("uso: /whitelist on|off|list ou /whitelist add|remove <nome>"))
+	};
+
+	response.map_err(|err| format!("falha ao salvar a whitelist: {:?}", err))
+}
+
+fn handle_role(server: &mut Server, _issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	let name = match &args[0] {
+		Some(Arg::Word(name)) => name.clone(),
+
+		_ => unreachable!("argumentos já validados")
+	};
+
+	match &args[1] {
+		Some(Arg::Word(role_name)) => {
+			let role = Role::from_name(role_name)
+				.ok_or_else(|| format!(
+					"cargo desconhecido '{}', disponíveis: {}",
+					role_name,
+					ALL_ROLES.iter().map(|role| role.name()).collect::<Vec<_>>().join(", ")
+				))?;
+
+			let mut set = false;
+
+			server.modify_access(|access| set = access.set_role(&name, role))
+				.map_err(|err| format!("falha ao salvar o cargo: {:?}", err))?;
+
+			// cargos só valem para nomes protegidos por senha, então o dono do nome precisa se registrar antes
+			if set {
+				Ok(format!("{} agora é {}", name, role.name()))
+			} else {
+				Err(format!("{} não está registrado, e precisa entrar no servidor com uma senha antes", name))
+			}
+		},
+
+		_ => Ok(format!("{} é {}", name, server.access().role(&name).name()))
+	}
+}
+
+fn handle_say(server: &mut Server, _issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	match &args[0] {
+		Some(Arg::Text(msg)) => {
+			server.announce(msg.clone());
+
+			Ok(String::from("anúncio enviado"))
+		},
+
+		_ => unreachable!("argumentos já validados")
+	}
+}
+
+fn handle_spawn(server: &mut Server, issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	let amount = match &args[0] {
+		Some(Arg::Int(amount)) if *amount > 0 && *amount <= MAX_SPAWN_AMOUNT => *amount,
+		Some(_) => return Err(format!("a quantidade deve estar entre 1 e {}", MAX_SPAWN_AMOUNT)),
+
+		None => 1
+	};
+
+	let pos = issuer
+		.and_then(|issuer| server.state.read_storage::<comp::phys::Pos>().get(issuer).cloned())
+		.unwrap_or(comp::phys::Pos(Vec3::zero()));
+
+	for _ in 0..amount {
+		server.build_player()
+			.with(pos)
+			.with(comp::phys::UpdateKind::Force)
+			.with(comp::Character::default())
+			.build();
+	}
+
+	Ok(format!("{} personagens criados", amount))
+}
+
+fn handle_give(server: &mut Server, issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	let target = target_or_issuer(args[1].as_ref(), issuer)?;
+
+	let name = match &args[0] {
+		Some(Arg::Word(name)) => name.to_lowercase(),
+
+		_ => unreachable!("argumentos já validados")
+	};
+
+	let weapon = character::ALL_WEAPONS
+		.iter()
+		.find(|weapon| format!("{:?}", weapon).to_lowercase() == name)
+		.cloned()
+		.ok_or_else(|| format!(
+			"arma desconhecida '{}', disponíveis: {}",
+			name,
+			character::ALL_WEAPONS
+				.iter()
+				.map(|weapon| format!("{:?}", weapon).to_lowercase())
+				.collect::<Vec<_>>()
+				.join(", ")
+		))?;
+
+	let mut character = server.state
+		.read_storage::<comp::Character>()
+		.get(target)
+		.cloned()
+		.unwrap_or_else(comp::Character::default);
+
+	character.weapon = weapon;
+
+	server.state.write_component(target, character);
+
+	Ok(format!("{:?} equipada", weapon))
+}
+
+fn handle_save(server: &mut Server, _issuer: Option<EcsEntity>, _args: &[Option<Arg>]) -> Result<String, String> {
+	server.save_world()
+		.map(|()| format!("mundo salvo em '{}'", server.settings.save_file))
+		.map_err(|err| format!("falha ao salvar o mundo: {:?}", err))
+}
+
+fn handle_stop(server: &mut Server, _issuer: Option<EcsEntity>, args: &[Option<Arg>]) -> Result<String, String> {
+	let secs = match &args[0] {
+		Some(Arg::Int(secs)) if *secs >= 0 => *secs as u64,
+		Some(_) => return Err(String::from("a contagem regressiva não pode ser negativa")),
+
+		None => DEFAULT_SHUTDOWN_COUNTDOWN
+	};
+
+	server.shutdown_in(Duration::from_secs(secs));
+
+	Ok(format!("desligando o servidor em {} segundos...", secs))
+}
+
+// testes
+
+#[cfg(test)]
+use crate::{
+	test_server,
+	connect_player
+};
+
+#[cfg(test)]
+const TEST_ARGS: &[ArgSpec] = &[
+	arg("n", ArgKind::Int),
+	arg("f", ArgKind::Float),
+	arg("palavra", ArgKind::Word),
+	opt("jogador", ArgKind::Player),
+	opt("texto", ArgKind::Text)
+];
+
+#[cfg(test)]
+const TEST_COMMAND: ChatCommand = ChatCommand {
+	keyword: "teste",
+	args: TEST_ARGS,
+	description: "comando de teste",
+	capability: None,
+	handler: handle_list
+};
+
+#[test]
+fn parse_args_kinds() {
+	let mut server = test_server(59100);
+	let (_postbox, alice) = connect_player(&mut server, 59100, "alice", None);
+
+	let args = TEST_COMMAND.parse_args(&server, "  -3 2.5 palavra alice o resto   da linha").unwrap();
+
+	match &args[..] {
+		[
+			Some(Arg::Int(-3)),
+			Some(Arg::Float(f)),
+			Some(Arg::Word(word)),
+			Some(Arg::Player(player)),
+			Some(Arg::Text(text))
+		] => {
+			assert_eq!(*f, 2.5);
+			assert_eq!(word, "palavra");
+			assert_eq!(*player, alice);
+			assert_eq!(text, "o resto   da linha");
+		},
+
+		args => panic!("argumentos inesperados {:?}", args)
+	}
+
+	// argumentos opcionais omitidos são `None`
+	match &TEST_COMMAND.parse_args(&server, "1 1 a").unwrap()[..] {
+		[Some(_), Some(_), Some(_), None, None] => {},
+
+		args => panic!("argumentos inesperados {:?}", args)
+	}
+}
+
+#[test]
+fn parse_args_errors() {
+	let mut server = test_server(59101);
+	let _alice = connect_player(&mut server, 59101, "alice", None);
+
+	// argumentos obrigatórios faltando
+	assert!(TEST_COMMAND.parse_args(&server, "").is_err());
+	assert!(TEST_COMMAND.parse_args(&server, "1 2.0").is_err());
+
+	// tipos inválidos
+	assert!(TEST_COMMAND.parse_args(&server, "1.5 2.0 a").is_err());
+	assert!(TEST_COMMAND.parse_args(&server, "1 dois a").is_err());
+	assert!(TEST_COMMAND.parse_args(&server, "99999999999999999999 2.0 a").is_err());
+
+	// valores que não são finitos
+	assert!(TEST_COMMAND.parse_args(&server, "1 NaN a").is_err());
+	assert!(TEST_COMMAND.parse_args(&server, "1 inf a").is_err());
+	assert!(TEST_COMMAND.parse_args(&server, "1 -inf a").is_err());
+
+	// jogadores que não estão conectados
+	assert!(TEST_COMMAND.parse_args(&server, "1 2.0 a bob").is_err());
+
+	// argumentos demais para um comando sem texto no final
+	let tp = COMMANDS.iter().find(|cmd| cmd.keyword == "tp").unwrap();
+
+	assert!(tp.parse_args(&server, "1 2 3").is_ok());
+	assert!(tp.parse_args(&server, "1 2 3 alice").is_ok());
+	assert!(tp.parse_args(&server, "1 2 3 alice extra").is_err());
+}
+
+#[test]
+fn execute_checks_capabilities() {
+	let mut server = test_server(59102);
+
+	// os cargos só valem para quem entra com a senha da conta
+	let (_player_pb, player) = connect_player(&mut server, 59102, "jogador", None);
+	let (_moderator_pb, moderator) = connect_player(&mut server, 59102, "moderador", Some("senha"));
+	let (_admin_pb, admin) = connect_player(&mut server, 59102, "admin", Some("senha"));
+
+	server.modify_access(|access| {
+		assert!(access.set_role("moderador", Role::Moderator));
+		assert!(access.set_role("Admin", Role::Admin));
+	}).unwrap();
+
+	// comandos sem capacidade estão disponíveis para todos
+	assert!(execute(&mut server, Some(player), "/list").is_ok());
+
+	// jogadores comuns não podem utilizar comandos privilegiados, mesmo com argumentos inválidos
+	assert!(execute(&mut server, Some(player), "/tp 1 2 3").unwrap_err().contains("permissão"));
+	assert!(execute(&mut server, Some(player), "/tp").unwrap_err().contains("permissão"));
+
+	// moderadores podem teleportar, mas apenas administradores salvam o mundo
+	assert!(execute(&mut server, Some(moderator), "/tp 1 2 3").is_ok());
+	assert!(execute(&mut server, Some(moderator), "/save").unwrap_err().contains("permissão"));
+
+	// ninguém expulsa ou bane um cargo igual ou maior que o seu, mesmo trocando as maiúsculas do nome
+	assert!(execute(&mut server, Some(moderator), "/kick ADMIN").is_err());
+	assert!(execute(&mut server, Some(moderator), "/ban Admin 0").is_err());
+	assert!(execute(&mut server, Some(moderator), "/ban moderador 0").is_err());
+	assert!(execute(&mut server, Some(moderator), "/banip admin 0").is_err());
+	assert!(server.access().bans.is_empty());
+	assert!(server.clients.get(admin).is_some());
+
+	assert!(execute(&mut server, Some(moderator), "/ban griefer 5").is_ok());
+	assert!(server.access().find_ban("Griefer", None, access::unix_now()).is_some());
+	assert!(execute(&mut server, Some(admin), "/role jogador").is_ok());
+
+	// nomes sem conta não recebem cargos
+	assert!(execute(&mut server, Some(admin), "/role jogador moderador").is_err());
+	assert!(execute(&mut server, Some(admin), "/role moderador admin").is_ok());
+	assert!(server.has_capability(Some(moderator), Capability::Save));
+
+	// o console possui todas as capacidades
+	assert!(execute(&mut server, None, "/tp 1 2 3 jogador").is_ok());
+
+	// comandos desconhecidos e argumentos inválidos são recusados sem executar nada
+	assert!(execute(&mut server, None, "/naoexiste").is_err());
+	assert!(execute(&mut server, Some(moderator), "/tp NaN 0 0").is_err());
+}
+
+#[test]
+fn ban_duration_limits() {
+	assert_eq!(ban_expiry(&Some(Arg::Int(0))), Ok(None));
+
+	let now = access::unix_now();
+	let expires_at = ban_expiry(&Some(Arg::Int(10))).unwrap().unwrap();
+
+	assert!(expires_at >= now + 600 && expires_at <= access::unix_now() + 600);
+
+	// durações enormes ou negativas são recusadas em vez de estourar
+	assert!(ban_expiry(&Some(Arg::Int(i64::max_value()))).is_err());
+	assert!(ban_expiry(&Some(Arg::Int(MAX_BAN_MINUTES + 1))).is_err());
+	assert!(ban_expiry(&Some(Arg::Int(-5))).is_err());
+	assert!(ban_expiry(&Some(Arg::Int(MAX_BAN_MINUTES))).is_ok());
+}
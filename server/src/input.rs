@@ -1,9 +1,12 @@
 pub struct Input {
-	// todo: utilizar esse tipo para gerenciar input de servidor
+	/// comandos digitados no console do servidor, executados com permissão de administrador
+	pub commands: Vec<String>
 }
 
 impl Default for Input {
 	fn default() -> Self {
-		Input {}
+		Input {
+			commands: Vec::new()
+		}
 	}
 }
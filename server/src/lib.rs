@@ -1,18 +1,27 @@
 #![feature(drain_filter)]
 
+#[macro_use]
+extern crate serde_derive;
+
+pub mod access;
 pub mod client;
+pub mod cmd;
 pub mod error;
 pub mod input;
+pub mod save;
+pub mod settings;
+pub mod weather;
 
 // re-exportações
 pub use crate::{
     error::Error,
-    input::Input
+    input::Input,
+    settings::ServerSettings
 };
 
 use std::{
     time::Duration,
-    net::SocketAddr
+    collections::HashSet
 };
 
 use specs::{
@@ -29,59 +38,244 @@ use common::{
     comp,
     
     state::State,
-    net::PostOffice,
+
+    weather::{
+        Weather,
+        WeatherMap
+    },
+
+    net::{
+        PostOffice,
+        ServerTls
+    },
 
     msg::{
         ServerMsg,
-        ClientMsg
+        ServerInfo,
+        ClientMsg,
+        DisconnectReason,
+        ChatChannel,
+
+        PROTOCOL_VERSION,
+        MAX_CHAT_MSG_LEN,
+
+        is_valid_username
     }
 };
 
 use world::World;
 
-use crate::client::{
-    Client,
-    Clients
+use crate::{
+    save::WorldSave,
+
+    access::{
+        AccessList,
+        Authentication,
+        Capability
+    },
+
+    client::{
+        Client,
+        Clients,
+        PendingClient
+    },
+
+    weather::WeatherSim
 };
 
 const CLIENT_TIMEOUT: f64 = 5.0; // segundos
 
+// tempo que uma conexão pode permanecer sem completar o handshake
+const HANDSHAKE_TIMEOUT: f64 = 5.0; // segundos
+
+// limite de flood do chat: mensagens recuperadas por segundo e quantas podem ser enviadas de uma vez
+const CHAT_RATE: f64 = 1.0;
+const CHAT_BURST: f64 = 5.0;
+
+// distância máxima, em blocos, que uma mensagem do chat local alcança
+const LOCAL_CHAT_RADIUS: f32 = 64.0;
+
+// segundos restantes em que a contagem regressiva do desligamento é anunciada aos jogadores
+const SHUTDOWN_ANNOUNCEMENTS: [u64; 8] = [60, 30, 10, 5, 4, 3, 2, 1];
+
+// intervalo em que a hora do dia é reenviada aos clients, corrigindo a diferença acumulada entre os relógios
+const TIME_SYNC_INTERVAL: f64 = 10.0; // segundos
+
+// semente da simulação do clima, fixa para que o clima de cada região não mude ao reiniciar o servidor
+const WEATHER_SEED: u32 = 0x1e57_1a1a;
+
 pub enum Event {
     ClientConnected {
         ecs_entity: EcsEntity
     },
 
     ClientDisconnected {
-        ecs_entity: EcsEntity
+        ecs_entity: EcsEntity,
+        reason: DisconnectReason
     },
 
     Chat {
         ecs_entity: EcsEntity,
+        username: String,
+        channel: ChatChannel,
         msg: String
-    }
+    },
+
+    /// resposta de um comando digitado no console
+    ConsoleResponse(Result<String, String>),
+
+    /// a contagem regressiva do desligamento terminou, e o frontend deve chamar `Server::shutdown`
+    Shutdown
 }
 
 pub struct Server {
+    settings: ServerSettings,
+
     state: State,
     world: World,
 
     postoffice: PostOffice<ServerMsg, ClientMsg>,
-    clients: Clients
+    pending_clients: Vec<PendingClient>,
+    clients: Clients,
+
+    world_save: WorldSave,
+    access: AccessList,
+
+    // tempo do jogo em que o servidor deve ser desligado, e o último anúncio da contagem regressiva
+    shutdown_at: Option<f64>,
+    shutdown_announced: Option<u64>,
+
+    // tempo do jogo em que a hora do dia foi enviada a todos os clients pela última vez
+    last_time_sync: f64,
+
+    weather: WeatherSim
 }
 
 impl Server {
-    /// cria um novo servidor
+    /// cria um novo servidor com as configurações padrões
     #[allow(dead_code)]
     pub fn new() -> Result<Self, Error> {
+        Self::with_settings(ServerSettings::default())
+    }
+
+    /// cria um novo servidor com as configurações fornecidas
+    #[allow(dead_code)]
+    pub fn with_settings(settings: ServerSettings) -> Result<Self, Error> {
+        settings.validate()?;
+
+        let world_save = WorldSave::load(&settings.save_file)?.unwrap_or_default();
+        let access = AccessList::load(&settings.access_file)?;
+
+        let mut state = State::new();
+
+        state.set_time_of_day(world_save.time_of_day);
+
+        let postoffice = match &settings.tls {
+            Some(tls) => PostOffice::bind_tls(
+                settings.address,
+                settings.connection_limits.clone(),
+                ServerTls::from_pem_files(&tls.cert_file, &tls.key_file)?
+            )?,
+
+            None => PostOffice::bind_with_limits(settings.address, settings.connection_limits.clone())?
+        };
+
         Ok(Self {
-            state: State::new(),
+            postoffice,
+
+            settings,
+
+            state,
             world: World::new(),
 
-            postoffice: PostOffice::bind(SocketAddr::from(([0; 4], 59003)))?,
-            clients: Clients::empty()
+            pending_clients: Vec::new(),
+            clients: Clients::empty(),
+
+            world_save,
+            access,
+
+            shutdown_at: None,
+            shutdown_announced: None,
+
+            last_time_sync: 0.0,
+
+            weather: WeatherSim::new(WEATHER_SEED)
         })
     }
 
+    /// salva o estado persistente do mundo no arquivo definido nas configurações
+    #[allow(dead_code)]
+    pub fn save_world(&mut self) -> Result<(), Error> {
+        self.world_save.time_of_day = self.state.get_time_of_day();
+
+        // atualizar a posição dos jogadores ainda conectados
+        let positions = self.state.read_storage::<comp::phys::Pos>();
+
+        for client in self.clients.iter() {
+            if let Some(pos) = positions.get(client.ecs_entity) {
+                self.world_save.players.insert(client.username.clone(), pos.0);
+            }
+        }
+
+        self.world_save.save(&self.settings.save_file)
+    }
+
+    /// inicia a contagem regressiva para desligar o servidor, anunciada aos jogadores
+    ///
+    /// novas conexões deixam de ser aceitas imediatamente, e um `Event::Shutdown` é produzido quando
+    /// a contagem terminar. chamar novamente apenas reinicia a contagem
+    #[allow(dead_code)]
+    pub fn shutdown_in(&mut self, countdown: Duration) {
+        if self.shutdown_at.is_none() {
+            self.postoffice.stop_listening();
+
+            for pending in &mut self.pending_clients {
+                pending.postbox.send(ServerMsg::Disconnect {
+                    reason: DisconnectReason::ServerShutdown
+                });
+
+                pending.postbox.close();
+            }
+
+            self.pending_clients.clear();
+        }
+
+        self.shutdown_at = Some(self.state.get_time() + countdown.as_secs_f64());
+        self.shutdown_announced = Some(countdown.as_secs());
+
+        self.announce(format!("o servidor será desligado em {} segundos", countdown.as_secs()));
+    }
+
+    /// verifica se o servidor está em contagem regressiva para ser desligado
+    #[allow(dead_code)]
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown_at.is_some()
+    }
+
+    /// desliga o servidor: salva o mundo, avisa todos os clients e espera as threads de rede terminarem
+    #[allow(dead_code)]
+    pub fn shutdown(mut self) -> Result<(), Error> {
+        // salvar antes de desconectar, enquanto as posições dos jogadores ainda são conhecidas
+        let save_result = self.save_world();
+
+        self.clients.disconnect_all(DisconnectReason::ServerShutdown);
+
+        // todos os postboxes são fechados antes de serem descartados, então as filas são enviadas em paralelo e o
+        // descarte espera no máximo um `SHUTDOWN_FLUSH_TIMEOUT` no total
+        self.clients.close_all();
+
+        for pending in &mut self.pending_clients {
+            pending.postbox.close();
+        }
+
+        self.clients = Clients::empty();
+        self.pending_clients.clear();
+
+        self.postoffice.shutdown()?;
+
+        save_result
+    }
+
     /// obtém uma referência do estado do jogo do servidor
     #[allow(dead_code)]
     pub fn state(&self) -> &State { &self.state }
@@ -99,6 +293,134 @@ impl Server {
             .with(comp::phys::UpdateKind::Passive)
     }
 
+    /// obtém uma referência das configurações do servidor
+    #[allow(dead_code)]
+    pub fn settings(&self) -> &ServerSettings { &self.settings }
+
+    /// obtém as informações públicas do servidor, enviadas em resposta a um `ClientMsg::Query`
+    pub fn server_info(&self) -> ServerInfo {
+        ServerInfo {
+            name: self.settings.name.clone(),
+            motd: self.settings.motd.clone(),
+
+            version: PROTOCOL_VERSION,
+
+            players: self.clients.len() as u32,
+            max_players: self.settings.max_players as u32,
+
+            tick_rate: self.settings.tick_rate as u32
+        }
+    }
+
+    /// desconecta o client do jogador fornecido, informando o motivo a ele
+    ///
+    /// a entidade é removida e o `Event::ClientDisconnected` é produzido no próximo tick.
+    /// retorna `false` caso a entidade não pertença a nenhum client conectado
+    #[allow(dead_code)]
+    pub fn disconnect_client(&mut self, ecs_entity: EcsEntity, reason: DisconnectReason) -> bool {
+        self.clients.disconnect(ecs_entity, reason)
+    }
+
+    /// expulsa o jogador fornecido do servidor com a mensagem recebida
+    #[allow(dead_code)]
+    pub fn kick(&mut self, ecs_entity: EcsEntity, reason: String) -> bool {
+        self.disconnect_client(ecs_entity, DisconnectReason::Kicked(reason))
+    }
+
+    /// verifica se quem executa um comando possui a capacidade fornecida, sendo `None` o console do servidor,
+    /// que possui todas as capacidades
+    pub fn has_capability(&self, issuer: Option<EcsEntity>, capability: Capability) -> bool {
+        match issuer {
+            Some(ecs_entity) => self.clients
+                .get(ecs_entity)
+                .map(|client| client.role.has(capability))
+                .unwrap_or(false),
+
+            None => true
+        }
+    }
+
+    /// obtém os cargos, banimentos e whitelist do servidor
+    #[allow(dead_code)]
+    pub fn access(&self) -> &AccessList { &self.access }
+
+    /// altera os cargos, banimentos e whitelist do servidor, salvando as mudanças em disco
+    ///
+    /// os cargos dos jogadores conectados são atualizados, e jogadores que deixarem de ter acesso são desconectados
+    #[allow(dead_code)]
+    pub fn modify_access<F: FnOnce(&mut AccessList)>(&mut self, f: F) -> Result<(), Error> {
+        f(&mut self.access);
+
+        let now = access::unix_now();
+
+        self.access.remove_expired(now);
+
+        let mut refused = Vec::new();
+
+        for client in self.clients.iter_mut() {
+            client.role = self.access.role(&client.username);
+
+            let ip = client.postbox.peer_addr().map(|addr| addr.ip());
+
+            if let Some(ban) = self.access.find_ban(&client.username, ip, now) {
+                refused.push((client.ecs_entity, DisconnectReason::Banned(ban.describe(now))));
+            } else if !self.access.is_whitelisted(&client.username) {
+                refused.push((client.ecs_entity, DisconnectReason::NotWhitelisted));
+            }
+        }
+
+        for (ecs_entity, reason) in refused {
+            self.clients.disconnect(ecs_entity, reason);
+        }
+
+        self.access.save(&self.settings.access_file)
+    }
+
+    /// obtém a hora do dia atual, em segundos desde o início do primeiro dia
+    #[allow(dead_code)]
+    pub fn time_of_day(&self) -> f64 {
+        self.state.get_time_of_day()
+    }
+
+    /// altera a hora do dia, enviando a nova hora imediatamente para todos os clients
+    #[allow(dead_code)]
+    pub fn set_time_of_day(&mut self, time_of_day: f64) {
+        self.state.set_time_of_day(time_of_day);
+
+        self.last_time_sync = self.state.get_time();
+        self.clients.notify_all(ServerMsg::TimeOfDay(time_of_day));
+    }
+
+    /// obtém o clima atual de uma região
+    #[allow(dead_code)]
+    pub fn weather(&self, region: Vec2<i32>) -> Weather {
+        self.state.weather().get(region)
+    }
+
+    /// força o clima de uma região, ou volta a simulá-lo com `None`
+    ///
+    /// a mudança é enviada aos clients no próximo tick
+    #[allow(dead_code)]
+    pub fn set_weather(&mut self, region: Vec2<i32>, weather: Option<Weather>) {
+        self.weather.set_override(region, weather);
+    }
+
+    /// executa um comando em nome de um jogador ou do console (`None`), retornando a resposta para quem o executou
+    #[allow(dead_code)]
+    pub fn execute_command(&mut self, issuer: Option<EcsEntity>, input: &str) -> Result<String, String> {
+        cmd::execute(self, issuer, input)
+    }
+
+    /// envia um anúncio do servidor para todos os clients conectados
+    #[allow(dead_code)]
+    pub fn announce(&mut self, msg: String) {
+        self.clients.notify_all(ServerMsg::Chat {
+            channel: ChatChannel::System,
+            sender: None,
+            msg
+        });
+    }
+
     /// obtém uma referência para o mundo do servidor
     #[allow(dead_code)]
     pub fn world(&self) -> &World { &self.world }
@@ -131,6 +453,13 @@ impl Server {
             return Err(err.into());
         }
 
+        // executar os comandos digitados no console (passo 1)
+        for command in input.commands {
+            let result = self.execute_command(None, &command);
+
+            frontend_events.push(Event::ConsoleResponse(result));
+        }
+
         // auxiliar novas conexões do client (passo 2)
         frontend_events.append(&mut self.handle_new_connections()?);
 
@@ -140,9 +469,29 @@ impl Server {
         // tick para o localstate do client (passo 3)
         self.state.tick(dt);
 
+        // simular o clima das regiões com jogadores
+        self.update_weather();
+
         // sincroniza os clients com o novo estado do mundo
         self.sync_clients();
 
+        // contagem regressiva do desligamento
+        if let Some(shutdown_at) = self.shutdown_at {
+            let remaining = shutdown_at - self.state.get_time();
+
+            if remaining <= 0.0 {
+                frontend_events.push(Event::Shutdown);
+            } else {
+                let secs = remaining.ceil() as u64;
+
+                if SHUTDOWN_ANNOUNCEMENTS.contains(&secs) && self.shutdown_announced.map(|last| secs < last).unwrap_or(true) {
+                    self.shutdown_announced = Some(secs);
+
+                    self.announce(format!("o servidor será desligado em {} segundos", secs));
+                }
+            }
+        }
+
         // finalizar o tick, passar controle de volta para o frontend (passo 6)
         Ok(frontend_events)
     }
@@ -155,11 +504,143 @@ impl Server {
     }
 
     /// auxiliar novas conexões do client
+    ///
+    /// novas conexões ficam pendentes até completarem o handshake, e apenas recebem uma entidade
+    /// quando enviam um `ClientMsg::Connect` com a versão correta do protocolo
     fn handle_new_connections(&mut self) -> Result<Vec<Event>, Error> {
         let mut frontend_events = Vec::new();
 
+        let time = self.state.get_time();
+
         for mut postbox in self.postoffice.new_connections() {
+            // conexões aceitas logo antes de parar de escutar são recusadas
+            if self.shutdown_at.is_some() {
+                postbox.send(ServerMsg::Disconnect {
+                    reason: DisconnectReason::ServerShutdown
+                });
+
+                continue;
+            }
+
+            self.pending_clients.push(PendingClient {
+                postbox,
+                connected_at: time,
+
+                username: None,
+                compression: false
+            });
+        }
+
+        let server_info = self.server_info();
+
+        let now = access::unix_now();
+        let access = &mut self.access;
+        let clients = &self.clients;
+
+        // nomes aceitos nesse tick, que ainda não estão em `clients`
+        let mut accepted_names = HashSet::new();
+        let mut registered_accounts = false;
+
+        // jogadores aceitos nesse tick também ocupam vagas
+        let max_players = self.settings.max_players;
+        let mut players = self.clients.len();
+
+        let compression = self.settings.compression;
+
+        let registered = self.pending_clients
+            .drain_filter(|pending| {
+                for msg in pending.postbox.new_messages() {
+                    match msg {
+                        // consultas de status são respondidas sem criar um jogador, e a conexão é encerrada em seguida
+                        ClientMsg::Query => {
+                            pending.postbox.send(ServerMsg::QueryResponse(server_info.clone()));
+
+                            return true;
+                        },
+
+                        ClientMsg::Connect { version, username, password, compression: client_compression } => {
+                            let ip = pending.postbox.peer_addr().map(|addr| addr.ip());
+
+                            // jogadores recusados são avisados do motivo, e a conexão é encerrada em seguida
+                            let refusal = if version != PROTOCOL_VERSION {
+                                Some(DisconnectReason::VersionMismatch {
+                                    server_version: PROTOCOL_VERSION,
+                                    client_version: version
+                                })
+                            } else if !is_valid_username(&username) {
+                                Some(DisconnectReason::InvalidUsername)
+                            } else if
+                                clients.is_username_taken(&username) ||
+                                accepted_names.contains(&username.to_ascii_lowercase())
+                            {
+                                Some(DisconnectReason::UsernameTaken)
+                            } else if let Some(ban) = access.find_ban(&username, ip, now) {
+                                Some(DisconnectReason::Banned(ban.describe(now)))
+                            } else if !access.is_whitelisted(&username) {
+                                Some(DisconnectReason::NotWhitelisted)
+                            } else if players >= max_players {
+                                Some(DisconnectReason::ServerFull)
+                            } else {
+                                // a senha é verificada por último, para que apenas logins aceitos registrem contas
+                                match access.authenticate(&username, password.as_ref().map(|p| p.as_str())) {
+                                    Authentication::Refused => Some(DisconnectReason::WrongPassword),
+
+                                    Authentication::Registered => {
+                                        registered_accounts = true;
+
+                                        None
+                                    },
+
+                                    Authentication::Guest | Authentication::Verified => None
+                                }
+                            };
+
+                            match refusal {
+                                Some(reason) => pending.postbox.send(ServerMsg::Disconnect { reason }),
+
+                                None => {
+                                    players += 1;
+
+                                    accepted_names.insert(username.to_ascii_lowercase());
+
+                                    pending.username = Some(username);
+                                    pending.compression = compression && client_compression;
+                                }
+                            }
+
+                            return true;
+                        },
+
+                        ClientMsg::Disconnect { .. } => return true,
+
+                        // qualquer outra mensagem é ignorada até o fim do handshake
+                        _ => {}
+                    }
+                }
+
+                time - pending.connected_at > HANDSHAKE_TIMEOUT || pending.postbox.error().is_some()
+            })
+            .filter_map(|pending| {
+                let compression = pending.compression;
+
+                pending.username.map(|username| (pending.postbox, username, compression))
+            })
+            .collect::<Vec<_>>();
+
+        // uma falha ao salvar não impede o login, e a conta é salva novamente na próxima alteração do acesso
+        if registered_accounts {
+            let _ = self.access.save(&self.settings.access_file);
+        }
+
+        for (mut postbox, username, compression) in registered {
+            // jogadores que já estiveram no mundo voltam para a última posição salva
+            let pos = self.world_save.players
+                .get(&username)
+                .cloned()
+                .unwrap_or(Vec3::zero());
+
             let ecs_entity = self.build_player()
+                .with(comp::phys::Pos(pos))
                 // quando o jogador for criado primeiramente, forçar notificação de física para todos
                 // incluindo eles mesmos
                 .with(comp::phys::UpdateKind::Force)
@@ -167,13 +648,33 @@ impl Server {
             
             let uid = self.state.read_storage().get(ecs_entity).cloned().unwrap();
             
+            // o client só comprime depois de receber a confirmação, mas frames comprimidos são aceitos dos dois lados
+            postbox.send(ServerMsg::Compression { enabled: compression });
+            postbox.set_compression(compression);
+
             postbox.send(ServerMsg::SetPlayerEntity(uid));
+            postbox.send(ServerMsg::TimeOfDay(self.state.get_time_of_day()));
+
+            for (region, weather) in self.state.weather().iter() {
+                postbox.send(ServerMsg::Weather { region, weather });
+            }
+
+            self.announce(format!("{} entrou no jogo", username));
+
+            let role = self.access.role(&username);
 
             self.clients.add(Client {
                 ecs_entity,
+                username,
+                role,
                 postbox,
 
-                last_ping: self.state.get_time()
+                last_ping: time,
+
+                chat_allowance: CHAT_BURST,
+                last_chat: time,
+
+                disconnect: None
             });
 
             frontend_events.push(Event::ClientConnected {
@@ -191,10 +692,18 @@ impl Server {
         let state = &mut self.state;
         
         let mut new_chat_msgs = Vec::new();
+        let mut new_commands = Vec::new();
         let mut disconnected_clients = Vec::new();
 
         self.clients.remove_if(|client| {
-            let mut disconnected = false;
+            // clients já marcados pelo servidor são removidos sem processar novas mensagens
+            if let Some(reason) = client.disconnect.take() {
+                disconnected_clients.push((client.ecs_entity, client.username.clone(), reason));
+
+                return true;
+            }
+
+            let mut disconnect_reason = None;
             let new_msgs = client.postbox.new_messages();
 
             // atualizar ping do client
@@ -207,65 +716,201 @@ impl Server {
                         ClientMsg::Ping => client.postbox.send(ServerMsg::Pong),
                         ClientMsg::Pong => {},
 
-                        ClientMsg::Chat(msg) => new_chat_msgs.push((client.ecs_entity, msg)),
+                        ClientMsg::Chat { channel, msg } => {
+                            let msg = msg.trim().to_string();
+
+                            if msg.is_empty() {
+                                // ignorar mensagens vazias
+                            } else if channel == ChatChannel::System {
+                                client.notify_system(String::from("apenas o servidor pode enviar mensagens do sistema"));
+                            } else if msg.chars().count() > MAX_CHAT_MSG_LEN {
+                                client.notify_system(format!("mensagem longa demais (máximo de {} caracteres)", MAX_CHAT_MSG_LEN));
+                            } else if !client.take_chat_allowance(state.get_time(), CHAT_RATE, CHAT_BURST) {
+                                client.notify_system(String::from("você está enviando mensagens rápido demais"));
+                            } else if channel == ChatChannel::Global && msg.starts_with('/') {
+                                // mensagens começando com `/` são comandos, e não são repassadas para os outros jogadores
+                                new_commands.push((client.ecs_entity, msg));
+                            } else {
+                                new_chat_msgs.push((client.ecs_entity, channel, msg));
+                            }
+                        },
+
+                        ClientMsg::Character(character) => state.write_component(client.ecs_entity, character),
 
                         ClientMsg::PlayerPhysics { pos, vel, dir } => {
                             state.write_component(client.ecs_entity, pos);
-                            state.write_component(client.ecs_entity, vel;
+                            state.write_component(client.ecs_entity, vel);
                             state.write_component(client.ecs_entity, dir);
                         },
 
-                        ClientMsg::Disconnect => disconnected = true
+                        ClientMsg::Disconnect { reason } => disconnect_reason = Some(reason),
+
+                        // mensagens de handshake não são esperadas depois do client estar conectado
+                        ClientMsg::Connect { .. } | ClientMsg::Query => {
+                            client.postbox.send(ServerMsg::Disconnect {
+                                reason: DisconnectReason::ProtocolError
+                            });
+
+                            disconnect_reason = Some(DisconnectReason::ProtocolError);
+                        }
                     }
                 }
-            } else if
-                state.get_time() - client.last_ping > CLIENT_TIMEOUT || // timeout
-                client.postbox.error().is_some() // erro de postbox
-            {
-                disconnected = true;
+            } else if let Some(err) = client.postbox.error() {
+                // erro de postbox
+                disconnect_reason = Some(if err.is_protocol_violation() {
+                    DisconnectReason::ProtocolError
+                } else {
+                    DisconnectReason::ConnectionLost
+                });
+            } else if state.get_time() - client.last_ping > CLIENT_TIMEOUT {
+                // avisar o client, caso ele ainda esteja ouvindo
+                client.postbox.send(ServerMsg::Disconnect {
+                    reason: DisconnectReason::Timeout
+                });
+
+                disconnect_reason = Some(DisconnectReason::Timeout);
             } else if state.get_time() - client.last_ping > CLIENT_TIMEOUT * 0.5 {
 				// tentar pingar o client caso o timeout esteja próximo
 
 				client.postbox.send(ServerMsg::Ping);
 			}
 
-            if disconnected {
-                disconnected_clients.push(client.ecs_entity);
-                
-                true
-            } else {
-                false
+            match disconnect_reason {
+                Some(reason) => {
+                    disconnected_clients.push((client.ecs_entity, client.username.clone(), reason));
+
+                    true
+                },
+
+                None => false
             }
         });
 
         // auxiliar novas mensagens do chat
-        for (ecs_entity, msg) in new_chat_msgs {
-            self.clients.notify_all(ServerMsg::Chat(msg.clone()));
+        for (ecs_entity, channel, msg) in new_chat_msgs {
+            let sender = match self.clients.get(ecs_entity) {
+                Some(client) => client.username.clone(),
+
+                None => continue
+            };
+
+            let chat_msg = ServerMsg::Chat {
+                channel: channel.clone(),
+                sender: Some(sender.clone()),
+                msg: msg.clone()
+            };
+
+            match &channel {
+                ChatChannel::Global => self.clients.notify_all(chat_msg),
+
+                // apenas jogadores próximos do remetente recebem a mensagem
+                ChatChannel::Local => {
+                    let positions = state.read_storage::<comp::phys::Pos>();
+                    let origin = positions.get(ecs_entity).map(|pos| pos.0);
+
+                    self.clients.notify_if(|client| match (origin, positions.get(client.ecs_entity)) {
+                        (Some(origin), Some(pos)) => origin.distance(pos.0) <= LOCAL_CHAT_RADIUS,
+
+                        _ => client.ecs_entity == ecs_entity
+                    }, chat_msg);
+                },
+
+                // o remetente também recebe uma cópia do sussurro
+                ChatChannel::Whisper(target) => match self.clients.find_by_username(target) {
+                    Some(target) => {
+                        target.postbox.send(chat_msg.clone());
+
+                        if target.ecs_entity != ecs_entity {
+                            self.clients.notify_if(|client| client.ecs_entity == ecs_entity, chat_msg);
+                        }
+                    },
+
+                    None => if let Some(client) = self.clients.get_mut(ecs_entity) {
+                        client.notify_system(format!("jogador '{}' não encontrado", target));
+                    }
+                },
+
+                // filtrado ao receber a mensagem
+                ChatChannel::System => {}
+            }
 
             frontend_events.push(Event::Chat {
                 ecs_entity,
+                username: sender,
+                channel,
                 msg
             });
         }
 
         // auxiliar desconexões do cliente
-        for ecs_entity in disconnected_clients {
+        for (ecs_entity, username, reason) in disconnected_clients {
+            self.clients.notify_all(ServerMsg::Chat {
+                channel: ChatChannel::System,
+                sender: None,
+                msg: format!("{} saiu do jogo ({})", username, reason)
+            });
+
             self.clients.notify_all(ServerMsg::EntityDeleted(state.read_storage().get(ecs_entity).cloned().unwrap()));
 
+            // lembrar da posição do jogador para o próximo save
+            if let Some(pos) = state.read_storage::<comp::phys::Pos>().get(ecs_entity) {
+                self.world_save.players.insert(username.clone(), pos.0);
+            }
+
             frontend_events.push(Event::ClientDisconnected {
-                ecs_entity
+                ecs_entity,
+                reason
             });
 
             state.ecs_world_mut().delete_entity(ecs_entity);
         }
 
+        // executar os comandos recebidos, respondendo apenas para quem os enviou
+        for (ecs_entity, input) in new_commands {
+            // ignorar comandos de clients desconectados nesse mesmo tick
+            if self.clients.get(ecs_entity).is_none() {
+                continue;
+            }
+
+            let response = match self.execute_command(Some(ecs_entity), &input) {
+                Ok(response) => response,
+                Err(err) => err
+            };
+
+            if let Some(client) = self.clients.get_mut(ecs_entity) {
+                client.notify_system(response);
+            }
+        }
+
         Ok(frontend_events)
     }
 
+    /// atualiza o clima das regiões em que existem jogadores, avisando todos os clients sobre as mudanças
+    fn update_weather(&mut self) {
+        let regions = {
+            let positions = self.state.read_storage::<comp::phys::Pos>();
+
+            self.clients
+                .iter()
+                .filter_map(|client| positions.get(client.ecs_entity))
+                .map(|pos| WeatherMap::region_of(pos.0))
+                .collect::<Vec<_>>()
+        };
+
+        let time = self.state.get_time();
+        let time_of_day = self.state.get_time_of_day();
+
+        let changed = self.weather.tick(time, time_of_day, regions.into_iter(), &mut self.state.weather_mut());
+
+        for (region, weather) in changed {
+            self.clients.notify_all(ServerMsg::Weather { region, weather });
+        }
+    }
+
     /// sincroniza os estados do client com informações atualizadas
     fn sync_clients(&mut self) {
         for (entity, &uid, &pos, &vel, &dir, update_kind) in (
-            &self.state.esc_world().entities(),
+            &self.state.ecs_world().entities(),
             
             &self.state.ecs_world().read_storage::<comp::Uid>(),
             &self.state.ecs_world().read_storage::<comp::phys::Pos>(),
@@ -274,27 +919,228 @@ impl Server {
 
             &mut self.state.ecs_world().write_storage::<comp::phys::UpdateKind>()
         ).join() {
+            let force = match update_kind {
+                comp::phys::UpdateKind::Force => true,
+                comp::phys::UpdateKind::Passive => false
+            };
+
             let msg = ServerMsg::EntityPhysics {
                 uid,
                 pos,
                 vel,
-                dir
+                dir,
+
+                force
             };
 
-            // algumas vezes é necessário forçar atualização
-            match update_kind {
-                comp::phys::UpdateKind::Force => self.clients.notify_all(msg),
-                comp::phys::UpdateKind::Passive => self.clients.notify_all_except(entity, msg)
+            // algumas vezes é necessário forçar atualização, que é então entregue de forma confiável
+            if force {
+                self.clients.notify_all(msg);
+            } else {
+                self.clients.notify_all_except(entity, msg);
             }
 
             // com a atualização ocorrida, padrão é uma atualização passiva
             *update_kind = comp::phys::UpdateKind::Passive;
         }
+
+        // os clients avançam a hora do dia sozinhos, então basta corrigi-la de tempos em tempos
+        if self.state.get_time() - self.last_time_sync >= TIME_SYNC_INTERVAL {
+            self.last_time_sync = self.state.get_time();
+
+            self.clients.notify_all(ServerMsg::TimeOfDay(self.state.get_time_of_day()));
+        }
     }
 }
 
 impl Drop for Server {
     fn drop(&mut self) {
-        self.clients.notify_all(ServerMsg::Shutdown)
+        self.clients.disconnect_all(DisconnectReason::ServerShutdown)
+    }
+}
+
+// testes
+
+/// cria um servidor local com arquivos de acesso e do mundo novos
+#[cfg(test)]
+pub(crate) fn test_server(port: u16) -> Server {
+    let file = |name: &str| std::env::temp_dir()
+        .join(format!("lestinia-server-{}-{}", port, name))
+        .to_string_lossy()
+        .into_owned();
+
+    let settings = ServerSettings {
+        address: std::net::SocketAddr::from(([127, 0, 0, 1], port)),
+
+        access_file: file("acesso.sav"),
+        save_file: file("mundo.sav"),
+
+        ..ServerSettings::default()
+    };
+
+    let _ = std::fs::remove_file(&settings.access_file);
+    let _ = std::fs::remove_file(&settings.save_file);
+
+    Server::with_settings(settings).unwrap()
+}
+
+/// tenta conectar um jogador ao servidor de teste, retornando a conexão dele e sua entidade ou o motivo da recusa
+#[cfg(test)]
+pub(crate) fn try_connect(
+    server: &mut Server,
+    port: u16,
+    username: &str,
+    password: Option<&str>
+) -> (common::net::PostBox<ClientMsg, ServerMsg>, Result<EcsEntity, DisconnectReason>) {
+    // jogadores já conectados com o mesmo nome não contam como o resultado dessa tentativa
+    let existing = server.clients.iter().map(|client| client.ecs_entity).collect::<HashSet<_>>();
+
+    let mut postbox = common::net::PostBox::to_server(([127, 0, 0, 1], port)).unwrap();
+
+    postbox.send(ClientMsg::Connect {
+        version: PROTOCOL_VERSION,
+        username: username.to_string(),
+        password: password.map(|password| password.to_string()),
+
+        compression: false
+    });
+
+    for _ in 0..100 {
+        server.tick(Input::default(), Duration::from_millis(10)).unwrap();
+
+        let connected = server.clients
+            .iter()
+            .find(|client| client.username == username && !existing.contains(&client.ecs_entity))
+            .map(|client| client.ecs_entity);
+
+        if let Some(ecs_entity) = connected {
+            return (postbox, Ok(ecs_entity));
+        }
+
+        for msg in postbox.new_messages() {
+            if let ServerMsg::Disconnect { reason } = msg {
+                return (postbox, Err(reason));
+            }
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
     }
+
+    panic!("o servidor não respondeu ao login de '{}'", username);
+}
+
+/// conecta um jogador ao servidor de teste, retornando a conexão dele e sua entidade
+#[cfg(test)]
+pub(crate) fn connect_player(
+    server: &mut Server,
+    port: u16,
+    username: &str,
+    password: Option<&str>
+) -> (common::net::PostBox<ClientMsg, ServerMsg>, EcsEntity) {
+    match try_connect(server, port, username, password) {
+        (postbox, Ok(ecs_entity)) => (postbox, ecs_entity),
+        (_, Err(reason)) => panic!("o jogador '{}' foi recusado: {}", username, reason)
+    }
+}
+
+#[test]
+fn usernames_are_unique_and_valid() {
+    let mut server = test_server(59110);
+
+    let _alice = connect_player(&mut server, 59110, "alice", None);
+
+    // o mesmo nome, mesmo com outras maiúsculas, não pode ser utilizado por outro jogador ao mesmo tempo
+    assert_eq!(try_connect(&mut server, 59110, "alice", None).1, Err(DisconnectReason::UsernameTaken));
+    assert_eq!(try_connect(&mut server, 59110, "ALICE", None).1, Err(DisconnectReason::UsernameTaken));
+
+    assert_eq!(try_connect(&mut server, 59110, "", None).1, Err(DisconnectReason::InvalidUsername));
+    assert_eq!(try_connect(&mut server, 59110, "nome com espaços", None).1, Err(DisconnectReason::InvalidUsername));
+    assert_eq!(
+        try_connect(&mut server, 59110, &"a".repeat(common::msg::MAX_USERNAME_LEN + 1), None).1,
+        Err(DisconnectReason::InvalidUsername)
+    );
+
+    assert_eq!(server.clients.len(), 1);
+}
+
+#[test]
+fn registered_names_need_the_password() {
+    let mut server = test_server(59111);
+
+    // sem conta, o primeiro a entrar com o nome e qualquer senha receberia o cargo, então ele é recusado
+    let mut set = true;
+
+    server.modify_access(|access| set = access.set_role("admin", crate::access::Role::Admin)).unwrap();
+
+    assert!(!set);
+
+    let (guest_pb, guest) = connect_player(&mut server, 59111, "admin", None);
+
+    assert!(!server.has_capability(Some(guest), Capability::Stop));
+
+    drop(guest_pb);
+    server.disconnect_client(guest, DisconnectReason::Quit);
+    server.tick(Input::default(), Duration::from_millis(10)).unwrap();
+
+    // o primeiro login com senha registra o nome, e só então ele pode receber um cargo
+    let (admin_pb, admin) = connect_player(&mut server, 59111, "admin", Some("segredo"));
+
+    assert!(!server.has_capability(Some(admin), Capability::Stop));
+
+    server.modify_access(|access| set = access.set_role("admin", crate::access::Role::Admin)).unwrap();
+
+    assert!(set);
+    assert!(server.has_capability(Some(admin), Capability::Stop));
+
+    drop(admin_pb);
+    server.disconnect_client(admin, DisconnectReason::Quit);
+    server.tick(Input::default(), Duration::from_millis(10)).unwrap();
+
+    assert_eq!(try_connect(&mut server, 59111, "admin", None).1, Err(DisconnectReason::WrongPassword));
+    assert_eq!(try_connect(&mut server, 59111, "admin", Some("outra")).1, Err(DisconnectReason::WrongPassword));
+
+    // outras maiúsculas não criam uma segunda conta para o mesmo nome
+    assert_eq!(try_connect(&mut server, 59111, "Admin", Some("outra")).1, Err(DisconnectReason::WrongPassword));
+
+    // a conta sobrevive a um reinício do servidor
+    assert!(AccessList::load(&server.settings.access_file).unwrap().is_registered("admin"));
+}
+
+#[test]
+fn access_ignores_username_case() {
+    use crate::access::{
+        Ban,
+        BanTarget
+    };
+
+    let mut server = test_server(59112);
+
+    server.modify_access(|access| {
+        access.ban(Ban {
+            target: BanTarget::Name(String::from("griefer")),
+            reason: String::from("teste"),
+            expires_at: None
+        });
+
+        access.whitelist_enabled = true;
+        access.whitelist_add("alice");
+    }).unwrap();
+
+    // trocar as maiúsculas do nome não escapa do banimento nem da whitelist
+    match try_connect(&mut server, 59112, "Griefer", None).1 {
+        Err(DisconnectReason::Banned(_)) => {},
+
+        result => panic!("'Griefer' deveria ter sido recusado: {:?}", result)
+    }
+
+    let (_alice_pb, alice) = connect_player(&mut server, 59112, "Alice", None);
+
+    assert_eq!(try_connect(&mut server, 59112, "bob", None).1, Err(DisconnectReason::NotWhitelisted));
+
+    // e os comandos encontram o jogador por qualquer forma do nome
+    assert!(cmd::execute(&mut server, None, "/kick ALICE").is_ok());
+
+    server.tick(Input::default(), Duration::from_millis(10)).unwrap();
+
+    assert!(server.clients.get(alice).is_none());
 }
@@ -0,0 +1,41 @@
+use std::{
+	fs,
+
+	collections::HashMap
+};
+
+use vek::*;
+
+use crate::Error;
+
+/// estado persistente do mundo, salvo pelo comando `/save` e carregado ao iniciar o servidor
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WorldSave {
+	pub time_of_day: f64,
+
+	/// última posição conhecida de cada jogador, por nome de usuário
+	pub players: HashMap<String, Vec3<f32>>
+}
+
+impl WorldSave {
+	/// carrega o estado salvo no arquivo fornecido, retornando `None` caso ele ainda não exista
+	pub fn load(path: &str) -> Result<Option<Self>, Error> {
+		match fs::read(path) {
+			Ok(bytes) => bincode::deserialize(&bytes)
+				.map(Some)
+				.map_err(|err| Error::Other(format!("arquivo de save '{}' inválido: {:?}", path, err))),
+
+			Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+
+			Err(err) => Err(Error::Other(format!("falha ao ler o save '{}': {:?}", path, err)))
+		}
+	}
+
+	pub fn save(&self, path: &str) -> Result<(), Error> {
+		let bytes = bincode::serialize(self)
+			.map_err(|err| Error::Other(format!("falha ao serializar o save: {:?}", err)))?;
+
+		fs::write(path, bytes)
+			.map_err(|err| Error::Other(format!("falha ao escrever o save '{}': {:?}", path, err)))
+	}
+}
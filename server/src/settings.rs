@@ -0,0 +1,286 @@
+use std::{
+	fs,
+	net::SocketAddr,
+	time::Duration
+};
+
+use common::{
+	msg::DEFAULT_PORT,
+	net::ConnectionLimits
+};
+
+use crate::Error;
+
+// maior taxa de ticks aceita, acima disso o clock não consegue mais esperar entre os ticks
+const MAX_TICK_RATE: u64 = 1000;
+
+/// arquivos em formato pem com o certificado e a chave privada do servidor
+#[derive(Clone, Debug)]
+pub struct TlsSettings {
+	pub cert_file: String,
+	pub key_file: String
+}
+
+/// configurações do servidor
+#[derive(Clone, Debug)]
+pub struct ServerSettings {
+	/// endereço em que o servidor escuta por conexões
+	pub address: SocketAddr,
+
+	/// nome do servidor, mostrado na lista de servidores
+	pub name: String,
+
+	/// mensagem do dia
+	pub motd: String,
+
+	/// número máximo de jogadores conectados ao mesmo tempo
+	pub max_players: usize,
+
+	/// limites de conexões por endereço ip, aplicados antes do handshake
+	pub connection_limits: ConnectionLimits,
+
+	/// quando presente, todas as conexões são criptografadas com tls
+	pub tls: Option<TlsSettings>,
+
+	/// número de ticks por segundo
+	pub tick_rate: u64,
+
+	/// comprimir mensagens grandes para clients que suportam compressão
+	pub compression: bool,
+
+	/// arquivo em que os cargos, banimentos e whitelist são salvos
+	pub access_file: String,
+
+	/// arquivo em que o estado do mundo é salvo
+	pub save_file: String
+}
+
+impl Default for ServerSettings {
+	fn default() -> Self {
+		Self {
+			address: SocketAddr::from(([0; 4], DEFAULT_PORT)),
+
+			name: String::from("servidor lestinia"),
+			motd: String::from("bem-vindo ao lestinia!"),
+
+			max_players: 16,
+
+			connection_limits: ConnectionLimits {
+				max_per_ip: Some(4),
+				max_rate_per_ip: Some((10, Duration::from_secs(60)))
+			},
+
+			tls: None,
+
+			tick_rate: 30,
+
+			compression: true,
+
+			access_file: String::from("acesso.sav"),
+			save_file: String::from("mundo.sav")
+		}
+	}
+}
+
+impl ServerSettings {
+	/// carrega as configurações de um arquivo de texto com uma linha `chave = valor` por configuração
+	///
+	/// chaves ausentes mantêm o valor padrão, linhas começando com `#` são ignoradas e um arquivo inexistente
+	/// resulta nas configurações padrão
+	pub fn load(path: &str) -> Result<Self, Error> {
+		let mut settings = Self::default();
+
+		let text = match fs::read_to_string(path) {
+			Ok(text) => text,
+
+			Err(ref err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(settings),
+
+			Err(err) => return Err(Error::Other(format!("falha ao ler o arquivo de configurações '{}': {:?}", path, err)))
+		};
+
+		for (i, line) in text.lines().enumerate() {
+			let line = line.trim();
+
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let (key, value) = split_pair(line, '=')
+				.ok_or_else(|| Error::Other(format!("{}:{}: esperado 'chave = valor'", path, i + 1)))?;
+
+			settings.set(key, value)
+				.map_err(|err| match err {
+					Error::Other(msg) => Error::Other(format!("{}:{}: {}", path, i + 1, msg)),
+
+					err => err
+				})?;
+		}
+
+		Ok(settings)
+	}
+
+	/// altera uma configuração a partir do nome dela e de um valor em texto
+	///
+	/// os limites de conexões aceitam `nenhum` para desativá-los, e a taxa de conexões é escrita como
+	/// `conexões/segundos`
+	pub fn set(&mut self, key: &str, value: &str) -> Result<(), Error> {
+		let invalid = || Error::Other(format!("valor inválido para '{}': '{}'", key, value));
+
+		match key {
+			"address" => self.address = value.parse().map_err(|_| invalid())?,
+
+			"name" => self.name = value.to_string(),
+			"motd" => self.motd = value.to_string(),
+
+			"max_players" => self.max_players = value.parse().map_err(|_| invalid())?,
+
+			"max_connections_per_ip" => self.connection_limits.max_per_ip = match value {
+				"nenhum" => None,
+
+				_ => Some(value.parse().map_err(|_| invalid())?)
+			},
+
+			"max_connection_rate_per_ip" => self.connection_limits.max_rate_per_ip = match value {
+				"nenhum" => None,
+
+				_ => {
+					let (count, secs) = split_pair(value, '/').ok_or_else(invalid)?;
+
+					Some((
+						count.parse().map_err(|_| invalid())?,
+						Duration::from_secs(secs.parse().map_err(|_| invalid())?)
+					))
+				}
+			},
+
+			"tls_cert_file" => self.tls_mut().cert_file = value.to_string(),
+			"tls_key_file" => self.tls_mut().key_file = value.to_string(),
+
+			"tick_rate" => self.tick_rate = value.parse().map_err(|_| invalid())?,
+
+			"compression" => self.compression = value.parse().map_err(|_| invalid())?,
+
+			"access_file" => self.access_file = value.to_string(),
+			"save_file" => self.save_file = value.to_string(),
+
+			_ => return Err(Error::Other(format!("configuração desconhecida '{}'", key)))
+		}
+
+		Ok(())
+	}
+
+	/// verifica se as configurações podem ser utilizadas por um servidor
+	pub fn validate(&self) -> Result<(), Error> {
+		if self.tick_rate == 0 || self.tick_rate > MAX_TICK_RATE {
+			return Err(Error::Other(format!("tick_rate deve estar entre 1 e {}", MAX_TICK_RATE)));
+		}
+
+		if self.max_players == 0 {
+			return Err(Error::Other(String::from("max_players deve ser maior que zero")));
+		}
+
+		if self.connection_limits.max_per_ip == Some(0) {
+			return Err(Error::Other(String::from("max_connections_per_ip deve ser maior que zero")));
+		}
+
+		if let Some((count, period)) = self.connection_limits.max_rate_per_ip {
+			if count == 0 || period == Duration::from_secs(0) {
+				return Err(Error::Other(String::from("max_connection_rate_per_ip deve ser maior que zero")));
+			}
+		}
+
+		if let Some(tls) = &self.tls {
+			if tls.cert_file.is_empty() || tls.key_file.is_empty() {
+				return Err(Error::Other(String::from("tls precisa de tls_cert_file e tls_key_file")));
+			}
+		}
+
+		Ok(())
+	}
+
+	fn tls_mut(&mut self) -> &mut TlsSettings {
+		self.tls.get_or_insert_with(|| TlsSettings {
+			cert_file: String::new(),
+			key_file: String::new()
+		})
+	}
+}
+
+/// separa um texto no primeiro `sep`, removendo os espaços em volta das duas partes
+fn split_pair(text: &str, sep: char) -> Option<(&str, &str)> {
+	let i = text.find(sep)?;
+
+	Some((text[..i].trim(), text[i + sep.len_utf8()..].trim()))
+}
+
+// testes
+
+#[test]
+fn load_settings_file() {
+	let path = std::env::temp_dir().join("lestinia_settings_test.cfg");
+	let path = path.to_str().unwrap();
+
+	fs::write(path, "\
+		# servidor de testes\n\
+		address = 127.0.0.1:4000\n\
+		name = teste = sim\n\
+		\n\
+		max_players = 4\n\
+		max_connections_per_ip = nenhum\n\
+		max_connection_rate_per_ip = 3/10\n\
+		tls_cert_file = cert.pem\n\
+		tls_key_file = key.pem\n\
+		tick_rate = 20\n\
+		compression = false\n\
+	").unwrap();
+
+	let settings = ServerSettings::load(path).unwrap();
+
+	let _ = fs::remove_file(path);
+
+	assert_eq!(settings.address, SocketAddr::from(([127, 0, 0, 1], 4000)));
+	assert_eq!(settings.name, "teste = sim");
+	assert_eq!(settings.max_players, 4);
+	assert_eq!(settings.connection_limits.max_per_ip, None);
+	assert_eq!(settings.connection_limits.max_rate_per_ip, Some((3, Duration::from_secs(10))));
+	assert_eq!(settings.tick_rate, 20);
+	assert!(!settings.compression);
+
+	let tls = settings.tls.as_ref().unwrap();
+
+	assert_eq!((tls.cert_file.as_str(), tls.key_file.as_str()), ("cert.pem", "key.pem"));
+
+	// chaves ausentes mantêm o padrão
+	assert_eq!(settings.motd, ServerSettings::default().motd);
+	assert!(settings.validate().is_ok());
+
+	// um arquivo inexistente resulta nas configurações padrão
+	assert_eq!(ServerSettings::load(path).unwrap().name, ServerSettings::default().name);
+}
+
+#[test]
+fn invalid_settings() {
+	let mut settings = ServerSettings::default();
+
+	assert!(settings.set("tick_rate", "rápido").is_err());
+	assert!(settings.set("address", "lestinia").is_err());
+	assert!(settings.set("max_connection_rate_per_ip", "10").is_err());
+	assert!(settings.set("cor", "azul").is_err());
+
+	// valores recusados não alteram as configurações
+	assert_eq!(settings.tick_rate, ServerSettings::default().tick_rate);
+
+	settings.set("tick_rate", "0").unwrap();
+	assert!(settings.validate().is_err());
+
+	settings.set("tick_rate", "30").unwrap();
+	settings.set("max_players", "0").unwrap();
+	assert!(settings.validate().is_err());
+
+	settings.set("max_players", "8").unwrap();
+	settings.set("tls_cert_file", "cert.pem").unwrap();
+	assert!(settings.validate().is_err());
+
+	settings.set("tls_key_file", "key.pem").unwrap();
+	assert!(settings.validate().is_ok());
+}
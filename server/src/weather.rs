@@ -0,0 +1,202 @@
+use std::{
+	f32::consts::PI,
+
+	collections::{
+		HashMap,
+		HashSet
+	}
+};
+
+use vek::*;
+
+use common::weather::{
+	Weather,
+	WeatherKind,
+	WeatherMap
+};
+
+// intervalo entre atualizações do clima simulado
+const WEATHER_UPDATE_INTERVAL: f64 = 5.0; // segundos
+
+// duração de uma frente de clima, em tempo do dia, o que equivale a 15 minutos reais
+const WEATHER_FRONT_DURATION: f64 = 6.0 * 60.0 * 60.0;
+
+// fração das frentes de clima que trazem chuva, neve ou neblina
+const STORM_CHANCE: f32 = 0.4;
+
+// fração das regiões frias, onde a precipitação cai como neve
+const COLD_REGION_CHANCE: f32 = 0.3;
+
+// diferença mínima de intensidade que justifica enviar o clima novamente aos clients
+const INTENSITY_EPSILON: f32 = 0.05;
+
+/// simula o clima de cada região ocupada por jogadores
+///
+/// o clima é uma função determinística da região e da hora do dia: cada região passa por frentes de clima que
+/// crescem e diminuem suavemente, começando em momentos diferentes para que as regiões não mudem todas juntas
+pub struct WeatherSim {
+	seed: u32,
+	last_update: Option<f64>,
+
+	// clima forçado pelo `/weather`, que substitui o simulado até ser removido
+	overrides: HashMap<Vec2<i32>, Weather>
+}
+
+impl WeatherSim {
+	pub fn new(seed: u32) -> Self {
+		Self {
+			seed,
+			last_update: None,
+
+			overrides: HashMap::new()
+		}
+	}
+
+	/// força o clima de uma região, ou volta a simulá-lo com `None`
+	pub fn set_override(&mut self, region: Vec2<i32>, weather: Option<Weather>) {
+		match weather {
+			Some(weather) => { self.overrides.insert(region, weather); },
+
+			None => { self.overrides.remove(&region); }
+		}
+
+		// a mudança deve chegar aos clients no próximo tick, sem esperar o intervalo
+		self.last_update = None;
+	}
+
+	/// atualiza o clima das regiões fornecidas e das regiões forçadas, retornando as que mudaram
+	///
+	/// as demais regiões são removidas do `weather_map`, e as que não estavam com clima limpo são retornadas como
+	/// limpas para que os clients também as esqueçam
+	pub fn tick(
+		&mut self,
+		time: f64,
+		time_of_day: f64,
+		regions: impl Iterator<Item = Vec2<i32>>,
+		weather_map: &mut WeatherMap
+	) -> Vec<(Vec2<i32>, Weather)> {
+		if self.last_update.map(|last| time - last < WEATHER_UPDATE_INTERVAL).unwrap_or(false) {
+			return Vec::new();
+		}
+
+		self.last_update = Some(time);
+
+		let regions = regions
+			.chain(self.overrides.keys().cloned())
+			.collect::<HashSet<_>>();
+
+		let mut changed = Vec::new();
+
+		for region in regions {
+			let weather = self.overrides
+				.get(&region)
+				.cloned()
+				.unwrap_or_else(|| self.simulate(region, time_of_day));
+
+			let old = weather_map.get(region);
+
+			if old.kind != weather.kind || (old.intensity - weather.intensity).abs() >= INTENSITY_EPSILON {
+				weather_map.set(region, weather);
+
+				changed.push((region, weather));
+			}
+		}
+
+		// regiões sem jogadores e sem clima forçado deixam de ser acompanhadas
+		let abandoned = weather_map
+			.iter()
+			.map(|(region, _)| region)
+			.filter(|region| !regions.contains(region))
+			.collect::<Vec<_>>();
+
+		for region in abandoned {
+			if weather_map.remove(region) != Weather::clear() {
+				changed.push((region, Weather::clear()));
+			}
+		}
+
+		changed
+	}
+
+	/// o clima simulado de uma região em um momento do dia
+	pub fn simulate(&self, region: Vec2<i32>, time_of_day: f64) -> Weather {
+		// cada região começa suas frentes em um momento diferente
+		let time = time_of_day / WEATHER_FRONT_DURATION + self.hash(region, 0, 0) as f64;
+
+		let front = time.floor() as i64 as u64;
+		let progress = time.fract() as f32;
+
+		let chance = self.hash(region, 1, front);
+
+		if chance >= STORM_CHANCE {
+			return Weather::clear();
+		}
+
+		let cold = self.hash(region, 2, 0) < COLD_REGION_CHANCE;
+
+		let kind = match (chance / STORM_CHANCE < 0.7, cold) {
+			(true, false) => WeatherKind::Rain,
+			(true, true) => WeatherKind::Snow,
+
+			(false, _) => WeatherKind::Fog
+		};
+
+		// a frente cresce até a metade da sua duração e depois se dissipa, trocando de tipo apenas quando fraca
+		let peak = 0.3 + 0.7 * self.hash(region, 3, front);
+		let intensity = peak * (progress * PI).sin();
+
+		if intensity < INTENSITY_EPSILON {
+			Weather::clear()
+		} else {
+			Weather::new(kind, intensity)
+		}
+	}
+
+	/// número pseudoaleatório entre 0 e 1 para uma região
+	fn hash(&self, region: Vec2<i32>, salt: u64, n: u64) -> f32 {
+		let mut h = self.seed as u64 ^ 0x9e37_79b9_7f4a_7c15;
+
+		for &v in &[region.x as u32 as u64, region.y as u32 as u64, salt, n] {
+			h = (h ^ v).wrapping_mul(0x0000_0100_0000_01b3);
+			h ^= h >> 29;
+			h = h.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+			h ^= h >> 32;
+		}
+
+		(h >> 40) as f32 / (1u64 << 24) as f32
+	}
+}
+
+// testes
+
+#[test]
+fn abandoned_regions_are_removed() {
+	let mut sim = WeatherSim::new(7);
+	let mut weather_map = WeatherMap::new();
+
+	let region = Vec2::new(3, -2);
+	let rain = Weather::new(WeatherKind::Rain, 0.8);
+
+	sim.set_override(region, Some(rain));
+
+	// regiões forçadas são mantidas mesmo sem jogadores
+	assert_eq!(sim.tick(0.0, 0.0, std::iter::empty(), &mut weather_map), vec![(region, rain)]);
+	assert_eq!(weather_map.get(region), rain);
+
+	sim.set_override(region, None);
+
+	// sem jogadores e sem clima forçado, a região é esquecida e os clients a veem limpa
+	assert_eq!(sim.tick(1.0, 0.0, std::iter::empty(), &mut weather_map), vec![(region, Weather::clear())]);
+	assert_eq!(weather_map.iter().count(), 0);
+
+	// regiões com jogadores continuam sendo simuladas
+	let occupied = (0..64).map(|x| Vec2::new(x, 0)).collect::<Vec<_>>();
+
+	sim.tick(WEATHER_UPDATE_INTERVAL * 2.0, 0.0, occupied.iter().cloned(), &mut weather_map);
+
+	assert!(weather_map.iter().all(|(region, _)| occupied.contains(&region)));
+
+	sim.tick(WEATHER_UPDATE_INTERVAL * 4.0, 0.0, std::iter::empty(), &mut weather_map);
+
+	assert_eq!(weather_map.iter().count(), 0);
+}
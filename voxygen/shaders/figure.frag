@@ -3,6 +3,7 @@
 in vec3 f_pos;
 in vec3 f_norm;
 in vec3 f_col;
+in float f_ao;
 
 flat in uint f_bone_idx;
 
@@ -22,6 +23,15 @@ uniform u_globals {
 	vec4 view_distance;
 	vec4 time_of_day;
 	vec4 tick;
+
+	vec4 sun_dir;
+	vec4 sun_color;
+	vec4 moon_dir;
+	vec4 moon_color;
+
+	vec4 ambient;
+	vec4 sky_top;
+	vec4 sky_bottom;
 };
 
 struct BoneData {
@@ -42,11 +52,14 @@ void main() {
 		vec4(f_norm, 0.0)
 	).xyz;
 
-	float ambient = 0.5;
+	// cantos cercados por voxels recebem menos luz ambiente
+	float ao = mix(0.4, 1.0, f_ao);
 
-	vec3 sun_dir = normalize(vec3(1.3, 1.7, 1.1));
+	// o sol e a lua só iluminam as faces voltadas para eles
+	vec3 sun_diffuse = sun_color.rgb * max(dot(sun_dir.xyz, world_norm), 0.0);
+	vec3 moon_diffuse = moon_color.rgb * max(dot(moon_dir.xyz, world_norm), 0.0);
 
-	float sun_diffuse = dot(sun_dir, world_norm) * 0.5;
+	vec3 light = ambient.rgb * ao + sun_diffuse + moon_diffuse;
 
-	tgt_color = vec4(f_col * (ambient + sun_diffuse), 1.0);
+	tgt_color = vec4(f_col * light, 1.0);
 }
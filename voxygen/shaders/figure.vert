@@ -3,6 +3,7 @@
 in vec3 v_pos;
 in vec3 v_norm;
 in vec3 v_col;
+in float v_ao;
 in uint v_bone_idx;
 
 layout (std140)
@@ -21,6 +22,15 @@ uniform u_globals {
 	vec4 view_distance;
 	vec4 time_of_day;
 	vec4 tick;
+
+	vec4 sun_dir;
+	vec4 sun_color;
+	vec4 moon_dir;
+	vec4 moon_color;
+
+	vec4 ambient;
+	vec4 sky_top;
+	vec4 sky_bottom;
 };
 
 struct BoneData {
@@ -35,6 +45,7 @@ uniform u_bones {
 out vec3 f_pos;
 out vec3 f_norm;
 out vec3 f_col;
+out float f_ao;
 
 flat out uint f_bone_idx;
 
@@ -42,6 +53,7 @@ void main() {
 	f_pos = v_pos;
 	f_norm = v_norm;
 	f_col = v_col;
+	f_ao = v_ao;
 	f_bone_idx = v_bone_idx;
 
 	gl_Position =
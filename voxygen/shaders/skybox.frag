@@ -18,33 +18,42 @@ uniform u_globals {
     vec4 view_distance;
     vec4 time_of_day;
     vec4 tick;
+
+    vec4 sun_dir;
+    vec4 sun_color;
+    vec4 moon_dir;
+    vec4 moon_color;
+
+    vec4 ambient;
+    vec4 sky_top;
+    vec4 sky_bottom;
 };
 
 out vec4 tgt_color;
 
-const float PI = 3.141592;
-
-vec3 get_sky_color(vec3 dir, float time_of_day) {
-	const float TIME_FACTOR = (PI * 2.0) / (3600.0 * 24.0);
+vec3 get_sky_color(vec3 dir) {
+	const vec3 SUN_HALO_COLOR = vec3(1.0, 0.8, 0.5);
+	const vec3 SUN_SURF_COLOR = vec3(1.0, 0.9, 0.7);
 
-	const vec3 SKY_TOP    = vec3(0.0, 0.3, 1.0);
-	const vec3 SKY_BOTTOM = vec3(0.0, 0.05, 0.2);
+	const vec3 MOON_SURF_COLOR = vec3(0.8, 0.85, 1.0);
 
-	const vec3 SUN_HALO_COLOR = vec3(1.0, 0.8, 0.5);
-	const vec3 SUN_SURF_COLOR = vec3(1.0, 0.8, 0.5);
+	// o gradiente e as cores dos astros são calculados na cpu de acordo com a hora do dia
+	vec3 sky = mix(sky_bottom.rgb, sky_top.rgb, clamp((dir.z + 1.0) / 2.0, 0.0, 1.0));
 
-	float sun_angle_rad = time_of_day * TIME_FACTOR;
+	float sun_dot = max(dot(dir, sun_dir.xyz), 0.0);
+	float moon_dot = max(dot(dir, moon_dir.xyz), 0.0);
 
-	vec3 sun_dir = vec3(sin(sun_angle_rad), 0.0, cos(sun_angle_rad));
+	// o brilho do sol diminui junto com a luz que ele emite, sumindo abaixo do horizonte
+	float sun_strength = clamp(length(sun_color.rgb), 0.0, 1.0);
 
-	vec3 sun_halo = pow(max(dot(dir, sun_dir), 0.0), 8.0) * SUN_HALO_COLOR;
-	vec3 sun_surf = min(pow(max(dot(dir, sun_dir), 0.0) + 0.01, 16.0), 1.0) * SUN_SURF_COLOR;
+	vec3 sun_halo = pow(sun_dot, 8.0) * SUN_HALO_COLOR;
+	vec3 sun_surf = min(pow(sun_dot + 0.01, 256.0), 1.0) * SUN_SURF_COLOR;
 
-	vec3 sun_light = sun_halo + sun_surf;
+	vec3 moon_surf = min(pow(moon_dot + 0.005, 512.0), 1.0) * MOON_SURF_COLOR;
 
-	return mix(SKY_BOTTOM, SKY_TOP, (dir.z + 1.0) / 2.0) + sun_light * 0.5;
+	return sky + (sun_halo * 0.5 + sun_surf) * sun_strength + moon_surf * (1.0 - sun_strength);
 }
 
 void main() {
-	tgt_color = vec4(get_sky_color(normalize(f_pos), time_of_day.x), 1.0);
+	tgt_color = vec4(get_sky_color(normalize(f_pos)), 1.0);
 }
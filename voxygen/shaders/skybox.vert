@@ -18,6 +18,15 @@ uniform u_globals {
 	vec4 view_distance;
 	vec4 time_of_day;
 	vec4 tick;
+
+	vec4 sun_dir;
+	vec4 sun_color;
+	vec4 moon_dir;
+	vec4 moon_color;
+
+	vec4 ambient;
+	vec4 sky_top;
+	vec4 sky_bottom;
 };
 
 out vec3 f_pos;
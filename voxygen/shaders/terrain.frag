@@ -3,6 +3,8 @@
 in vec3 f_pos;
 in vec3 f_norm;
 in vec3 f_col;
+in float f_ao;
+in vec2 f_light;
 
 layout (std140)
 uniform u_locals {
@@ -20,16 +22,43 @@ uniform u_globals {
     vec4 view_distance;
     vec4 time_of_day;
     vec4 tick;
+
+    vec4 sun_dir;
+    vec4 sun_color;
+    vec4 moon_dir;
+    vec4 moon_color;
+
+    vec4 ambient;
+    vec4 sky_top;
+    vec4 sky_bottom;
 };
 
 out vec4 tgt_color;
 
+// cor da luz emitida por blocos como tochas
+const vec3 BLOCK_LIGHT_COLOR = vec3(1.0, 0.75, 0.45);
+
+// luz mínima, para que lugares completamente fechados não fiquem totalmente pretos
+const vec3 MIN_LIGHT = vec3(0.02);
+
+// cada nível de luz a menos escurece mais que o anterior
+float light_curve(float level) {
+    return level * level;
+}
+
 void main() {
-    float ambient = 0.5;
+    // cantos cercados por voxels recebem menos luz ambiente
+    float ao = mix(0.4, 1.0, f_ao);
+
+    // o sol e a lua só iluminam as faces voltadas para eles
+    vec3 sun_diffuse = sun_color.rgb * max(dot(sun_dir.xyz, f_norm), 0.0);
+    vec3 moon_diffuse = moon_color.rgb * max(dot(moon_dir.xyz, f_norm), 0.0);
 
-    vec3 sun_dir = normalize(vec3(1.3, 1.7, 1.1));
+    // a luz do céu só alcança lugares expostos a ele, e a luz de blocos não depende da hora do dia
+    vec3 sky_light = (ambient.rgb * ao + sun_diffuse + moon_diffuse) * light_curve(f_light.x);
+    vec3 block_light = BLOCK_LIGHT_COLOR * light_curve(f_light.y) * ao;
 
-    float sun_diffuse = dot(sun_dir, f_norm) * 0.5;
+    vec3 light = sky_light + block_light + MIN_LIGHT * ao;
 
-    tgt_color = vec4(f_col * (ambient + sun_diffuse), 1.0);
+    tgt_color = vec4(f_col * light, 1.0);
 }
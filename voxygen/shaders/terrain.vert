@@ -3,6 +3,8 @@
 in vec3 v_pos;
 in vec3 v_norm;
 in vec3 v_col;
+in float v_ao;
+in vec2 v_light;
 
 layout (std140)
 uniform u_locals {
@@ -20,16 +22,29 @@ uniform u_globals {
     vec4 view_distance;
     vec4 time_of_day;
     vec4 tick;
+
+    vec4 sun_dir;
+    vec4 sun_color;
+    vec4 moon_dir;
+    vec4 moon_color;
+
+    vec4 ambient;
+    vec4 sky_top;
+    vec4 sky_bottom;
 };
 
 out vec3 f_pos;
 out vec3 f_norm;
 out vec3 f_col;
+out float f_ao;
+out vec2 f_light;
 
 void main() {
     f_pos = v_pos;
     f_norm = v_norm;
     f_col = v_col;
+    f_ao = v_ao;
+    f_light = v_light;
 
     gl_Position =
         proj_mat *
@@ -1,5 +1,11 @@
 use crate::ui::Ui;
 
+use common::msg::chat::{
+	self,
+
+	ChatChannel
+};
+
 use conrod_core::{
 	input::Key,
 	position::Dimension,
@@ -38,7 +44,7 @@ widget_ids! {
 // considerar marcar isso como um widget personalizado
 pub struct Chat {
 	ids: Ids,
-	messages: VecDeque<String>,
+	messages: VecDeque<(String, Color)>,
 	input: String,
 	new_messages: bool
 }
@@ -53,11 +59,24 @@ impl Chat {
 		}
 	}
 
-	pub fn new_message(&mut self, msg: String) {
-		self.messages.push_back(msg);
+	/// adiciona uma mensagem recebida do servidor, colorida de acordo com o canal
+	pub fn new_message(&mut self, channel: &ChatChannel, sender: Option<&str>, msg: &str) {
+		let color = match channel {
+			ChatChannel::Global => Color::Rgba(1.0, 1.0, 1.0, 1.0),
+			ChatChannel::Local => Color::Rgba(0.7, 1.0, 0.7, 1.0),
+			ChatChannel::Whisper(_) => Color::Rgba(1.0, 0.6, 1.0, 1.0),
+			ChatChannel::System => Color::Rgba(1.0, 0.9, 0.3, 1.0)
+		};
+
+		self.messages.push_back((chat::format_chat_msg(channel, sender, msg), color));
 		self.new_messages = true;
 	}
 
+	/// verifica se o jogador está digitando no chat, para que as teclas não sejam tratadas como comandos do jogo
+	pub fn is_typing(&self, ui: &Ui) -> bool {
+		ui.keyboard_capture() == Some(self.ids.input)
+	}
+
 	// determinar se a caixa de mensagem é scrollada até o bottom
 	// (exemplo: o jogador está vendo novas mensagens)
 	//
@@ -91,11 +110,9 @@ impl Chat {
 			let new_message = self.input.clone();
 
 			self.input.clear();
-			self.new_message(new_message.clone());
 
-			// scrollar para o bottom
-			// todo: remover comentário quando obter mensagens de outras pessoas
-			// ui_widgets.scroll_widget(self.ids.message_box, [0.0, std::f64::MAX]);
+			// a mensagem aparece quando o servidor a repassar de volta, então apenas scrollar para o bottom
+			ui_widgets.scroll_widget(self.ids.message_box, [0.0, std::f64::MAX]);
 
 			Some(new_message)
 		} else {
@@ -150,10 +167,12 @@ impl Chat {
             .set(self.ids.message_box, ui_widgets);
         
 		while let Some(item) = items.next(ui_widgets) {
+            let (ref text, color) = self.messages[item.i];
+
             item.set(
-                Text::new(&self.messages[item.i])
+                Text::new(text)
                     .font_size(30)
-                    .rgba(1.0, 1.0, 1.0, 1.0),
+                    .color(color),
 				
                 ui_widgets,
             )
@@ -1,7 +1,12 @@
-#![feature(drain_filter)]
+#![feature(drain_filter, euclidean_division)]
+#![cfg_attr(test, feature(test))]
+
+#[cfg(test)]
+extern crate test;
 
 pub mod anim;
 pub mod error;
+pub mod hud;
 pub mod key_state;
 pub mod menu;
 pub mod mesh;
@@ -29,7 +34,10 @@ use crate::{
 
 /// tipagem utilizada para armazenar o estado que é compartilhado entre os estados de play
 pub struct GlobalState {
-    window: Window
+    window: Window,
+
+    /// mensagem para ser mostrada ao jogador pelo próximo estado que a consumir (ex: o motivo de uma desconexão)
+    info_message: Option<String>
 }
 
 impl GlobalState {
@@ -70,7 +78,9 @@ fn main() {
     // configura o estado global
     let mut global_state = GlobalState {
         window: Window::new()
-            .expect("falha ao criar janela")
+            .expect("falha ao criar janela"),
+
+        info_message: None
     };
 
     // configura o estado de play inicial
@@ -0,0 +1,260 @@
+// padrão
+use std::time::Duration;
+
+// biblioteca
+use vek::*;
+
+// projeto
+use common::{
+    clock::Clock,
+    comp::Character,
+    weather::Weather
+};
+
+use client::{
+    self,
+
+    Client
+};
+
+// caixote
+use crate::{
+    PlayState,
+    PlayStateResult,
+
+    GlobalState,
+
+    window::{
+        Event,
+        Window
+    },
+
+    render::{
+        Consts,
+        Globals,
+        Renderer,
+        FigureLocals
+    },
+
+    anim::{
+        Animation,
+
+        character::{
+            CharacterSkeleton,
+            RunAnimation
+        }
+    },
+
+    scene::{
+        camera::Camera,
+
+        figure::{
+            self,
+
+            Figure
+        },
+
+        sky
+    },
+
+    session::SessionState
+};
+
+use super::describe_client_error;
+
+// local
+use super::char_selection_ui::{
+    self,
+
+    CharSelectionUi
+};
+
+const FPS: u64 = 60;
+
+// velocidade com que a pré-visualização gira em volta do personagem (radianos por segundo)
+const PREVIEW_SPIN_SPEED: f32 = 0.5;
+
+// a pré-visualização sempre mostra o personagem no meio da tarde
+const PREVIEW_TIME_OF_DAY: f64 = sky::DAY_LENGTH * 0.6;
+
+// a cor do background
+const BG_COLOR: Rgba<f32> = Rgba { r: 0.0, g: 0.3, b: 1.0, a: 1.0 };
+
+/// pré-visualização em tempo real do personagem que está sendo criado
+struct CharPreview {
+    globals: Consts<Globals>,
+    camera: Camera,
+
+    figure: Figure<CharacterSkeleton>,
+    time: f64
+}
+
+impl CharPreview {
+    fn new(renderer: &mut Renderer, character: &Character) -> Self {
+        let mut camera = Camera::new();
+
+        // aproximar a câmera do personagem
+        camera.zoom_by(-100.0);
+
+        Self {
+            globals: renderer
+                .create_consts(&[Globals::default()])
+                .unwrap(),
+
+            camera,
+
+            figure: Figure::new(
+                renderer,
+                figure::character_bone_meshes(character),
+
+                CharacterSkeleton::new()
+            )
+                .unwrap(),
+
+            time: 0.0
+        }
+    }
+
+    /// reconstrói o modelo da figura após uma mudança no personagem
+    fn set_character(&mut self, renderer: &mut Renderer, character: &Character) {
+        self.figure
+            .set_bone_meshes(renderer, figure::character_bone_meshes(character))
+            .expect("falha ao atualizar a pré-visualização do personagem");
+    }
+
+    fn maintain(&mut self, renderer: &mut Renderer, dt: Duration) {
+        self.time += dt.as_secs_f64();
+
+        // girar lentamente em volta do personagem
+        self.camera.rotate_by(Vec3::new(dt.as_secs_f32() * PREVIEW_SPIN_SPEED, 0.0, 0.0));
+
+        let (view_mat, proj_mat, cam_pos) = self.camera.compute_dependents();
+
+        renderer.update_consts(&mut self.globals, &[Globals::new(
+            view_mat,
+            proj_mat,
+            cam_pos,
+
+            self.camera.get_focus_pos(),
+
+            10.0,
+
+            PREVIEW_TIME_OF_DAY,
+            self.time,
+
+            sky::sky_light(PREVIEW_TIME_OF_DAY, Weather::clear())
+        )])
+            .expect("falha ao atualizar constantes globais");
+
+        RunAnimation::update_skeleton(&mut self.figure.skeleton, self.time);
+
+        self.figure.update_locals(renderer, FigureLocals::default()).unwrap();
+        self.figure.update_skeleton(renderer).unwrap();
+    }
+
+    fn render(&self, renderer: &mut Renderer) {
+        self.figure.render(renderer, &self.globals);
+    }
+}
+
+pub struct CharSelectionState {
+    char_selection_ui: CharSelectionUi,
+    preview: CharPreview,
+
+    // o client já conectado, passado para a sessão quando o jogador entrar no mundo
+    client: Option<Client>
+}
+
+impl CharSelectionState {
+    /// cria um novo `charselectionstate` para um client já conectado
+    pub fn new(window: &mut Window, client: Client) -> Self {
+        let char_selection_ui = CharSelectionUi::new(window);
+        let preview = CharPreview::new(window.renderer_mut(), &char_selection_ui.character());
+
+        Self {
+            char_selection_ui,
+            preview,
+
+            client: Some(client)
+        }
+    }
+}
+
+impl PlayState for CharSelectionState {
+    fn play(&mut self, global_state: &mut GlobalState) -> PlayStateResult {
+        // configurar clock de fps
+        let mut clock = Clock::new();
+
+        loop {
+            // ajuda os eventos da janela
+            for event in global_state.window.fetch_events() {
+                match event {
+                    Event::Close => return PlayStateResult::Shutdown,
+
+                    // passar eventos para ui
+                    Event::UiEvent(input) => {
+                        self.char_selection_ui.handle_event(input);
+                    }
+
+                    // ignorar todos os outros eventos
+                    _ => {}
+                }
+            }
+
+            global_state.window.renderer_mut().clear(BG_COLOR);
+
+            // mantém a ui e reage às escolhas do jogador
+            for event in self.char_selection_ui.maintain(global_state.window.renderer_mut()) {
+                match event {
+                    char_selection_ui::Event::CharacterChanged => {
+                        let character = self.char_selection_ui.character();
+
+                        self.preview.set_character(global_state.window.renderer_mut(), &character);
+                    },
+
+                    char_selection_ui::Event::Play => {
+                        let character = self.char_selection_ui.character();
+
+                        if let Some(client) = self.client.take() {
+                            return PlayStateResult::Switch(Box::new(
+                                SessionState::new(&mut global_state.window, client, character)
+                            ));
+                        }
+                    }
+                }
+            }
+
+            // manter a conexão com o servidor ativa enquanto o personagem é criado
+            if let Some(client) = &mut self.client {
+                if let Err(err) = client.tick(client::Input::default(), clock.get_last_delta()) {
+                    log::error!("conexão com o servidor perdida: {:?}", err);
+
+                    global_state.info_message = Some(format!("desconectado: {}", describe_client_error(&err)));
+
+                    return PlayStateResult::Pop;
+                }
+
+                client.cleanup();
+            }
+
+            // mantém e desenha a pré-visualização do personagem
+            self.preview.maintain(global_state.window.renderer_mut(), clock.get_last_delta());
+            self.preview.render(global_state.window.renderer_mut());
+
+            // desenha a ui na tela
+            self.char_selection_ui.render(global_state.window.renderer_mut());
+
+            // finalizar o frame
+            global_state.window.renderer_mut().flush();
+
+            global_state.window
+                .swap_buffers()
+                .expect("falha ao trocar buffers da janela");
+
+            // esperar para o próximo tick
+            clock.tick(Duration::from_millis(1000 / FPS));
+        }
+    }
+
+    fn name(&self) -> &'static str { "criação de personagem" }
+}
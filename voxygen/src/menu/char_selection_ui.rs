@@ -0,0 +1,238 @@
+// biblioteca
+use conrod_core::{
+	Positionable,
+	Sizeable,
+	Labelable,
+	Colorable,
+
+	Widget,
+	widget_ids,
+
+	image::Id as ImgId,
+	event::Input,
+
+	widget::{
+		Button,
+		Text
+	}
+};
+
+// projeto
+use common::comp::character::{
+	self,
+
+	Character
+};
+
+// caixote
+use crate::{
+	window::Window,
+	render::Renderer,
+	ui::Ui
+};
+
+/// número de linhas de opções (raça, gênero e cada parte do corpo)
+const OPTION_ROWS: usize = 9;
+
+widget_ids! {
+	struct Ids {
+		title,
+
+		prev_buttons[],
+		labels[],
+		next_buttons[],
+
+		play_button
+	}
+}
+
+struct Imgs {
+	button: ImgId
+}
+
+impl Imgs {
+	fn new(ui: &mut Ui, renderer: &mut Renderer) -> Imgs {
+		let mut load = |filename| {
+			let image = image::open(&[env!("CARGO_MANIFEST_DIR"), "/test_assets/", filename].concat()).unwrap();
+
+			ui.new_image(renderer, &image).unwrap()
+		};
+
+		Imgs {
+			button: load("test_menu_button_blank.png")
+		}
+	}
+}
+
+/// eventos produzidos pela interface de criação de personagem
+pub enum Event {
+	/// alguma parte do personagem foi alterada e a pré-visualização precisa ser reconstruída
+	CharacterChanged,
+
+	/// o jogador confirmou o personagem e deseja entrar no mundo
+	Play
+}
+
+/// escolhe a variante anterior ou seguinte de `cur` na lista `all`
+fn cycle<T: Copy + PartialEq>(all: &[T], cur: T, forward: bool) -> T {
+	let idx = all.iter().position(|e| *e == cur).unwrap_or(0);
+
+	if forward {
+		all[(idx + 1) % all.len()]
+	} else {
+		all[(idx + all.len() - 1) % all.len()]
+	}
+}
+
+pub struct CharSelectionUi {
+	ui: Ui,
+	ids: Ids,
+	imgs: Imgs,
+
+	character: Character
+}
+
+impl CharSelectionUi {
+	pub fn new(window: &mut Window) -> Self {
+		let mut ui = Ui::new(window).unwrap();
+
+		// gerar ids
+		let mut ids = Ids::new(ui.id_generator());
+
+		ids.prev_buttons.resize(OPTION_ROWS, &mut ui.id_generator());
+		ids.labels.resize(OPTION_ROWS, &mut ui.id_generator());
+		ids.next_buttons.resize(OPTION_ROWS, &mut ui.id_generator());
+
+		// carregar imagens
+		let imgs = Imgs::new(&mut ui, window.renderer_mut());
+
+		// carregar fontes
+		ui.new_font(conrod_core::text::font::from_file(
+			concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/font/Metamorphous-Regular.ttf")
+		).unwrap());
+
+		Self {
+			ui,
+			ids,
+			imgs,
+
+			character: Character::default()
+		}
+	}
+
+	/// obtém o personagem atualmente montado na interface
+	pub fn character(&self) -> Character {
+		self.character
+	}
+
+	fn update_layout(&mut self) -> Vec<Event> {
+		let mut events = Vec::new();
+
+		let CharSelectionUi {
+			ref mut ui,
+			ref imgs,
+			ref ids,
+
+			ref mut character
+		} = *self;
+
+		let ref mut ui_cell = ui.set_widgets();
+
+		Text::new("criação de personagem")
+			.mid_top_with_margin_on(ui_cell.window, 20.0)
+			.font_size(28)
+			.rgba(1.0, 1.0, 1.0, 1.0)
+			.set(ids.title, ui_cell);
+
+		let labels = [
+			format!("raça: {:?}", character.race),
+			format!("gênero: {:?}", character.gender),
+			format!("cabeça: {:?}", character.head),
+			format!("peito: {:?}", character.chest),
+			format!("cinto: {:?}", character.belt),
+			format!("calças: {:?}", character.pants),
+			format!("mãos: {:?}", character.hand),
+			format!("pés: {:?}", character.foot),
+			format!("arma: {:?}", character.weapon)
+		];
+
+		for (i, label) in labels.iter().enumerate() {
+			let prev = Button::image(imgs.button)
+				.w_h(30.0, 30.0)
+				.top_left_with_margins_on(ui_cell.window, 80.0 + i as f64 * 40.0, 20.0)
+				.label("<")
+				.label_font_size(14)
+				.set(ids.prev_buttons[i], ui_cell)
+				.was_clicked();
+
+			Text::new(label)
+				.right_from(ids.prev_buttons[i], 10.0)
+				.w(220.0)
+				.font_size(16)
+				.rgba(1.0, 1.0, 1.0, 1.0)
+				.set(ids.labels[i], ui_cell);
+
+			let next = Button::image(imgs.button)
+				.w_h(30.0, 30.0)
+				.right_from(ids.labels[i], 10.0)
+				.label(">")
+				.label_font_size(14)
+				.set(ids.next_buttons[i], ui_cell)
+				.was_clicked();
+
+			// cada clique altera a opção da linha correspondente
+			let clicked = if prev {
+				Some(false)
+			} else if next {
+				Some(true)
+			} else {
+				None
+			};
+
+			if let Some(forward) = clicked {
+				match i {
+					0 => character.race = cycle(&character::ALL_RACES, character.race, forward),
+					1 => character.gender = cycle(&character::ALL_GENDERS, character.gender, forward),
+					2 => character.head = cycle(&character::ALL_HEADS, character.head, forward),
+					3 => character.chest = cycle(&character::ALL_CHESTS, character.chest, forward),
+					4 => character.belt = cycle(&character::ALL_BELTS, character.belt, forward),
+					5 => character.pants = cycle(&character::ALL_PANTS, character.pants, forward),
+					6 => character.hand = cycle(&character::ALL_HANDS, character.hand, forward),
+					7 => character.foot = cycle(&character::ALL_FEET, character.foot, forward),
+					_ => character.weapon = cycle(&character::ALL_WEAPONS, character.weapon, forward)
+				}
+
+				events.push(Event::CharacterChanged);
+			}
+		}
+
+		if Button::image(imgs.button)
+			.w_h(200.0, 40.0)
+			.mid_bottom_with_margin_on(ui_cell.window, 30.0)
+			.label("entrar no mundo")
+			.label_font_size(16)
+			.set(ids.play_button, ui_cell)
+			.was_clicked()
+		{
+			events.push(Event::Play);
+		}
+
+		events
+	}
+
+	pub fn handle_event(&mut self, input: Input) {
+		self.ui.handle_event(input);
+	}
+
+	pub fn maintain(&mut self, renderer: &mut Renderer) -> Vec<Event> {
+		let events = self.update_layout();
+
+		self.ui.maintain(renderer);
+
+		events
+	}
+
+	pub fn render(&self, renderer: &mut Renderer) {
+		self.ui.render(renderer);
+	}
+}
@@ -0,0 +1,198 @@
+// padrão
+use std::{
+    thread,
+
+    sync::mpsc,
+    time::Instant
+};
+
+// biblioteca
+use vek::*;
+
+// projeto
+use client::{
+    self,
+
+    Client
+};
+
+// caixote
+use crate::{
+    PlayState,
+    PlayStateResult,
+
+    GlobalState,
+
+    window::{
+        Event,
+        Window
+    }
+};
+
+// local
+use super::{
+    char_selection::CharSelectionState,
+    describe_client_error,
+
+    server_list::ServerList,
+
+    main_ui::{
+        self,
+
+        MainMenuUi
+    }
+};
+
+// a cor do background
+const BG_COLOR: Rgba<f32> = Rgba { r: 0.0, g: 0.3, b: 1.0, a: 1.0 };
+
+/// uma tentativa de conexão em andamento, executada em segundo plano para não travar a interface
+struct ClientInit {
+    addr: String,
+    started: Instant,
+    rx: mpsc::Receiver<Result<Client, client::Error>>
+}
+
+impl ClientInit {
+    fn new(addr: String, username: String, password: String) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let thread_addr = addr.clone();
+
+        // resolver o endereço também bloqueia enquanto o dns responde, então é feito junto com a conexão
+        thread::spawn(move || {
+            let result = match client::parse_server_addr(&thread_addr) {
+                Some(sock_addr) => Client::new(sock_addr, username, Some(password)),
+
+                None => Err(client::Error::Other(String::from("endereço inválido")))
+            };
+
+            let _ = tx.send(result);
+        });
+
+        Self {
+            addr,
+            started: Instant::now(),
+            rx
+        }
+    }
+}
+
+pub struct MainMenuState {
+    main_menu_ui: MainMenuUi,
+    server_list: ServerList,
+
+    client_init: Option<ClientInit>,
+    status: String
+}
+
+impl MainMenuState {
+    /// cria um novo `mainmenustate`
+    pub fn new(window: &mut Window) -> Self {
+        let mut server_list = ServerList::load();
+
+        server_list.ping_all();
+
+        Self {
+            main_menu_ui: MainMenuUi::new(window),
+            server_list,
+
+            client_init: None,
+            status: String::new()
+        }
+    }
+}
+
+impl PlayState for MainMenuState {
+    fn play(&mut self, global_state: &mut GlobalState) -> PlayStateResult {
+        // mostrar mensagens deixadas pelos estados anteriores, como o motivo de uma desconexão
+        if let Some(msg) = global_state.info_message.take() {
+            self.status = msg;
+        }
+
+        loop {
+            // ajuda os eventos da janela
+            for event in global_state.window.fetch_events() {
+                match event {
+                    Event::Close => return PlayStateResult::Shutdown,
+
+                    // passar eventos para ui
+                    Event::UiEvent(input) => {
+                        self.main_menu_ui.handle_event(input);
+                    }
+
+                    // ignorar todos os outros eventos
+                    _ => {}
+                }
+            }
+
+            // verificar o progresso de uma conexão em andamento
+            if let Some(client_init) = &self.client_init {
+                match client_init.rx.try_recv() {
+                    Ok(Ok(client)) => {
+                        self.client_init = None;
+                        self.status.clear();
+
+                        return PlayStateResult::Push(
+                            Box::new(CharSelectionState::new(&mut global_state.window, client))
+                        );
+                    },
+
+                    Ok(Err(err)) => {
+                        self.status = format!("falha ao conectar: {}", describe_client_error(&err));
+                        self.client_init = None;
+                    },
+
+                    Err(mpsc::TryRecvError::Empty) => {
+                        self.status = format!(
+                            "conectando em {}... ({}s)",
+                            client_init.addr,
+                            client_init.started.elapsed().as_secs()
+                        );
+                    },
+
+                    Err(mpsc::TryRecvError::Disconnected) => {
+                        self.status = String::from("falha ao conectar: erro interno");
+                        self.client_init = None;
+                    }
+                }
+            }
+
+            self.server_list.maintain();
+
+            global_state.window.renderer_mut().clear(BG_COLOR);
+
+            // mantém a ui e reage às ações do jogador
+            for event in self.main_menu_ui.maintain(global_state.window.renderer_mut(), &self.server_list, &self.status) {
+                match event {
+                    // ignorar novas tentativas enquanto uma conexão está em andamento
+                    main_ui::Event::Connect { .. } if self.client_init.is_some() => {},
+
+                    main_ui::Event::Connect { addr, username, password } => {
+                        self.client_init = Some(ClientInit::new(addr, username, password));
+                    },
+
+                    main_ui::Event::SaveServer(addr) => {
+                        self.server_list.add(addr);
+                        self.server_list.ping_all();
+                    },
+
+                    main_ui::Event::RemoveServer(idx) => self.server_list.remove(idx),
+
+                    main_ui::Event::RefreshServers => self.server_list.ping_all()
+                }
+            }
+
+            // desenha a ui na tela
+            self.main_menu_ui.render(global_state.window.renderer_mut());
+
+            // finalizar o frame
+            global_state.window.renderer_mut().flush();
+
+            global_state.window
+                .swap_buffers()
+                .expect("falha ao trocar buffers da janela");
+        }
+    }
+
+    fn name(&self) -> &'static str { "menu principal" }
+}
@@ -0,0 +1,339 @@
+// biblioteca
+use conrod_core::{
+	Positionable,
+	Sizeable,
+	Labelable,
+	Colorable,
+
+	Widget,
+	widget_ids,
+
+	image::Id as ImgId,
+	event::Input,
+
+	widget::{
+		Button,
+		Text,
+		TextEdit
+	}
+};
+
+// caixote
+use crate::{
+	window::Window,
+	render::Renderer,
+	ui::Ui
+};
+
+// local
+use super::server_list::{
+	ServerList,
+	ServerStatus
+};
+
+widget_ids! {
+	struct Ids {
+		title,
+
+		addr_label,
+		addr_input,
+		username_label,
+		username_input,
+		password_label,
+		password_input,
+
+		connect_button,
+		save_button,
+		refresh_button,
+		status_text,
+
+		server_buttons[],
+		server_status[],
+		server_remove[]
+	}
+}
+
+struct Imgs {
+	button: ImgId
+}
+
+impl Imgs {
+	fn new(ui: &mut Ui, renderer: &mut Renderer) -> Imgs {
+		let mut load = |filename| {
+			let image = image::open(&[env!("CARGO_MANIFEST_DIR"), "/test_assets/", filename].concat()).unwrap();
+
+			ui.new_image(renderer, &image).unwrap()
+		};
+
+		Imgs {
+			button: load("test_menu_button_blank.png")
+		}
+	}
+}
+
+/// eventos produzidos pela interface do menu principal
+pub enum Event {
+	/// conectar ao endereço e com o nome de usuário escritos, e com a senha caso não esteja vazia
+	Connect {
+		addr: String,
+		username: String,
+		password: String
+	},
+
+	/// salvar o endereço escrito na lista de servidores
+	SaveServer(String),
+
+	/// remover o servidor da posição fornecida da lista
+	RemoveServer(usize),
+
+	/// consultar o status de todos os servidores salvos
+	RefreshServers
+}
+
+pub struct MainMenuUi {
+	ui: Ui,
+	ids: Ids,
+	imgs: Imgs,
+
+	addr: String,
+	username: String,
+	password: String
+}
+
+/// aplica a edição do campo de senha, que mostra apenas um `*` por caractere, à senha real
+///
+/// os `*` que restarem são os caracteres mantidos do início da senha, e os demais caracteres foram digitados agora
+fn edit_password(password: &mut String, edited: &str) {
+	let kept = edited.chars().filter(|c| *c == '*').count();
+	let typed = edited.chars().filter(|c| *c != '*' && *c != '\n').collect::<String>();
+
+	*password = password.chars().take(kept).collect();
+	password.push_str(&typed);
+}
+
+impl MainMenuUi {
+	pub fn new(window: &mut Window) -> Self {
+		let mut ui = Ui::new(window).unwrap();
+
+		// gerar ids
+		let ids = Ids::new(ui.id_generator());
+
+		// carregar imagens
+		let imgs = Imgs::new(&mut ui, window.renderer_mut());
+
+		// carregar fontes
+		ui.new_font(conrod_core::text::font::from_file(
+			concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/font/Metamorphous-Regular.ttf")
+		).unwrap());
+
+		Self {
+			ui,
+			ids,
+			imgs,
+
+			addr: String::from("127.0.0.1"),
+			username: String::from("jogador"),
+			password: String::new()
+		}
+	}
+
+	fn update_layout(&mut self, servers: &ServerList, status: &str) -> Vec<Event> {
+		let mut events = Vec::new();
+
+		let MainMenuUi {
+			ref mut ui,
+			ref imgs,
+			ref mut ids,
+
+			ref mut addr,
+			ref mut username,
+			ref mut password
+		} = *self;
+
+		// manter um conjunto de ids para cada servidor salvo
+		let num = servers.servers().len();
+
+		if ids.server_buttons.len() != num {
+			ids.server_buttons.resize(num, &mut ui.id_generator());
+			ids.server_status.resize(num, &mut ui.id_generator());
+			ids.server_remove.resize(num, &mut ui.id_generator());
+		}
+
+		let ref mut ui_cell = ui.set_widgets();
+
+		Text::new("jogar")
+			.mid_top_with_margin_on(ui_cell.window, 20.0)
+			.font_size(28)
+			.rgba(1.0, 1.0, 1.0, 1.0)
+			.set(ids.title, ui_cell);
+
+		// endereço do servidor
+		Text::new("servidor:")
+			.top_left_with_margins_on(ui_cell.window, 80.0, 20.0)
+			.font_size(16)
+			.rgba(1.0, 1.0, 1.0, 1.0)
+			.set(ids.addr_label, ui_cell);
+
+		if let Some(new_addr) = TextEdit::new(addr)
+			.right_from(ids.addr_label, 10.0)
+			.w_h(300.0, 24.0)
+			.font_size(16)
+			.set(ids.addr_input, ui_cell)
+		{
+			*addr = new_addr;
+
+			addr.retain(|c| c != '\n');
+		}
+
+		// nome de usuário
+		Text::new("usuário:")
+			.down_from(ids.addr_label, 16.0)
+			.font_size(16)
+			.rgba(1.0, 1.0, 1.0, 1.0)
+			.set(ids.username_label, ui_cell);
+
+		if let Some(new_username) = TextEdit::new(username)
+			.right_from(ids.username_label, 10.0)
+			.w_h(300.0, 24.0)
+			.font_size(16)
+			.set(ids.username_input, ui_cell)
+		{
+			*username = new_username;
+
+			username.retain(|c| c != '\n');
+		}
+
+		// senha, opcional para nomes não registrados
+		Text::new("senha:")
+			.down_from(ids.username_label, 16.0)
+			.font_size(16)
+			.rgba(1.0, 1.0, 1.0, 1.0)
+			.set(ids.password_label, ui_cell);
+
+		let masked = "*".repeat(password.chars().count());
+
+		if let Some(edited) = TextEdit::new(&masked)
+			.right_from(ids.password_label, 10.0)
+			.w_h(300.0, 24.0)
+			.font_size(16)
+			.set(ids.password_input, ui_cell)
+		{
+			edit_password(password, &edited);
+		}
+
+		if Button::image(imgs.button)
+			.w_h(140.0, 30.0)
+			.down_from(ids.password_label, 20.0)
+			.label("conectar")
+			.label_font_size(14)
+			.set(ids.connect_button, ui_cell)
+			.was_clicked()
+		{
+			events.push(Event::Connect {
+				addr: addr.clone(),
+				username: username.clone(),
+				password: password.clone()
+			});
+		}
+
+		if Button::image(imgs.button)
+			.w_h(140.0, 30.0)
+			.right_from(ids.connect_button, 10.0)
+			.label("salvar servidor")
+			.label_font_size(14)
+			.set(ids.save_button, ui_cell)
+			.was_clicked()
+		{
+			events.push(Event::SaveServer(addr.clone()));
+		}
+
+		if Button::image(imgs.button)
+			.w_h(140.0, 30.0)
+			.right_from(ids.save_button, 10.0)
+			.label("atualizar lista")
+			.label_font_size(14)
+			.set(ids.refresh_button, ui_cell)
+			.was_clicked()
+		{
+			events.push(Event::RefreshServers);
+		}
+
+		// progresso da conexão ou último erro
+		Text::new(status)
+			.down_from(ids.connect_button, 16.0)
+			.font_size(14)
+			.rgba(1.0, 0.8, 0.5, 1.0)
+			.set(ids.status_text, ui_cell);
+
+		// servidores salvos
+		for (i, server) in servers.servers().iter().enumerate() {
+			let button = Button::image(imgs.button)
+				.w_h(220.0, 26.0)
+				.label(&server.addr)
+				.label_font_size(12);
+
+			let button = if i == 0 {
+				button.down_from(ids.status_text, 20.0)
+			} else {
+				button.down_from(ids.server_buttons[i - 1], 6.0)
+			};
+
+			// selecionar um servidor preenche o endereço
+			if button.set(ids.server_buttons[i], ui_cell).was_clicked() {
+				*addr = server.addr.clone();
+			}
+
+			let status = match &server.status {
+				ServerStatus::Unknown => String::from("-"),
+				ServerStatus::Pinging => String::from("consultando..."),
+
+				ServerStatus::Online { info, ping } => format!(
+					"{} - {}/{} jogadores, {} ms - {}",
+					info.name,
+					info.players,
+					info.max_players,
+					ping.as_millis(),
+					info.motd
+				),
+
+				ServerStatus::Offline(err) => format!("offline ({})", err)
+			};
+
+			Text::new(&status)
+				.right_from(ids.server_buttons[i], 10.0)
+				.w(260.0)
+				.font_size(12)
+				.rgba(1.0, 1.0, 1.0, 1.0)
+				.set(ids.server_status[i], ui_cell);
+
+			if Button::image(imgs.button)
+				.w_h(26.0, 26.0)
+				.right_from(ids.server_status[i], 10.0)
+				.label("x")
+				.label_font_size(12)
+				.set(ids.server_remove[i], ui_cell)
+				.was_clicked()
+			{
+				events.push(Event::RemoveServer(i));
+			}
+		}
+
+		events
+	}
+
+	pub fn handle_event(&mut self, input: Input) {
+		self.ui.handle_event(input);
+	}
+
+	pub fn maintain(&mut self, renderer: &mut Renderer, servers: &ServerList, status: &str) -> Vec<Event> {
+		let events = self.update_layout(servers, status);
+
+		self.ui.maintain(renderer);
+
+		events
+	}
+
+	pub fn render(&self, renderer: &mut Renderer) {
+		self.ui.render(renderer);
+	}
+}
@@ -1,3 +1,34 @@
+pub mod char_selection;
+mod char_selection_ui;
+pub mod main;
+mod main_ui;
+pub mod server_list;
 pub mod title;
 mod title_ui;
 pub mod test_hud;
+
+/// descreve um erro de conexão do client de forma legível para o jogador
+pub fn describe_client_error(err: &client::Error) -> String {
+    match err {
+        client::Error::ServerTimeout => String::from("tempo de conexão esgotado"),
+        client::Error::ServerShutdown => String::from("o servidor foi desligado"),
+
+        client::Error::VersionMismatch { server_version, client_version } => format!(
+            "versão incompatível (servidor: {}, cliente: {})",
+            server_version,
+            client_version
+        ),
+
+        client::Error::Kicked(reason) => format!("expulso do servidor: {}", reason),
+        client::Error::Banned(reason) => format!("banido do servidor: {}", reason),
+        client::Error::NotWhitelisted => String::from("você não está na whitelist do servidor"),
+        client::Error::ServerFull => String::from("o servidor está cheio"),
+        client::Error::InvalidUsername => String::from("nome de usuário inválido: utilize apenas letras, números, _ e -"),
+        client::Error::UsernameTaken => String::from("já existe um jogador conectado com esse nome"),
+        client::Error::WrongPassword => String::from("senha incorreta para esse nome de usuário"),
+        client::Error::ProtocolError => String::from("erro de protocolo"),
+
+        client::Error::Network(err) => format!("conexão recusada ou falha de rede ({})", err),
+        client::Error::Other(err) => err.clone()
+    }
+}
@@ -0,0 +1,153 @@
+// padrão
+use std::{
+    fs,
+    thread,
+
+    sync::mpsc,
+
+    time::{
+        Duration,
+        Instant
+    }
+};
+
+// projeto
+use common::msg::ServerInfo;
+
+use client::{
+    self,
+
+    Client
+};
+
+/// arquivo em que a lista de servidores salvos é armazenada, um endereço por linha
+const SERVER_LIST_FILE: &str = "servers.txt";
+
+/// estado da última consulta de status de um servidor salvo
+pub enum ServerStatus {
+    Unknown,
+    Pinging,
+
+    Online {
+        info: ServerInfo,
+        ping: Duration
+    },
+
+    Offline(String)
+}
+
+pub struct ServerEntry {
+    pub addr: String,
+    pub status: ServerStatus
+}
+
+/// lista de servidores salvos pelo jogador, com o status de cada um
+pub struct ServerList {
+    servers: Vec<ServerEntry>,
+
+    ping_tx: mpsc::Sender<(String, Result<(ServerInfo, Duration), String>)>,
+    ping_rx: mpsc::Receiver<(String, Result<(ServerInfo, Duration), String>)>
+}
+
+impl ServerList {
+    /// carrega a lista de servidores salvos do disco
+    pub fn load() -> Self {
+        let servers = fs::read_to_string(SERVER_LIST_FILE)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|addr| ServerEntry {
+                addr: addr.to_string(),
+                status: ServerStatus::Unknown
+            })
+            .collect();
+
+        let (ping_tx, ping_rx) = mpsc::channel();
+
+        Self {
+            servers,
+
+            ping_tx,
+            ping_rx
+        }
+    }
+
+    /// salva a lista de servidores no disco
+    pub fn save(&self) {
+        let contents = self.servers
+            .iter()
+            .map(|server| server.addr.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if let Err(err) = fs::write(SERVER_LIST_FILE, contents) {
+            log::warn!("falha ao salvar a lista de servidores: {:?}", err);
+        }
+    }
+
+    pub fn servers(&self) -> &[ServerEntry] {
+        &self.servers
+    }
+
+    /// adiciona um servidor à lista, caso ainda não esteja nela
+    pub fn add(&mut self, addr: String) {
+        if !self.servers.iter().any(|server| server.addr == addr) {
+            self.servers.push(ServerEntry {
+                addr,
+                status: ServerStatus::Unknown
+            });
+
+            self.save();
+        }
+    }
+
+    /// remove o servidor da posição fornecida
+    pub fn remove(&mut self, idx: usize) {
+        if idx < self.servers.len() {
+            self.servers.remove(idx);
+
+            self.save();
+        }
+    }
+
+    /// consulta o status de todos os servidores salvos em segundo plano
+    pub fn ping_all(&mut self) {
+        for server in &mut self.servers {
+            server.status = ServerStatus::Pinging;
+
+            let addr = server.addr.clone();
+            let ping_tx = self.ping_tx.clone();
+
+            thread::spawn(move || {
+                let start = Instant::now();
+
+                let result = match client::parse_server_addr(&addr) {
+                    Some(sock_addr) => Client::query(sock_addr)
+                        .map(|info| (info, start.elapsed()))
+                        .map_err(|err| super::describe_client_error(&err)),
+
+                    None => Err(String::from("endereço inválido"))
+                };
+
+                let _ = ping_tx.send((addr, result));
+            });
+        }
+    }
+
+    /// recebe os resultados das consultas que terminaram
+    pub fn maintain(&mut self) {
+        while let Ok((addr, result)) = self.ping_rx.try_recv() {
+            for server in self.servers.iter_mut().filter(|server| server.addr == addr) {
+                server.status = match &result {
+                    Ok((info, ping)) => ServerStatus::Online {
+                        info: info.clone(),
+                        ping: *ping
+                    },
+
+                    Err(err) => ServerStatus::Offline(err.clone())
+                };
+            }
+        }
+    }
+}
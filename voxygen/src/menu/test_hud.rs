@@ -22,11 +22,16 @@ use conrod_core::{
 	}
 };
 
+// projeto
+use common::msg::ChatChannel;
+
 // caixote
 use crate::{
 	window::Window,
 	render::Renderer,
-	ui::Ui
+	ui::Ui,
+
+	hud::chat::Chat
 };
 
 widget_ids!{
@@ -95,6 +100,8 @@ pub struct TestHud {
 	
 	bag_open: bool,
 	menu_open: bool,
+
+	chat: Chat,
 	
 	font_id: FontId
 }
@@ -116,6 +123,8 @@ impl TestHud {
 			concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/font/Metamorphous-Regular.ttf")
 		).unwrap());
 
+		let chat = Chat::new(&mut ui);
+
 		Self {
 			ui,
 			imgs,
@@ -124,11 +133,14 @@ impl TestHud {
 			bag_open: false,
 			menu_open: false,
 
+			chat,
+
 			font_id
 		}
 	}
 
-	fn update_layout(&mut self) {
+	/// retorna a linha digitada no chat quando o jogador pressionar enter
+	fn update_layout(&mut self) -> Option<String> {
 		// muito útil
 		let TestHud {
 			ref mut ui,
@@ -138,10 +150,14 @@ impl TestHud {
 			ref mut bag_open,
 			ref mut menu_open,
 
+			ref mut chat,
+
 			..
 		} = *self;
 
 		let ref mut ui_cell = ui.set_widgets();
+
+		let chat_input = chat.update_layout(ui_cell);
 		
 		// bolsa
 		if Button::image(imgs.bag)
@@ -228,20 +244,35 @@ impl TestHud {
                     .set(ids.menu_buttons[i], ui_cell);
             }
 		}
+
+		chat_input
 	}
 
 	pub fn toggle_menu(&mut self) {
         self.menu_open = !self.menu_open;
     }
 
+	/// mostra uma mensagem recebida do servidor no chat
+	pub fn new_chat_message(&mut self, channel: &ChatChannel, sender: Option<&str>, msg: &str) {
+		self.chat.new_message(channel, sender, msg);
+	}
+
+	/// verifica se o jogador está digitando no chat
+	pub fn is_typing(&self) -> bool {
+		self.chat.is_typing(&self.ui)
+	}
+
 	pub fn handle_event(&mut self, input: Input) {
         self.ui.handle_event(input);
     }
 
-    pub fn maintain(&mut self, renderer: &mut Renderer) {
-        self.update_layout();
+	/// mantém a ui, retornando a linha enviada pelo chat caso exista
+    pub fn maintain(&mut self, renderer: &mut Renderer) -> Option<String> {
+        let chat_input = self.update_layout();
 		
         self.ui.maintain(renderer);
+
+		chat_input
     }
 
     pub fn render(&self, renderer: &mut Renderer) {
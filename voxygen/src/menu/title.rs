@@ -13,7 +13,7 @@ use crate::{
 		Window
 	},
 	
-	session::SessionState
+	menu::main::MainMenuState
 };
 
 // local
@@ -44,9 +44,9 @@ impl PlayState for TitleState {
                 match event {
                     Event::Close => return PlayStateResult::Shutdown,
 
-                    // quando o espaço é pressionado, iniciar uma sessão
+                    // quando o espaço é pressionado, ir para o menu principal
                     Event::Char(' ') => return PlayStateResult::Push(
-                        Box::new(SessionState::new(&mut global_state.window).unwrap()) // todo: auxiliar esse erro
+                        Box::new(MainMenuState::new(&mut global_state.window))
                     ),
 
 					// passar eventos para ui
@@ -17,7 +17,7 @@ use crate::{
 	window::Window,
     render::Renderer,
     ui::Ui
-}
+};
 
 pub struct TitleUi {
 	ui: Ui,
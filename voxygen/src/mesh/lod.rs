@@ -0,0 +1,223 @@
+// biblioteca
+use vek::*;
+
+// projeto
+use common::{
+    vol::{
+        Vox,
+
+        SizedVol,
+        ReadVol,
+        WriteVol
+    },
+
+    volumes::dyna::Dyna,
+    terrain::Block
+};
+
+// caixote
+use crate::{
+    mesh::{
+        Meshable,
+
+        terrain::TerrainMesher
+    },
+
+    render::{
+        Mesh,
+        TerrainPipeline
+    }
+};
+
+/// nível de detalhe de um chunk, em que cada voxel do mesh cobre `2^nível` voxels em cada eixo
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Lod(u8);
+
+impl Lod {
+    /// nível mais baixo de detalhe, em que cada voxel do mesh cobre 8x8x8 voxels
+    pub const MAX_LEVEL: u8 = 3;
+
+    pub fn new(level: u8) -> Self {
+        Lod(level.min(Self::MAX_LEVEL))
+    }
+
+    /// detalhe completo, um voxel do mesh para cada voxel do chunk
+    pub fn full() -> Self {
+        Self::default()
+    }
+
+    pub fn level(self) -> u8 {
+        self.0
+    }
+
+    /// quantos voxels do chunk cada voxel do mesh cobre em cada eixo
+    pub fn scale(self) -> i32 {
+        1 << self.0
+    }
+}
+
+/// reduz o volume, cuja borda de um voxel serve apenas para consultar os vizinhos, para o nível de detalhe fornecido
+///
+/// cada célula é sólida quando pelo menos metade dos voxels que ela cobre são sólidos, com o tipo mais comum e a média
+/// das cores desses voxels. a borda do volume reduzido é vazia, de forma que as faces nas laterais do chunk sempre são
+/// geradas e cobrem as frestas entre chunks de níveis diferentes
+pub fn downsample<V>(vol: &V, lod: Lod) -> Dyna<Block, ()>
+where
+    V: ReadVol<Vox = Block> + SizedVol
+{
+    let scale = lod.scale();
+    let cells = vol.get_size().map(|e| (e as i32 - 2) / scale);
+
+    let mut sample = Dyna::filled(cells.map(|e| e as u32 + 2), Block::empty(), ());
+
+    for x in 0..cells.x {
+        for y in 0..cells.y {
+            for z in 0..cells.z {
+                let cell = Vec3::new(x, y, z);
+
+                if let Some(block) = downsample_cell(vol, Vec3::one() + cell * scale, scale) {
+                    let _ = sample.set(Vec3::one() + cell, block);
+                }
+            }
+        }
+    }
+
+    sample
+}
+
+/// combina os `scale³` voxels a partir de `min` em um único bloco, caso a maioria seja sólida
+fn downsample_cell<V>(vol: &V, min: Vec3<i32>, scale: i32) -> Option<Block>
+where
+    V: ReadVol<Vox = Block>
+{
+    let mut solid = 0;
+    let mut col_sum = Rgb::<u32>::zero();
+    let mut kinds: Vec<(u8, u32)> = Vec::new();
+
+    for x in 0..scale {
+        for y in 0..scale {
+            for z in 0..scale {
+                let block = match vol.get(min + Vec3::new(x, y, z)) {
+                    Ok(block) if !block.is_empty() => block,
+
+                    _ => continue
+                };
+
+                solid += 1;
+
+                if let Some(col) = block.get_color() {
+                    col_sum += col.map(|e| e as u32);
+                }
+
+                match kinds.iter_mut().find(|(kind, _)| *kind == block.get_kind()) {
+                    Some((_, count)) => *count += 1,
+
+                    None => kinds.push((block.get_kind(), 1))
+                }
+            }
+        }
+    }
+
+    if solid == 0 || solid * 2 < scale * scale * scale {
+        return None;
+    }
+
+    let kind = kinds
+        .iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(kind, _)| *kind)?;
+
+    Some(Block::new(kind, col_sum.map(|e| (e / solid as u32) as u8)))
+}
+
+/// gera o mesh do volume no nível de detalhe fornecido, no mesmo espaço do mesh de detalhe completo
+pub fn lod_mesh<V>(vol: &V, lod: Lod, mesher: TerrainMesher) -> Mesh<TerrainPipeline>
+where
+    V: ReadVol<Vox = Block> + SizedVol + Meshable<Pipeline = TerrainPipeline, Supplement = TerrainMesher>
+{
+    if lod == Lod::full() {
+        return vol.generate_mesh(mesher);
+    }
+
+    let scale = lod.scale() as f32;
+    let mut mesh = Mesh::new();
+
+    // os meshers posicionam o voxel `p` da parte interna do volume em `p + 2`, e essa origem é mantida ao escalar
+    mesh.push_mesh_map(&downsample(vol, lod).generate_mesh(mesher), |mut vert| {
+        vert.pos = (Vec3::from(vert.pos) - 2.0).map(|e| e * scale + 2.0).into_array();
+
+        vert
+    });
+
+    mesh
+}
+
+// testes
+
+#[cfg(test)]
+fn flat_ground(height: i32) -> Dyna<Block, ()> {
+    let mut vol = Dyna::filled(Vec3::broadcast(34), Block::empty(), ());
+
+    for pos in vol.iter_positions().filter(|pos| pos.z < height).collect::<Vec<_>>() {
+        vol.set(pos, Block::new(1, Rgb::new(40, 160, 40))).unwrap();
+    }
+
+    vol
+}
+
+/// maior altura das faces voltadas para cima do mesh
+#[cfg(test)]
+fn top_height(mesh: &Mesh<TerrainPipeline>) -> f32 {
+    mesh.vertices()
+        .iter()
+        .filter(|vert| vert.norm == [0.0, 0.0, 1.0])
+        .map(|vert| vert.pos[2])
+        .fold(std::f32::MIN, f32::max)
+}
+
+#[test]
+fn downsample_majority() {
+    let mut vol = Dyna::filled(Vec3::broadcast(6), Block::empty(), ());
+
+    // a primeira célula 2x2x2 tem metade dos voxels sólidos, a segunda apenas um
+    for &(pos, col) in [
+        (Vec3::new(1, 1, 1), Rgb::new(200, 0, 0)),
+        (Vec3::new(2, 1, 1), Rgb::new(200, 0, 0)),
+        (Vec3::new(1, 2, 1), Rgb::new(0, 0, 100)),
+        (Vec3::new(2, 2, 1), Rgb::new(0, 0, 100)),
+
+        (Vec3::new(3, 3, 3), Rgb::new(255, 255, 255))
+    ].iter() {
+        vol.set(pos, Block::new(1, col)).unwrap();
+    }
+
+    let sample = downsample(&vol, Lod::new(1));
+
+    assert_eq!(sample.get_size(), Vec3::broadcast(4));
+
+    assert_eq!(sample.get(Vec3::new(1, 1, 1)).unwrap().get_color(), Some(Rgb::new(100, 0, 50)));
+    assert!(sample.get(Vec3::new(2, 2, 2)).unwrap().is_empty());
+
+    // a borda sempre fica vazia
+    assert!(sample.iter_positions()
+        .filter(|pos| pos.map(|e| e == 0 || e == 3).reduce_or())
+        .all(|pos| sample.get(pos).unwrap().is_empty())
+    );
+}
+
+#[test]
+fn lod_keeps_surface_height() {
+    let vol = flat_ground(17);
+    let full = lod_mesh(&vol, Lod::full(), TerrainMesher::Greedy);
+
+    for level in 1..=Lod::MAX_LEVEL {
+        let mesh = lod_mesh(&vol, Lod::new(level), TerrainMesher::Greedy);
+
+        // 16 voxels de chão cabem exatamente em qualquer nível
+        assert_eq!(top_height(&mesh), top_height(&full));
+
+        // as laterais do chunk também geram faces, cobrindo frestas entre níveis
+        assert!(mesh.vertices().iter().any(|vert| vert.norm == [1.0, 0.0, 0.0]));
+        assert!(mesh.vertices().len() < 6 * 6 * 6);
+    }
+}
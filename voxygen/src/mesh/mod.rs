@@ -1,11 +1,23 @@
+pub mod lod;
 pub mod segment;
 pub mod terrain;
 
+// biblioteca
+use vek::*;
+
+// projeto
+use common::vol::{
+    Vox,
+
+    ReadVol
+};
+
 // caixote
 use crate::render::{
     self,
 
-    Mesh
+    Mesh,
+    Quad
 };
 
 pub trait Meshable {
@@ -14,3 +26,66 @@ pub trait Meshable {
 
     fn generate_mesh(&self, supp: Self::Supplement) -> Mesh<Self::Pipeline>;
 }
+
+/// faces de um voxel: normal, deslocamento do canto de origem do quad e os dois eixos do quad
+///
+/// os eixos são escolhidos de forma que `unit_x × unit_y` aponte para a normal
+const VOXEL_FACES: [(Vec3<i32>, Vec3<i32>, Vec3<i32>, Vec3<i32>); 6] = [
+    // -x
+    (Vec3 { x: -1, y: 0, z: 0 }, Vec3 { x: 0, y: 1, z: 0 }, Vec3 { x: 0, y: -1, z: 0 }, Vec3 { x: 0, y: 0, z: 1 }),
+    // +x
+    (Vec3 { x: 1, y: 0, z: 0 }, Vec3 { x: 1, y: 0, z: 0 }, Vec3 { x: 0, y: 1, z: 0 }, Vec3 { x: 0, y: 0, z: 1 }),
+    // -y
+    (Vec3 { x: 0, y: -1, z: 0 }, Vec3 { x: 0, y: 0, z: 0 }, Vec3 { x: 1, y: 0, z: 0 }, Vec3 { x: 0, y: 0, z: 1 }),
+    // +y
+    (Vec3 { x: 0, y: 1, z: 0 }, Vec3 { x: 0, y: 1, z: 0 }, Vec3 { x: 0, y: 0, z: 1 }, Vec3 { x: 1, y: 0, z: 0 }),
+    // -z
+    (Vec3 { x: 0, y: 0, z: -1 }, Vec3 { x: 0, y: 0, z: 0 }, Vec3 { x: 0, y: 1, z: 0 }, Vec3 { x: 1, y: 0, z: 0 }),
+    // +z
+    (Vec3 { x: 0, y: 0, z: 1 }, Vec3 { x: 0, y: 0, z: 1 }, Vec3 { x: 1, y: 0, z: 0 }, Vec3 { x: 0, y: 1, z: 0 })
+];
+
+/// oclusão ambiente de cada canto da face do voxel em `pos` voltada para `norm`, de 0 (fechado) a 3 (aberto)
+///
+/// os cantos seguem a ordem dos vértices de um quad com origem no canto mínimo e eixos `unit_x` e `unit_y`. cada canto
+/// é escurecido pelos dois vizinhos laterais e pelo vizinho diagonal na camada à frente da face, e fica completamente
+/// fechado quando os dois laterais são sólidos
+fn face_ao<V>(vol: &V, pos: Vec3<i32>, norm: Vec3<i32>, unit_x: Vec3<i32>, unit_y: Vec3<i32>) -> [u8; 4]
+where
+    V: ReadVol,
+    V::Vox: Vox
+{
+    let solid = |pos| vol
+        .get(pos)
+        .map(|v| !v.is_empty())
+        .unwrap_or(false);
+
+    let corner = |dx: Vec3<i32>, dy: Vec3<i32>| {
+        let side1 = solid(pos + norm + dx);
+        let side2 = solid(pos + norm + dy);
+
+        if side1 && side2 {
+            0
+        } else {
+            3 - side1 as u8 - side2 as u8 - solid(pos + norm + dx + dy) as u8
+        }
+    };
+
+    [
+        corner(-unit_x, -unit_y),
+        corner(unit_x, -unit_y),
+        corner(unit_x, unit_y),
+        corner(-unit_x, unit_y)
+    ]
+}
+
+/// escolhe a diagonal do quad que interpola a oclusão de forma simétrica
+///
+/// sem isso, quads com um único canto escuro mostram um degrau diferente dependendo da direção em que são vistos
+fn orient_quad<P: render::Pipeline>(quad: Quad<P>, ao: [u8; 4]) -> Quad<P> {
+    if ao[0] + ao[2] < ao[1] + ao[3] {
+        quad.rotated()
+    } else {
+        quad
+    }
+}
@@ -15,7 +15,14 @@ use common::{
 
 // caixote
 use crate::{
-    mesh::Meshable,
+    mesh::{
+        Meshable,
+
+        VOXEL_FACES,
+
+        face_ao,
+        orient_quad
+    },
 
     render::{
         self,
@@ -37,15 +44,18 @@ fn create_quad(
     norm: Vec3<f32>,
     
     col: Rgb<f32>,
+    ao: [u8; 4],
     bone: u8
 ) -> Quad<FigurePipeline> {
-    Quad::new(
-        FigureVertex::new(origin, norm, col, bone),
+    let vert_ao = |i: usize| ao[i] as f32 / 3.0;
+
+    orient_quad(Quad::new(
+        FigureVertex::new(origin, norm, col, vert_ao(0), bone),
 
-        FigureVertex::new(origin + unit_x, norm, col, bone),
-        FigureVertex::new(origin + unit_x + unit_y, norm, col, bone),
-        FigureVertex::new(origin + unit_y, norm, col, bone)
-    )
+        FigureVertex::new(origin + unit_x, norm, col, vert_ao(1), bone),
+        FigureVertex::new(origin + unit_x + unit_y, norm, col, vert_ao(2), bone),
+        FigureVertex::new(origin + unit_y, norm, col, vert_ao(3), bone)
+    ), ao)
 }
 
 impl Meshable for Segment {
@@ -63,94 +73,21 @@ impl Meshable for Segment {
             {
                 let col = col.map(|e| e as f32 / 255.0);
 
-                // -x
-                if self.get(pos - Vec3::unit_x())
-                    .map(|v| v.is_empty())
-                    .unwrap_or(true)
-                {
-                    mesh.push_quad(create_quad(
-                        offs + pos.map(|e| e as f32) + Vec3::unit_y(),
-                        -Vec3::unit_y(),
-                        Vec3::unit_z(),
-                        -Vec3::unit_x(),
-                        col,
-                        0
-                    ));
-                }
-
-                // +x
-                if self.get(pos + Vec3::unit_x())
-                    .map(|v| v.is_empty())
-                    .unwrap_or(true)
-                {
-                    mesh.push_quad(create_quad(
-                        offs + pos.map(|e| e as f32) + Vec3::unit_x(),
-                        Vec3::unit_y(),
-                        Vec3::unit_z(),
-                        Vec3::unit_x(),
-                        col,
-                        0
-                    ));
-                }
-
-                // -y
-                if self.get(pos - Vec3::unit_y())
-                    .map(|v| v.is_empty())
-                    .unwrap_or(true)
-                {
-                    mesh.push_quad(create_quad(
-                        offs + pos.map(|e| e as f32),
-                        Vec3::unit_x(),
-                        Vec3::unit_z(),
-                        -Vec3::unit_y(),
-                        col,
-                        0
-                    ));
-                }
-
-                // +y
-                if self.get(pos + Vec3::unit_y())
-                    .map(|v| v.is_empty())
-                    .unwrap_or(true)
-                {
-                    mesh.push_quad(create_quad(
-                        offs + pos.map(|e| e as f32) + Vec3::unit_y(),
-                        Vec3::unit_z(),
-                        Vec3::unit_x(),
-                        Vec3::unit_y(),
-                        col,
-                        0
-                    ));
-                }
-
-                // -z
-                if self.get(pos - Vec3::unit_z())
-                    .map(|v| v.is_empty())
-                    .unwrap_or(true)
-                {
-                    mesh.push_quad(create_quad(
-                        offs + pos.map(|e| e as f32),
-                        Vec3::unit_y(),
-                        Vec3::unit_x(),
-                        -Vec3::unit_z(),
-                        col,
-                        0
-                    ));
-                }
-
-                // +z
-                if self.get(pos + Vec3::unit_z())
-                    .map(|v| v.is_empty())
-                    .unwrap_or(true)
-                {
-                    mesh.push_quad(create_quad(
-                        offs + pos.map(|e| e as f32) + Vec3::unit_z(),
-                        Vec3::unit_x(),
-                        Vec3::unit_y(),
-                        Vec3::unit_z(),
-                        col,
-                        0
-                    ));
+                for &(norm, face_offs, unit_x, unit_y) in VOXEL_FACES.iter() {
+                    if self.get(pos + norm)
+                        .map(|v| v.is_empty())
+                        .unwrap_or(true)
+                    {
+                        mesh.push_quad(create_quad(
+                            offs + (pos + face_offs).map(|e| e as f32),
+                            unit_x.map(|e| e as f32),
+                            unit_y.map(|e| e as f32),
+                            norm.map(|e| e as f32),
+                            col,
+                            face_ao(self, pos, norm, unit_x, unit_y),
+                            0
+                        ));
+                    }
                 }
             }
         }
@@ -10,13 +10,31 @@ use common::{
         ReadVol
     },
 
-    volumes::dyna::Dyna,
-    terrain::Block
+    volumes::{
+        dyna::Dyna,
+        vol_map::ChunkNeighbourhood
+    },
+
+    vol::VolSize,
+
+    terrain::{
+        Block,
+        Light,
+
+        light::MAX_LIGHT
+    }
 };
 
 // caixote
 use crate::{
-    mesh::Meshable,
+    mesh::{
+        Meshable,
+
+        VOXEL_FACES,
+
+        face_ao,
+        orient_quad
+    },
 
     render::{
         self,
@@ -39,121 +57,603 @@ fn create_quad(
 
     norm: Vec3<f32>,
     col: Rgb<f32>,
+    ao: [u8; 4],
+    light: Light
 ) -> Quad<TerrainPipeline> {
-    Quad::new(
-        TerrainVertex::new(origin, norm, col),
-        TerrainVertex::new(origin + unit_x, norm, col),
-        TerrainVertex::new(origin + unit_x + unit_y, norm, col),
-        TerrainVertex::new(origin + unit_y, norm, col)
-    )
+    let vert_ao = |i: usize| ao[i] as f32 / 3.0;
+    let light = Vec2::new(light.get_sun(), light.get_block()).map(|e| e as f32 / MAX_LIGHT as f32);
+
+    orient_quad(Quad::new(
+        TerrainVertex::new(origin, norm, col, vert_ao(0), light),
+        TerrainVertex::new(origin + unit_x, norm, col, vert_ao(1), light),
+        TerrainVertex::new(origin + unit_x + unit_y, norm, col, vert_ao(2), light),
+        TerrainVertex::new(origin + unit_y, norm, col, vert_ao(3), light)
+    ), ao)
+}
+
+/// luz de um lugar aberto em todas as posições, para volumes sem luz calculada
+fn full_sun(_: Vec3<i32>) -> Light {
+    Light::full_sun()
+}
+
+/// algoritmo utilizado para gerar o mesh de um chunk de terreno
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TerrainMesher {
+    /// um quad para cada face exposta de cada voxel
+    Naive,
+
+    /// faces vizinhas no mesmo plano, com a mesma cor e a mesma oclusão são unidas em quads maiores
+    Greedy
+}
+
+impl Default for TerrainMesher {
+    fn default() -> Self {
+        TerrainMesher::Greedy
+    }
 }
 
 impl<M> Meshable for Dyna<Block, M> {
     type Pipeline = TerrainPipeline;
-    type Supplement = ();
+    type Supplement = TerrainMesher;
+
+    fn generate_mesh(&self, mesher: Self::Supplement) -> Mesh<Self::Pipeline> {
+        terrain_mesh(self, &full_sun, mesher)
+    }
+}
+
+/// mesh do chunk central da vizinhança, lido diretamente dos chunks do mapa
+impl<S: VolSize, M> Meshable for ChunkNeighbourhood<Block, S, M> {
+    type Pipeline = TerrainPipeline;
+    type Supplement = TerrainMesher;
 
-    fn generate_mesh(&self, _: Self::Supplement) -> Mesh<Self::Pipeline> {
-        let mut mesh = Mesh::new();
+    fn generate_mesh(&self, mesher: Self::Supplement) -> Mesh<Self::Pipeline> {
+        terrain_mesh(self, &full_sun, mesher)
+    }
+}
 
-        for pos in self
-            .iter_positions()
-            
-            .filter(|pos| pos.map(|e| e >= 1).reduce_and())
-            .filter(|pos| pos.map2(self.get_size(), |e, sz| e < sz as i32 - 1).reduce_and())
+/// gera o mesh de um volume iluminado pelo volume de luz `light`, que usa as mesmas coordenadas
+///
+/// cada face recebe a luz do voxel aberto à frente dela
+pub fn lit_terrain_mesh<V, L>(vol: &V, light: &L, mesher: TerrainMesher) -> Mesh<TerrainPipeline>
+where
+    V: ReadVol<Vox = Block> + SizedVol,
+    L: ReadVol<Vox = Light>
+{
+    terrain_mesh(vol, &|pos| light.get(pos).ok().cloned().unwrap_or(Light::full_sun()), mesher)
+}
+
+/// gera o mesh de um volume cuja borda de um voxel serve apenas para consultar os vizinhos
+fn terrain_mesh<V, L>(vol: &V, light: &L, mesher: TerrainMesher) -> Mesh<TerrainPipeline>
+where
+    V: ReadVol<Vox = Block> + SizedVol,
+    L: Fn(Vec3<i32>) -> Light
+{
+    match mesher {
+        TerrainMesher::Naive => naive_mesh(vol, light),
+        TerrainMesher::Greedy => greedy_mesh(vol, light)
+    }
+}
+
+fn naive_mesh<V, L>(vol: &V, light: &L) -> Mesh<TerrainPipeline>
+where
+    V: ReadVol<Vox = Block> + SizedVol,
+    L: Fn(Vec3<i32>) -> Light
+{
+    let mut mesh = Mesh::new();
+
+    for pos in vol
+        .iter_positions()
+
+        .filter(|pos| pos.map(|e| e >= 1).reduce_and())
+        .filter(|pos| pos.map2(vol.get_size(), |e, sz| e < sz as i32 - 1).reduce_and())
+    {
+        if let Some(col) = vol
+            .get(pos)
+            .ok()
+            .and_then(|vox| vox.get_color())
         {
-            if let Some(col) = self
-                .get(pos)
-                .ok()
-                .and_then(|vox| vox.get_color())
-            {
-                let col = col.map(|e| e as f32 / 255.0);
-
-                // -x
-                if self.get(pos - Vec3::unit_x())
-                    .map(|v| v.is_empty())
-                    .unwrap_or(true)
-                {
-                    mesh.push_quad(create_quad(
-                        Vec3::one() + pos.map(|e| e as f32) + Vec3::unit_y(),
-                        -Vec3::unit_y(),
-                        Vec3::unit_z(),
-                        -Vec3::unit_x(),
-                        col
-                    ));
-                }
+            let col = col.map(|e| e as f32 / 255.0);
 
-                // +x
-                if self.get(pos + Vec3::unit_x())
+            for &(norm, offs, unit_x, unit_y) in VOXEL_FACES.iter() {
+                if vol.get(pos + norm)
                     .map(|v| v.is_empty())
                     .unwrap_or(true)
                 {
                     mesh.push_quad(create_quad(
-                        Vec3::one() + pos.map(|e| e as f32) + Vec3::unit_x(),
-                        Vec3::unit_y(),
-                        Vec3::unit_z(),
-                        Vec3::unit_x(),
-                        col
+                        Vec3::one() + (pos + offs).map(|e| e as f32),
+                        unit_x.map(|e| e as f32),
+                        unit_y.map(|e| e as f32),
+                        norm.map(|e| e as f32),
+                        col,
+                        face_ao(vol, pos, norm, unit_x, unit_y),
+                        light(pos + norm)
                     ));
                 }
+            }
+        }
+    }
 
-                // -y
-                if self.get(pos - Vec3::unit_y())
-                    .map(|v| v.is_empty())
-                    .unwrap_or(true)
-                {
-                    mesh.push_quad(create_quad(
-                        Vec3::one() + pos.map(|e| e as f32),
-                        Vec3::unit_x(),
-                        Vec3::unit_z(),
-                        -Vec3::unit_y(),
-                        col
-                    ));
-                }
+    mesh
+}
 
-                // +y
-                if self.get(pos + Vec3::unit_y())
-                    .map(|v| v.is_empty())
-                    .unwrap_or(true)
-                {
-                    mesh.push_quad(create_quad(
-                        Vec3::one() + pos.map(|e| e as f32) + Vec3::unit_y(),
-                        Vec3::unit_z(),
-                        Vec3::unit_x(),
-                        Vec3::unit_y(),
-                        col
-                    ));
+// eixos normais e sentidos das seis faces de um voxel
+const FACES: [(usize, i32); 6] = [
+    (0, -1), (0, 1),
+    (1, -1), (1, 1),
+    (2, -1), (2, 1)
+];
+
+fn unit(axis: usize) -> Vec3<i32> {
+    match axis {
+        0 => Vec3::unit_x(),
+        1 => Vec3::unit_y(),
+        _ => Vec3::unit_z()
+    }
+}
+
+fn component(v: Vec3<i32>, axis: usize) -> i32 {
+    match axis {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z
+    }
+}
+
+/// cor, oclusão e luz da face do voxel em `pos` voltada para `norm`, caso ela esteja exposta
+fn face_info<V: ReadVol<Vox = Block>, L: Fn(Vec3<i32>) -> Light>(
+    vol: &V,
+    light: &L,
+    pos: Vec3<i32>,
+    norm: Vec3<i32>,
+    unit_x: Vec3<i32>,
+    unit_y: Vec3<i32>
+) -> Option<(Rgb<u8>, [u8; 4], Light)> {
+    let col = vol
+        .get(pos)
+        .ok()
+        .and_then(|vox| vox.get_color())?;
+
+    if vol.get(pos + norm)
+        .map(|v| v.is_empty())
+        .unwrap_or(true)
+    {
+        Some((col, face_ao(vol, pos, norm, unit_x, unit_y), light(pos + norm)))
+    } else {
+        None
+    }
+}
+
+/// gera o mesh percorrendo cada camada do volume em cada direção, unindo faces expostas de mesma cor em retângulos
+///
+/// apenas faces com a mesma oclusão em todos os cantos e a mesma luz são unidas, e somente ao longo dos eixos em
+/// que a oclusão da face é constante, para que o quad resultante seja sombreado como as faces que ele substitui
+fn greedy_mesh<V, L>(vol: &V, light: &L) -> Mesh<TerrainPipeline>
+where
+    V: ReadVol<Vox = Block> + SizedVol,
+    L: Fn(Vec3<i32>) -> Light
+{
+    let mut mesh = Mesh::new();
+
+    // assim como no mesher simples, a borda de um voxel serve apenas para consultar os vizinhos
+    let lower = Vec3::one();
+    let upper = vol.get_size().map(|e| e as i32 - 1);
+
+    for &(d, sign) in FACES.iter() {
+        // eixos do plano da face, escolhidos de forma que `u × v` aponte para `d` positivo
+        let (u, v) = ((d + 1) % 3, (d + 2) % 3);
+        let norm = unit(d) * sign;
+
+        // inverter a ordem dos eixos inverte o sentido do quad
+        let (axis_x, axis_y) = if sign > 0 { (unit(u), unit(v)) } else { (unit(v), unit(u)) };
+
+        let w = (component(upper, u) - component(lower, u)).max(0) as usize;
+        let h = (component(upper, v) - component(lower, v)).max(0) as usize;
+
+        let mut mask = vec![None; w * h];
+
+        for slice in component(lower, d)..component(upper, d) {
+            let base = unit(d) * slice + unit(u) * component(lower, u) + unit(v) * component(lower, v);
+
+            for j in 0..h {
+                for i in 0..w {
+                    let pos = base + unit(u) * i as i32 + unit(v) * j as i32;
+
+                    mask[j * w + i] = face_info(vol, light, pos, norm, axis_x, axis_y);
                 }
+            }
+
+            for j in 0..h {
+                let mut i = 0;
+
+                while i < w {
+                    let face = match mask[j * w + i] {
+                        Some(face) => face,
+
+                        None => {
+                            i += 1;
+
+                            continue;
+                        }
+                    };
+
+                    // a oclusão é interpolada entre os cantos do quad, então esticar a face ao longo de um eixo em que
+                    // ela varia espalharia o gradiente de um voxel pelo quad inteiro
+                    let ao = face.1;
+
+                    let flat_x = ao[0] == ao[1] && ao[3] == ao[2];
+                    let flat_y = ao[0] == ao[3] && ao[1] == ao[2];
+
+                    let (grow_u, grow_v) = if sign > 0 { (flat_x, flat_y) } else { (flat_y, flat_x) };
+
+                    // crescer o retângulo primeiro ao longo de `u`, depois linha por linha ao longo de `v`
+                    let mut qw = 1;
+
+                    while grow_u && i + qw < w && mask[j * w + i + qw] == Some(face) {
+                        qw += 1;
+                    }
+
+                    let mut qh = 1;
+
+                    while grow_v && j + qh < h && (0..qw).all(|k| mask[(j + qh) * w + i + k] == Some(face)) {
+                        qh += 1;
+                    }
+
+                    for y in 0..qh {
+                        for x in 0..qw {
+                            mask[(j + y) * w + i + x] = None;
+                        }
+                    }
+
+                    // faces positivas ficam do lado de fora do voxel
+                    let origin = base
+                        + unit(u) * i as i32
+                        + unit(v) * j as i32
+                        + if sign > 0 { unit(d) } else { Vec3::zero() };
+
+                    let du = unit(u).map(|e| (e * qw as i32) as f32);
+                    let dv = unit(v).map(|e| (e * qh as i32) as f32);
+
+                    let (unit_x, unit_y) = if sign > 0 { (du, dv) } else { (dv, du) };
+                    let (col, ao, light) = face;
 
-                // -z
-                if self.get(pos - Vec3::unit_z())
-                    .map(|v| v.is_empty())
-                    .unwrap_or(true)
-                {
                     mesh.push_quad(create_quad(
-                        Vec3::one() + pos.map(|e| e as f32),
-                        Vec3::unit_y(),
-                        Vec3::unit_z(),
-                        -Vec3::unit_z(),
-                        col
+                        Vec3::one() + origin.map(|e| e as f32),
+                        unit_x,
+                        unit_y,
+                        norm.map(|e| e as f32),
+                        col.map(|e| e as f32 / 255.0),
+                        ao,
+                        light
                     ));
+
+                    i += qw;
                 }
+            }
+        }
+    }
 
-                // +z
-                if self.get(pos + Vec3::unit_z())
-                    .map(|v| v.is_empty())
-                    .unwrap_or(true)
-                {
-                    mesh.push_quad(create_quad(
-                        Vec3::one() + pos.map(|e| e as f32) + Vec3::unit_z(),
-                        Vec3::unit_x(),
-                        Vec3::unit_y(),
-                        Vec3::unit_z(),
-                        col
-                    ));
+    mesh
+}
+
+// testes
+
+/// decompõe os quads do mesh em faces unitárias, identificadas pelo canto mínimo, normal e cor
+#[cfg(test)]
+fn unit_faces(mesh: &Mesh<TerrainPipeline>) -> Vec<([i32; 3], [i32; 3], [u8; 3])> {
+    let mut faces = Vec::new();
+
+    // cada quad ocupa seis vértices, na ordem a, b, c, c, d, a
+    for quad in mesh.vertices().chunks(6) {
+        let corners = [quad[0].pos, quad[1].pos, quad[2].pos, quad[4].pos];
+
+        let min = corners.iter().fold(Vec3::broadcast(std::f32::MAX), |m, c| Vec3::partial_min(m, Vec3::from(*c)));
+        let max = corners.iter().fold(Vec3::broadcast(std::f32::MIN), |m, c| Vec3::partial_max(m, Vec3::from(*c)));
+
+        let min = min.map(|e| e.round() as i32);
+        let max = max.map(|e| e.round() as i32);
+
+        let norm = Vec3::from(quad[0].norm).map(|e| e.round() as i32);
+        let col = Vec3::from(quad[0].col).map(|e| (e * 255.0).round() as u8);
+
+        // o sentido dos triângulos deve concordar com a normal
+        let a = Vec3::from(quad[0].pos);
+        let b = Vec3::from(quad[1].pos);
+        let c = Vec3::from(quad[2].pos);
+
+        assert!((b - a).cross(c - a).dot(norm.map(|e| e as f32)) > 0.0);
+
+        let ext = (max - min).map(|e| e.max(1));
+
+        for x in 0..ext.x {
+            for y in 0..ext.y {
+                for z in 0..ext.z {
+                    faces.push(((min + Vec3::new(x, y, z)).into_array(), norm.into_array(), col.into_array()));
                 }
             }
         }
+    }
+
+    faces.sort();
+
+    faces
+}
+
+#[cfg(test)]
+fn random_volume(seed: &mut u64, size: u32, density: u64) -> Dyna<Block, ()> {
+    use common::vol::WriteVol;
+
+    // poucas cores, para que o mesher guloso tenha faces para unir
+    let colors = [Rgb::new(40, 160, 40), Rgb::new(128, 128, 128), Rgb::new(120, 80, 40)];
+
+    let mut vol = Dyna::filled(Vec3::broadcast(size), Block::empty(), ());
+
+    for pos in vol.iter_positions().collect::<Vec<_>>() {
+        if common::util::test_rng(seed) % 100 < density {
+            let col = colors[common::util::test_rng(seed) as usize % colors.len()];
+
+            vol.set(pos, Block::new(1, col)).unwrap();
+        }
+    }
+
+    vol
+}
+
+#[test]
+fn greedy_covers_same_surface() {
+    let mut seed = 42;
+
+    for &density in [5, 30, 60, 95].iter() {
+        for _ in 0..8 {
+            let vol = random_volume(&mut seed, 12, density);
+
+            let naive = vol.generate_mesh(TerrainMesher::Naive);
+            let greedy = vol.generate_mesh(TerrainMesher::Greedy);
+
+            assert!(greedy.vertices().len() <= naive.vertices().len());
+
+            // as faces unitárias são iguais, incluindo repetições, então nenhum quad se sobrepõe
+            assert_eq!(unit_faces(&greedy), unit_faces(&naive));
+        }
+    }
+}
+
+#[test]
+fn greedy_flat_ground() {
+    use common::vol::WriteVol;
+
+    let mut vol = Dyna::filled(Vec3::broadcast(34), Block::empty(), ());
+
+    for pos in vol.iter_positions().filter(|pos| pos.z < 10).collect::<Vec<_>>() {
+        vol.set(pos, Block::new(1, Rgb::new(40, 160, 40))).unwrap();
+    }
+
+    let naive = vol.generate_mesh(TerrainMesher::Naive);
+    let greedy = vol.generate_mesh(TerrainMesher::Greedy);
+
+    // o chão plano vira um único quad no topo, em vez de um por voxel
+    assert_eq!(naive.vertices().len(), 32 * 32 * 6);
+    assert_eq!(greedy.vertices().len(), 6);
+
+    assert_eq!(unit_faces(&greedy), unit_faces(&naive));
+}
+
+#[test]
+fn ambient_occlusion() {
+    use common::vol::WriteVol;
+
+    // um chão com um único voxel em cima
+    let mut vol = Dyna::filled(Vec3::broadcast(7), Block::empty(), ());
+
+    for pos in vol.iter_positions().filter(|pos| pos.z < 2).collect::<Vec<_>>() {
+        vol.set(pos, Block::new(1, Rgb::new(40, 160, 40))).unwrap();
+    }
+
+    vol.set(Vec3::new(3, 3, 2), Block::new(1, Rgb::new(128, 128, 128))).unwrap();
+
+    for &mesher in [TerrainMesher::Naive, TerrainMesher::Greedy].iter() {
+        let mesh = vol.generate_mesh(mesher);
+
+        // apenas os cantos do chão que encostam no voxel são escurecidos
+        for vert in mesh.vertices().iter().filter(|vert| vert.norm == [0.0, 0.0, 1.0] && vert.pos[2] == 3.0) {
+            let touches = [4.0, 5.0].contains(&vert.pos[0]) && [4.0, 5.0].contains(&vert.pos[1]);
+
+            assert_eq!(vert.ao < 1.0, touches, "{:?} {:?}", mesher, vert.pos);
+        }
+
+        // a diagonal compartilhada pelos dois triângulos liga os cantos mais claros
+        for quad in mesh.vertices().chunks(6) {
+            assert!(quad[0].ao + quad[2].ao >= quad[1].ao + quad[4].ao);
+        }
+    }
+}
+
+#[test]
+fn greedy_ambient_occlusion_matches_naive() {
+    use std::collections::HashMap;
+
+    use common::vol::WriteVol;
+
+    // um chão com um degrau em forma de l em cima, que escurece as faces em volta dele em direções diferentes
+    let mut vol = Dyna::filled(Vec3::broadcast(12), Block::empty(), ());
+
+    for pos in vol.iter_positions().filter(|pos| pos.z < 3).collect::<Vec<_>>() {
+        vol.set(pos, Block::new(1, Rgb::new(40, 160, 40))).unwrap();
+    }
+
+    for i in 3..9 {
+        vol.set(Vec3::new(i, 3, 3), Block::new(1, Rgb::new(40, 160, 40))).unwrap();
+        vol.set(Vec3::new(3, i, 3), Block::new(1, Rgb::new(40, 160, 40))).unwrap();
+    }
+
+    let key = |pos: [f32; 3], norm: [f32; 3]| (Vec3::from(pos).map(|e| e.round() as i32), Vec3::from(norm).map(|e| e.round() as i32));
+
+    // a oclusão de cada canto do mesh simples, que é a mesma em todas as faces que compartilham o canto
+    let mut naive_ao = HashMap::new();
+
+    for vert in vol.generate_mesh(TerrainMesher::Naive).vertices() {
+        let ao = *naive_ao.entry(key(vert.pos, vert.norm)).or_insert(vert.ao);
+
+        assert_eq!(ao, vert.ao, "{:?}", vert.pos);
+    }
+
+    // a oclusão interpolada em cada canto de voxel coberto por um quad do mesh guloso deve ser a do mesh simples
+    for quad in vol.generate_mesh(TerrainMesher::Greedy).vertices().chunks(6) {
+        let (a, b, c, d) = (&quad[0], &quad[1], &quad[2], &quad[4]);
 
-        mesh
+        let origin = Vec3::from(a.pos);
+        let edge_x = Vec3::from(b.pos) - origin;
+        let edge_y = Vec3::from(d.pos) - origin;
+
+        let (len_x, len_y) = (edge_x.magnitude().round() as usize, edge_y.magnitude().round() as usize);
+
+        for x in 0..=len_x {
+            for y in 0..=len_y {
+                let (s, t) = (x as f32 / len_x as f32, y as f32 / len_y as f32);
+
+                let ao = a.ao * (1.0 - s) * (1.0 - t) + b.ao * s * (1.0 - t) + c.ao * s * t + d.ao * (1.0 - s) * t;
+                let pos = origin + edge_x * s + edge_y * t;
+
+                let expected = naive_ao[&key(pos.into_array(), a.norm)];
+
+                assert!((ao - expected).abs() < 1e-4, "{:?} {:?}: {} != {}", pos, a.norm, ao, expected);
+            }
+        }
+    }
+}
+
+/// mapa de 3x3x3 chunks com colinas suaves, parecido com o terreno gerado pelo mundo
+#[cfg(test)]
+fn hills_terrain_map() -> common::terrain::TerrainMap {
+    use common::{
+        vol::WriteVol,
+
+        terrain::{
+            TerrainMap,
+            TerrainChunk,
+            TerrainChunkMeta
+        }
+    };
+
+    let mut map = TerrainMap::new();
+
+    for x in -1..=1 {
+        for y in -1..=1 {
+            for z in -1..=1 {
+                map.insert(Vec3::new(x, y, z), TerrainChunk::filled(Block::empty(), TerrainChunkMeta::void()));
+            }
+        }
+    }
+
+    let size = TerrainMap::chunk_size().map(|e| e as i32);
+
+    for x in -size.x..size.x * 2 {
+        for y in -size.y..size.y * 2 {
+            let height = ((x as f32 * 0.15).sin() * 6.0 + (y as f32 * 0.1).cos() * 6.0 + 16.0) as i32;
+
+            for z in -size.z..height {
+                let col = if z + 1 == height { Rgb::new(40, 160, 40) } else { Rgb::new(120, 80, 40) };
+
+                map.set(Vec3::new(x, y, z), Block::new(1, col)).unwrap();
+            }
+        }
     }
+
+    map
+}
+
+#[cfg(test)]
+fn hills_sample(map: &common::terrain::TerrainMap) -> Dyna<Block, ()> {
+    use common::{
+        vol::SampleVol,
+        terrain::TerrainMap
+    };
+
+    let aabb = Aabb {
+        min: Vec3::broadcast(-1),
+        max: TerrainMap::chunk_size().map(|e| e as i32 + 1)
+    };
+
+    map.sample(aabb).unwrap()
+}
+
+#[test]
+fn neighbourhood_mesh_matches_sample() {
+    let map = hills_terrain_map();
+
+    let sample = hills_sample(&map);
+    let neighbourhood = map.neighbourhood(Vec3::zero()).unwrap();
+
+    for &mesher in [TerrainMesher::Naive, TerrainMesher::Greedy].iter() {
+        assert_eq!(
+            unit_faces(&neighbourhood.generate_mesh(mesher)),
+            unit_faces(&sample.generate_mesh(mesher))
+        );
+    }
+}
+
+#[test]
+fn lit_mesh_reads_light() {
+    use common::terrain::{
+        LightMap,
+
+        light::light_chunk
+    };
+
+    let map = hills_terrain_map();
+    let mut light = LightMap::new();
+
+    for key in map.keys().collect::<Vec<_>>() {
+        light_chunk(&map, &mut light, key);
+    }
+
+    let neighbourhood = map.neighbourhood(Vec3::zero()).unwrap();
+    let light_neighbourhood = light.neighbourhood(Vec3::zero()).unwrap();
+
+    for &mesher in [TerrainMesher::Naive, TerrainMesher::Greedy].iter() {
+        let lit = lit_terrain_mesh(&neighbourhood, &light_neighbourhood, mesher);
+
+        // a luz não muda a forma do mesh
+        assert_eq!(unit_faces(&lit), unit_faces(&neighbourhood.generate_mesh(mesher)));
+
+        // as colinas ficam a céu aberto, e nenhuma face do terreno fica completamente sem luz
+        assert!(lit.vertices()
+            .iter()
+            .filter(|vert| vert.norm == [0.0, 0.0, 1.0])
+            .all(|vert| vert.light == [1.0, 0.0])
+        );
+
+        assert!(lit.vertices().iter().all(|vert| vert.light[0] > 0.0));
+    }
+}
+
+// benchmarks
+
+#[bench]
+fn bench_mesh_sampled_greedy(b: &mut test::Bencher) {
+    let map = hills_terrain_map();
+
+    // o caminho antigo: copiar o chunk e a borda para um volume novo antes de cada mesh
+    b.iter(|| hills_sample(&map).generate_mesh(TerrainMesher::Greedy));
+}
+
+#[bench]
+fn bench_mesh_neighbourhood_greedy(b: &mut test::Bencher) {
+    let map = hills_terrain_map();
+
+    b.iter(|| map
+        .neighbourhood(Vec3::zero())
+        .unwrap()
+        .generate_mesh(TerrainMesher::Greedy)
+    );
+}
+
+#[bench]
+fn bench_mesh_neighbourhood_naive(b: &mut test::Bencher) {
+    let map = hills_terrain_map();
+
+    b.iter(|| map
+        .neighbourhood(Vec3::zero())
+        .unwrap()
+        .generate_mesh(TerrainMesher::Naive)
+    );
 }
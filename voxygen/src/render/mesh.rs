@@ -48,20 +48,20 @@ impl<P: Pipeline> Mesh<P> {
         self.verts.push(quad.c);
         self.verts.push(quad.d);
         self.verts.push(quad.a);
+    }
 
-        /// puxa os vértices de outro mesh no fim desse mesh
-        pub fn push_mesh(&mut self, other: &Mesh<P>) {
-            self.verts.extend_from_slice(other.vertices());
-        }
+    /// puxa os vértices de outro mesh no fim desse mesh
+    pub fn push_mesh(&mut self, other: &Mesh<P>) {
+        self.verts.extend_from_slice(other.vertices());
+    }
 
-        /// puxa os vértices de outro mesh no fim desse mesh
-        pub fn push_mesh_map<F: FnMut(P::Vertex) -> P::Vertex>(&mut self, other: &Mesh<P>, mut f: F) {
-            // reservar espaço suficiente no vec. não necessário, mas reduz o número de alocações necessárias
-            self.verts.reserve(other.vertices().len());
+    /// puxa os vértices de outro mesh no fim desse mesh
+    pub fn push_mesh_map<F: FnMut(P::Vertex) -> P::Vertex>(&mut self, other: &Mesh<P>, mut f: F) {
+        // reservar espaço suficiente no vec. não necessário, mas reduz o número de alocações necessárias
+        self.verts.reserve(other.vertices().len());
 
-            for vert in other.vertices() {
-                self.verts.push(f(vert.clone()));
-            }
+        for vert in other.vertices() {
+            self.verts.push(f(vert.clone()));
         }
     }
 }
@@ -100,4 +100,14 @@ impl<P: Pipeline> Quad<P> {
     ) -> Self {
         Self { a, b, c, d }
     }
+
+    /// gira os vértices do quad, de forma que ele seja dividido pela outra diagonal sem mudar de sentido
+    pub fn rotated(self) -> Self {
+        Self {
+            a: self.b,
+            b: self.c,
+            c: self.d,
+            d: self.a
+        }
+    }
 }
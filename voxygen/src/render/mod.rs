@@ -28,6 +28,7 @@ pub use self::{
 
     pipelines::{
         Globals,
+        SkyLight,
 
         figure::{
             FigurePipeline,
@@ -29,10 +29,36 @@ gfx_defines! {
         
         view_distance: [f32; 4] = "view_distance",
         time_of_day: [f32; 4] = "time_of_day", // TODO: fazer disso um f64
-        tick: [f32; 4] = "tick"
+        tick: [f32; 4] = "tick",
+
+        sun_dir: [f32; 4] = "sun_dir",
+        sun_color: [f32; 4] = "sun_color",
+        moon_dir: [f32; 4] = "moon_dir",
+        moon_color: [f32; 4] = "moon_color",
+
+        ambient: [f32; 4] = "ambient",
+        sky_top: [f32; 4] = "sky_top",
+        sky_bottom: [f32; 4] = "sky_bottom"
     }
 }
 
+/// iluminação do céu em um momento do dia, compartilhada por todos os shaders
+///
+/// as direções apontam da superfície para o astro, e as cores já incluem a intensidade da luz
+#[derive(Copy, Clone, Debug)]
+pub struct SkyLight {
+    pub sun_dir: Vec3<f32>,
+    pub sun_color: Rgb<f32>,
+
+    pub moon_dir: Vec3<f32>,
+    pub moon_color: Rgb<f32>,
+
+    pub ambient: Rgb<f32>,
+
+    pub sky_top: Rgb<f32>,
+    pub sky_bottom: Rgb<f32>
+}
+
 impl Globals {
     /// cria novas constantes globais com valores padrões
     pub fn default() -> Self {
@@ -45,7 +71,16 @@ impl Globals {
             
             view_distance: [0.0; 4],
             time_of_day: [0.0; 4],
-            tick: [0.0; 4]
+            tick: [0.0; 4],
+
+            sun_dir: [0.0; 4],
+            sun_color: [0.0; 4],
+            moon_dir: [0.0; 4],
+            moon_color: [0.0; 4],
+
+            ambient: [0.0; 4],
+            sky_top: [0.0; 4],
+            sky_bottom: [0.0; 4]
         }
     }
 
@@ -59,7 +94,9 @@ impl Globals {
 
         view_distance: f32,
         time_of_day: f32,
-        tick: f64
+        tick: f64,
+
+        light: SkyLight
     ) -> Self {
         Self {
             view_mat: arr_to_mat(view_mat.into_col_array()),
@@ -70,7 +107,16 @@ impl Globals {
 
             view_distance: [view_distance; 4],
             time_of_day: [time_of_day as f32; 4],
-            tick: [tick as f32; 4]
+            tick: [tick as f32; 4],
+
+            sun_dir: Vec4::from(light.sun_dir).into_array(),
+            sun_color: Rgba::from_opaque(light.sun_color).into_array(),
+            moon_dir: Vec4::from(light.moon_dir).into_array(),
+            moon_color: Rgba::from_opaque(light.moon_color).into_array(),
+
+            ambient: Rgba::from_opaque(light.ambient).into_array(),
+            sky_top: Rgba::from_opaque(light.sky_top).into_array(),
+            sky_bottom: Rgba::from_opaque(light.sky_bottom).into_array()
         }
     }
 }
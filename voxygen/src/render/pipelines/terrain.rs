@@ -32,6 +32,8 @@ gfx_defines! {
         pos: [f32; 3] = "v_pos";
         norm: [f32; 3] = "v_norm";
         col: [f32; 3] = "v_col";
+        ao: f32 = "v_ao";
+        light: [f32; 2] = "v_light";
     }
 
     constant Locals {
@@ -50,11 +52,14 @@ gfx_defines! {
 }
 
 impl Vertex {
-    pub fn new(pos: Vec3<f32>, norm: Vec3<f32>, col: Rgb<f32>) -> Self {
+    /// `light` guarda a exposição ao céu e a luz de blocos que chegam ao vértice, entre 0 e 1
+    pub fn new(pos: Vec3<f32>, norm: Vec3<f32>, col: Rgb<f32>, ao: f32, light: Vec2<f32>) -> Self {
         Self {
             pos: pos.into_array(),
             col: col.into_array(),
-            norm: norm.into_array()
+            norm: norm.into_array(),
+            ao,
+            light: light.into_array()
         }
     }
 }
@@ -152,10 +152,10 @@ impl Renderer {
     }
 
     /// cria um novo conjunto de constantes com os valores fornecidos
-    pub fn create_consts<T: Copy + gfx::traits::Pod> {
+    pub fn create_consts<T: Copy + gfx::traits::Pod>(
         &mut self,
         vals: &[T]
-    } -> Result<Consts<T>, RenderError> {
+    ) -> Result<Consts<T>, RenderError> {
         let mut consts = Consts::new(&mut self.factory, vals.len());
 
         consts.update(&mut self.encoder, vals)?;
@@ -164,11 +164,11 @@ impl Renderer {
     }
 
     /// atualizar lista de conjuntos com os valores fornecidos
-    pub fn update_consts<T: Copy + gfx::traits::Pod> {
+    pub fn update_consts<T: Copy + gfx::traits::Pod>(
         &mut self,
         consts: &mut Consts<T>,
         vals: &[T]
-    } -> Result<(), RenderError> {
+    ) -> Result<(), RenderError> {
         consts.update(&mut self.encoder, vals)
     }
 
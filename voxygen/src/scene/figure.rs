@@ -5,6 +5,26 @@ use specs::{
 };
 
 use vek::*;
+use dot_vox;
+
+use common::{
+    figure::Segment,
+
+    comp::character::{
+        Character,
+
+        Race,
+        Gender,
+
+        Head,
+        Chest,
+        Belt,
+        Pants,
+        Hand,
+        Foot,
+        Weapon
+    }
+};
 
 use crate::{
     Error,
@@ -21,9 +41,122 @@ use crate::{
         FigureLocals
     },
 
-    anim::Skeleton
+    anim::Skeleton,
+    mesh::Meshable
 };
 
+// todo: fazer um asset proper para carregar o sistema
+pub fn load_segment(filename: &str) -> Segment {
+    Segment::from(dot_vox::load(&(concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/").to_string() + filename)).unwrap())
+}
+
+/// cor da pele de cada raça, aplicada sobre as partes do corpo que a deixam à mostra
+fn skin_tint(race: Race) -> Rgb<f32> {
+    match race {
+        Race::Danari => Rgb::new(0.55, 0.7, 1.0),
+        Race::Dwarf => Rgb::new(1.0, 0.8, 0.7),
+        Race::Elf => Rgb::new(0.85, 1.0, 0.85),
+        Race::Human => Rgb::new(1.0, 1.0, 1.0),
+        Race::Orc => Rgb::new(0.6, 0.85, 0.5),
+        Race::Undead => Rgb::new(0.7, 0.7, 0.75)
+    }
+}
+
+/// proporções do tronco de cada gênero
+fn torso_scale(gender: Gender) -> Vec3<f32> {
+    match gender {
+        Gender::Female => Vec3::new(0.9, 0.95, 1.0),
+        Gender::Male => Vec3::new(1.1, 1.05, 1.0),
+        Gender::Unspecified => Vec3::one()
+    }
+}
+
+fn tinted(mesh: Mesh<FigurePipeline>, tint: Rgb<f32>) -> Mesh<FigurePipeline> {
+    let mut tinted = Mesh::new();
+
+    tinted.push_mesh_map(&mesh, |mut vert| {
+        vert.col = (Rgb::from(vert.col) * tint).into_array();
+
+        vert
+    });
+
+    tinted
+}
+
+fn scaled(mesh: Mesh<FigurePipeline>, scale: Vec3<f32>) -> Mesh<FigurePipeline> {
+    let mut scaled = Mesh::new();
+
+    scaled.push_mesh_map(&mesh, |mut vert| {
+        vert.pos = (Vec3::from(vert.pos) * scale).into_array();
+
+        vert
+    });
+
+    scaled
+}
+
+/// gera os meshes de cada osso de um `CharacterSkeleton` por meio das partes do corpo do personagem
+pub fn character_bone_meshes(character: &Character) -> [Option<Mesh<FigurePipeline>>; 16] {
+    let head = match character.head {
+        Head::DefaultHead => "head.vox",
+        Head::ElfHead => "elf/head.vox"
+    };
+
+    let chest = match character.chest {
+        Chest::DefaultChest => "chest.vox",
+        Chest::ElfChest => "elf/chest.vox"
+    };
+
+    let belt = match character.belt {
+        Belt::DefaultBelt => "belt.vox",
+        Belt::ElfBelt => "elf/belt.vox"
+    };
+
+    let pants = match character.pants {
+        Pants::DefaultPants => "pants.vox",
+        Pants::ElfPants => "elf/pants.vox"
+    };
+
+    let hand = match character.hand {
+        Hand::DefaultHand => "hand.vox",
+        Hand::ElfHand => "elf/hand.vox"
+    };
+
+    let foot = match character.foot {
+        Foot::DefaultFoot => "foot.vox",
+        Foot::ElfFoot => "elf/foot.vox"
+    };
+
+    let weapon = match character.weapon {
+        Weapon::Sword => "sword.vox",
+        Weapon::ElfSword => "elf/sword.vox"
+    };
+
+    // a raça muda a cor da cabeça e das mãos, e o gênero as proporções do tronco
+    let skin = skin_tint(character.race);
+    let torso = torso_scale(character.gender);
+
+    [
+        Some(tinted(load_segment(head).generate_mesh(Vec3::new(-7.0, -5.5, -1.0)), skin)),
+        Some(scaled(load_segment(chest).generate_mesh(Vec3::new(-6.0, -3.0, 0.0)), torso)),
+        Some(scaled(load_segment(belt).generate_mesh(Vec3::new(-5.0, -3.0, 0.0)), torso)),
+        Some(scaled(load_segment(pants).generate_mesh(Vec3::new(-5.0, -3.0, 0.0)), torso)),
+        Some(tinted(load_segment(hand).generate_mesh(Vec3::new(-2.0, -2.0, -1.0)), skin)),
+        Some(tinted(load_segment(hand).generate_mesh(Vec3::new(-2.0, -2.0, -1.0)), skin)),
+        Some(load_segment(foot).generate_mesh(Vec3::new(-2.5, -3.0, -2.0))),
+        Some(load_segment(foot).generate_mesh(Vec3::new(-2.5, -3.0, -2.0))),
+        Some(load_segment(weapon).generate_mesh(Vec3::new(-6.5, -1.0, 0.0))),
+
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None
+    ]
+}
+
 pub struct Figure<S: Skeleton> {
     // dados de gpu
     model: Model<FigurePipeline>,
@@ -41,7 +174,7 @@ impl<S: Skeleton> Figure<S> {
         renderer: &mut Renderer,
         bone_mashes: [Option<Mesh<FigurePipeline>>; 16],
         skeleton: S
-    ) -> Result<Self, Error> 
+    ) -> Result<Self, Error> {
         let mut this = Self {
             model: renderer.create_model(&Mesh::new())?,
             bone_consts: renderer.create_consts(&skeleton.compute_matrices())?,
@@ -72,6 +205,17 @@ impl<S: Skeleton> Figure<S> {
         Ok(())
     }
 
+    /// substitui os meshes dos ossos, reconstruindo o modelo na gpu
+    pub fn set_bone_meshes(
+        &mut self,
+        renderer: &mut Renderer,
+        bone_mashes: [Option<Mesh<FigurePipeline>>; 16]
+    ) -> Result<(), Error> {
+        self.bone_mashes = bone_mashes;
+
+        self.update_model(renderer)
+    }
+
     pub fn update_skeleton(&mut self, renderer: &mut Renderer) -> Result<(), Error> {
         renderer.update_consts(&mut self.bone_consts, &self.skeleton.compute_matrices())?;
 
@@ -106,3 +250,23 @@ impl<S: Skeleton> Component for Figure<S> {
 	type Storage = VecStorage<Self>;
 }
 */
+
+// testes
+
+#[test]
+fn races_and_genders_change_the_figure() {
+    use common::comp::character::{ALL_RACES, ALL_GENDERS};
+
+    // cada raça precisa de uma cor de pele diferente para ser reconhecida na pré-visualização
+    for (i, a) in ALL_RACES.iter().enumerate() {
+        for b in &ALL_RACES[i + 1..] {
+            assert_ne!(skin_tint(*a), skin_tint(*b));
+        }
+    }
+
+    for (i, a) in ALL_GENDERS.iter().enumerate() {
+        for b in &ALL_GENDERS[i + 1..] {
+            assert_ne!(torso_scale(*a), torso_scale(*b));
+        }
+    }
+}
@@ -0,0 +1,153 @@
+// biblioteca
+use vek::*;
+
+/// o volume visível pela câmera, representado pelos seis planos que o limitam
+///
+/// cada plano é guardado como `(normal, d)`, com a normal apontando para dentro, de forma que um ponto `p` está do
+/// lado visível quando `normal · p + d >= 0`
+#[derive(Copy, Clone, Debug)]
+pub struct Frustum {
+    planes: [(Vec3<f32>, f32); 6]
+}
+
+impl Frustum {
+    /// extrai os planos da matriz `proj * view`, com profundidade de clip entre -1 e 1 como em `perspective_rh_no`
+    pub fn from_view_proj(view_proj_mat: Mat4<f32>) -> Self {
+        let m = view_proj_mat.into_row_array();
+        let row = |i: usize| Vec4::new(m[i * 4], m[i * 4 + 1], m[i * 4 + 2], m[i * 4 + 3]);
+
+        let plane = |v: Vec4<f32>| {
+            // normalizar permite comparar distâncias reais até os planos
+            let len = Vec3::from(v).magnitude();
+
+            (Vec3::from(v) / len, v.w / len)
+        };
+
+        Self {
+            planes: [
+                // esquerda e direita
+                plane(row(3) + row(0)),
+                plane(row(3) - row(0)),
+
+                // baixo e cima
+                plane(row(3) + row(1)),
+                plane(row(3) - row(1)),
+
+                // perto e longe
+                plane(row(3) + row(2)),
+                plane(row(3) - row(2))
+            ]
+        }
+    }
+
+    /// verifica se o ponto está dentro do frustum
+    pub fn contains_point(&self, point: Vec3<f32>) -> bool {
+        self.planes
+            .iter()
+            .all(|(norm, d)| norm.dot(point) + d >= 0.0)
+    }
+
+    /// verifica se a caixa pode ser visível
+    ///
+    /// o teste é conservador: caixas perto dos cantos do frustum podem ser consideradas visíveis mesmo estando fora
+    /// dele, mas nenhuma caixa visível é descartada
+    pub fn intersects_aabb(&self, aabb: Aabb<f32>) -> bool {
+        self.planes.iter().all(|(norm, d)| {
+            // o canto da caixa mais à frente do plano
+            let corner = Vec3::new(
+                if norm.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if norm.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if norm.z >= 0.0 { aabb.max.z } else { aabb.min.z }
+            );
+
+            norm.dot(corner) + d >= 0.0
+        })
+    }
+}
+
+/// verifica se algum ponto da caixa está a no máximo `max_dist` de `pos`
+pub fn aabb_within_distance(aabb: Aabb<f32>, pos: Vec3<f32>, max_dist: f32) -> bool {
+    let closest = Vec3::partial_max(aabb.min, Vec3::partial_min(aabb.max, pos));
+
+    closest.distance_squared(pos) <= max_dist * max_dist
+}
+
+// testes
+
+#[cfg(test)]
+fn test_frustum() -> Frustum {
+    // câmera na origem olhando para -z, com 90 graus de fov vertical
+    let view_mat = Mat4::<f32>::identity();
+    let proj_mat = Mat4::perspective_rh_no(std::f32::consts::PI / 2.0, 1.0, 0.1, 100.0);
+
+    Frustum::from_view_proj(proj_mat * view_mat)
+}
+
+#[cfg(test)]
+fn test_aabb(center: Vec3<f32>, half: f32) -> Aabb<f32> {
+    Aabb {
+        min: center - half,
+        max: center + half
+    }
+}
+
+#[test]
+fn frustum_points() {
+    let frustum = test_frustum();
+
+    assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -1.0)));
+    assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -99.0)));
+    assert!(frustum.contains_point(Vec3::new(9.0, -9.0, -10.0)));
+
+    // atrás da câmera, antes do plano próximo e depois do plano distante
+    assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 1.0)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -0.05)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -101.0)));
+
+    // fora dos lados, com 90 graus o limite fica em |x| = |z|
+    assert!(!frustum.contains_point(Vec3::new(11.0, 0.0, -10.0)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, -11.0, -10.0)));
+}
+
+#[test]
+fn frustum_aabbs() {
+    let frustum = test_frustum();
+
+    assert!(frustum.intersects_aabb(test_aabb(Vec3::new(0.0, 0.0, -20.0), 1.0)));
+
+    // caixas que cruzam um plano ou envolvem a câmera continuam visíveis
+    assert!(frustum.intersects_aabb(test_aabb(Vec3::new(21.0, 0.0, -20.0), 2.0)));
+    assert!(frustum.intersects_aabb(test_aabb(Vec3::zero(), 5.0)));
+    assert!(frustum.intersects_aabb(test_aabb(Vec3::new(0.0, 0.0, -100.0), 2.0)));
+
+    assert!(!frustum.intersects_aabb(test_aabb(Vec3::new(0.0, 0.0, 20.0), 1.0)));
+    assert!(!frustum.intersects_aabb(test_aabb(Vec3::new(30.0, 0.0, -20.0), 2.0)));
+    assert!(!frustum.intersects_aabb(test_aabb(Vec3::new(0.0, 0.0, -110.0), 2.0)));
+}
+
+#[test]
+fn frustum_follows_view() {
+    // a mesma câmera, deslocada e virada para +z
+    let view_mat = Mat4::<f32>::rotation_y(std::f32::consts::PI) * Mat4::translation_3d(-Vec3::new(5.0, 3.0, 0.0));
+    let proj_mat = Mat4::perspective_rh_no(std::f32::consts::PI / 2.0, 1.0, 0.1, 100.0);
+
+    let frustum = Frustum::from_view_proj(proj_mat * view_mat);
+
+    assert!(frustum.intersects_aabb(test_aabb(Vec3::new(5.0, 3.0, 20.0), 1.0)));
+    assert!(!frustum.intersects_aabb(test_aabb(Vec3::new(5.0, 3.0, -20.0), 1.0)));
+    assert!(!frustum.intersects_aabb(test_aabb(Vec3::new(-15.0, 3.0, 0.0), 1.0)));
+}
+
+#[test]
+fn distance_culling() {
+    let aabb = Aabb {
+        min: Vec3::new(10.0, 0.0, 0.0),
+        max: Vec3::new(20.0, 10.0, 10.0)
+    };
+
+    // a distância é medida até o ponto mais próximo da caixa, não até o centro
+    assert!(aabb_within_distance(aabb, Vec3::new(0.0, 5.0, 5.0), 10.0));
+    assert!(!aabb_within_distance(aabb, Vec3::new(0.0, 5.0, 5.0), 9.9));
+    assert!(aabb_within_distance(aabb, Vec3::new(15.0, 5.0, 5.0), 0.0));
+    assert!(!aabb_within_distance(aabb, Vec3::new(23.0, 14.0, 5.0), 4.9));
+}
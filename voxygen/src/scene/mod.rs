@@ -1,14 +1,14 @@
 pub mod camera;
 pub mod figure;
+pub mod frustum;
+pub mod sky;
 pub mod terrain;
 
 use vek::*;
-use dot_vox;
 
 use common::{
     comp,
-
-    figure::Segment
+    terrain::TerrainMap
 };
 
 use client::Client;
@@ -27,7 +27,6 @@ use crate::{
     },
 
     window::Event,
-    mesh::Meshable,
 
     anim::{
         Animation,
@@ -42,6 +41,7 @@ use crate::{
 use self::{
     camera::Camera,
     figure::Figure,
+    frustum::Frustum,
     terrain::Terrain
 };
 
@@ -63,14 +63,9 @@ pub struct Scene {
     test_figure: Figure<CharacterSkeleton>
 }
 
-// TODO: fazer um asset proper para carregar o sistema
-fn load_segment(filename: &'static str) -> Segment {
-    Segment::from(dot_vox::load(&(concat!(env!("CARGO_MANIFEST_DIR"), "/test_assets/").to_string() + filename)).unwrap())
-}
-
 impl Scene {
     /// criar um novo `scene` com parâmetros padrões
-    pub fn new(renderer: &mut Renderer, client: &Client) -> Self {
+    pub fn new(renderer: &mut Renderer, client: &Client, character: &comp::Character) -> Self {
         Self {
             globals: renderer
                 .create_consts(&[Globals::default()])
@@ -91,29 +86,8 @@ impl Scene {
             terrain: Terrain::new(),
 
             test_figure: Figure::new(
-                renderer, [
-                    Some(load_segment("head.vox").generate_mesh(Vec3::new(-7.0, -5.5, -1.0))),
-                    Some(load_segment("chest.vox").generate_mesh(Vec3::new(-6.0, -3.0, 0.0))),
-                    Some(load_segment("belt.vox").generate_mesh(Vec3::new(-5.0, -3.0, 0.0))),
-                    Some(load_segment("pants.vox").generate_mesh(Vec3::new(-5.0, -3.0, 0.0))),
-                    Some(load_segment("hand.vox").generate_mesh(Vec3::new(-2.0, -2.0, -1.0))),
-                    Some(load_segment("hand.vox").generate_mesh(Vec3::new(-2.0, -2.0, -1.0))),
-                    Some(load_segment("foot.vox").generate_mesh(Vec3::new(-2.5, -3.0, -2.0))),
-                    Some(load_segment("foot.vox").generate_mesh(Vec3::new(-2.5, -3.0, -2.0))),
-                    Some(load_segment("sword.vox").generate_mesh(Vec3::new(-6.5, -1.0, 0.0))),
-
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None,
-                    None
-                ],
+                renderer,
+                figure::character_bone_meshes(character),
 
                 CharacterSkeleton::new()
             )
@@ -181,6 +155,10 @@ impl Scene {
         
         // computar matrizes de câmera
         let (view_mat, proj_mat, cam_pos) = self.camera.compute_dependents();
+        let frustum = Frustum::from_view_proj(proj_mat * view_mat);
+
+        // distância máxima de renderização, a mesma em que o client mantém chunks carregados ao redor do jogador
+        let view_distance = client.view_distance() as f32 * TerrainMap::chunk_size().x as f32;
 
         // atualizar constantes globais
         renderer.update_consts(&mut self.globals, &[Globals::new(
@@ -190,15 +168,18 @@ impl Scene {
             
             self.camera.get_focus_pos(),
 
-            10.0,
+            view_distance,
             
             client.state().get_time_of_day(),
-            client.state().get_time()
+            client.state().get_time(),
+
+            sky::sky_light(client.state().get_time_of_day(), client.current_weather())
         )])
             .expect("falha ao atualizar constantes globais");
 
-        // manter dados gpu do terreno
-        self.terrain.maintain(renderer, client);
+        // manter dados gpu do terreno e escolher os chunks visíveis
+        self.terrain.maintain(renderer, client, cam_pos, self.camera.get_focus_pos(), &frustum);
+        self.terrain.cull(&frustum, self.camera.get_focus_pos(), view_distance);
 
         // TODO: não fazer isso aqui
         RunAnimation::update_skeleton(
@@ -0,0 +1,158 @@
+// padrão
+use std::f64::consts::PI;
+
+// biblioteca
+use vek::*;
+
+// projeto
+use common::weather::{
+    Weather,
+    WeatherKind
+};
+
+// caixote
+use crate::render::SkyLight;
+
+/// duração de um dia do jogo, em segundos do tempo do dia
+pub const DAY_LENGTH: f64 = 24.0 * 60.0 * 60.0;
+
+// inclinação da órbita do sol, para que ao meio-dia ele não fique exatamente acima e as sombras tenham direção
+const SUN_TILT: f32 = 0.4;
+
+const NOON_SUN: Rgb<f32> = Rgb { r: 0.65, g: 0.62, b: 0.55 };
+const DUSK_SUN: Rgb<f32> = Rgb { r: 0.6, g: 0.3, b: 0.1 };
+const MOON_LIGHT: Rgb<f32> = Rgb { r: 0.06, g: 0.08, b: 0.14 };
+
+const DAY_AMBIENT: Rgb<f32> = Rgb { r: 0.45, g: 0.47, b: 0.52 };
+const NIGHT_AMBIENT: Rgb<f32> = Rgb { r: 0.04, g: 0.05, b: 0.09 };
+
+const DAY_SKY_TOP: Rgb<f32> = Rgb { r: 0.05, g: 0.3, b: 0.9 };
+const DAY_SKY_BOTTOM: Rgb<f32> = Rgb { r: 0.5, g: 0.7, b: 1.0 };
+const NIGHT_SKY_TOP: Rgb<f32> = Rgb { r: 0.0, g: 0.0, b: 0.03 };
+const NIGHT_SKY_BOTTOM: Rgb<f32> = Rgb { r: 0.01, g: 0.02, b: 0.06 };
+const DUSK_HORIZON: Rgb<f32> = Rgb { r: 0.9, g: 0.4, b: 0.15 };
+
+// cor das nuvens de um céu encoberto ao meio-dia
+const OVERCAST_SKY: Rgb<f32> = Rgb { r: 0.55, g: 0.57, b: 0.6 };
+const FOG_SKY: Rgb<f32> = Rgb { r: 0.75, g: 0.75, b: 0.75 };
+
+/// direção do sol em um momento do dia: abaixo do horizonte à meia-noite e no alto ao meio-dia
+pub fn sun_dir(time_of_day: f64) -> Vec3<f32> {
+    let angle = (time_of_day.rem_euclid(DAY_LENGTH) / DAY_LENGTH * PI * 2.0) as f32;
+
+    Vec3::new(angle.sin(), SUN_TILT, -angle.cos()).normalized()
+}
+
+/// calcula a iluminação do sol, da lua e do céu para um momento do dia e o clima da região do jogador
+pub fn sky_light(time_of_day: f64, weather: Weather) -> SkyLight {
+    let sun_dir = sun_dir(time_of_day);
+    let moon_dir = -sun_dir;
+
+    // quanto do dia já clareou, começando um pouco antes do sol nascer
+    let daylight = smoothstep(-0.15, 0.25, sun_dir.z);
+
+    // o entardecer e o amanhecer tingem o horizonte e a luz do sol enquanto ele está perto do horizonte
+    let dusk = (1.0 - sun_dir.z.abs() / 0.3).max(0.0);
+
+    let sun_color = mix(DUSK_SUN, NOON_SUN, smoothstep(0.0, 0.5, sun_dir.z)) * smoothstep(-0.05, 0.1, sun_dir.z);
+    let moon_color = MOON_LIGHT * smoothstep(-0.05, 0.1, moon_dir.z) * (1.0 - daylight);
+
+    let sky_top = mix(NIGHT_SKY_TOP, DAY_SKY_TOP, daylight);
+    let sky_bottom = mix(mix(NIGHT_SKY_BOTTOM, DAY_SKY_BOTTOM, daylight), DUSK_HORIZON, dusk * 0.6);
+
+    // nuvens bloqueiam a luz direta e acinzentam o céu, escurecendo junto com o dia
+    let overcast = weather.overcast();
+
+    let cloud_color = match weather.kind {
+        WeatherKind::Fog => FOG_SKY,
+
+        _ => OVERCAST_SKY
+    } * mix(NIGHT_SKY_BOTTOM, Rgb::one(), daylight);
+
+    SkyLight {
+        sun_dir,
+        sun_color: sun_color * (1.0 - overcast * 0.8),
+
+        moon_dir,
+        moon_color: moon_color * (1.0 - overcast * 0.8),
+
+        ambient: mix(NIGHT_AMBIENT, DAY_AMBIENT, daylight) * (1.0 - overcast * 0.3),
+
+        sky_top: mix(sky_top, cloud_color, overcast),
+        sky_bottom: mix(sky_bottom, cloud_color, overcast)
+    }
+}
+
+fn mix(a: Rgb<f32>, b: Rgb<f32>, t: f32) -> Rgb<f32> {
+    a + (b - a) * t
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).max(0.0).min(1.0);
+
+    t * t * (3.0 - 2.0 * t)
+}
+
+// testes
+
+#[cfg(test)]
+fn brightness(color: Rgb<f32>) -> f32 {
+    color.r + color.g + color.b
+}
+
+#[test]
+fn noon_is_brighter_than_midnight() {
+    let noon = sky_light(DAY_LENGTH / 2.0, Weather::clear());
+    let midnight = sky_light(0.0, Weather::clear());
+
+    assert!(noon.sun_dir.z > 0.9);
+    assert!(midnight.sun_dir.z < -0.9);
+    assert!(midnight.moon_dir.z > 0.9);
+
+    assert!(brightness(noon.ambient) > brightness(midnight.ambient) * 4.0);
+    assert!(brightness(noon.sky_top) > brightness(midnight.sky_top) * 4.0);
+
+    // o sol não ilumina nada abaixo do horizonte, e a lua não aparece durante o dia
+    assert_eq!(brightness(midnight.sun_color), 0.0);
+    assert_eq!(brightness(noon.moon_color), 0.0);
+
+    assert!(brightness(midnight.moon_color) > 0.0);
+}
+
+#[test]
+fn sun_rises_and_sets() {
+    let morning = sun_dir(DAY_LENGTH * 0.3);
+    let evening = sun_dir(DAY_LENGTH * 0.7);
+
+    assert!(morning.z > 0.0 && evening.z > 0.0);
+
+    // o sol atravessa o céu de um lado para o outro
+    assert!(morning.x > 0.0);
+    assert!(evening.x < 0.0);
+}
+
+#[test]
+fn sky_repeats_every_day() {
+    for &time in &[0.0, 1000.0, DAY_LENGTH * 0.25, DAY_LENGTH * 0.8] {
+        let today = sky_light(time, Weather::clear());
+        let later = sky_light(time + DAY_LENGTH * 3.0, Weather::clear());
+
+        assert!(today.sun_dir.distance(later.sun_dir) < 1e-4);
+        assert!((brightness(today.ambient) - brightness(later.ambient)).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn clouds_block_the_sun() {
+    let clear = sky_light(DAY_LENGTH / 2.0, Weather::clear());
+    let rain = sky_light(DAY_LENGTH / 2.0, Weather::new(WeatherKind::Rain, 1.0));
+    let fog = sky_light(DAY_LENGTH / 2.0, Weather::new(WeatherKind::Fog, 0.5));
+
+    assert!(brightness(rain.sun_color) < brightness(clear.sun_color) * 0.5);
+    assert!(brightness(rain.ambient) < brightness(clear.ambient));
+    assert!(brightness(fog.sun_color) < brightness(clear.sun_color));
+    assert!(brightness(fog.sun_color) > brightness(rain.sun_color));
+
+    // a direção dos astros não depende do clima
+    assert_eq!(rain.sun_dir, clear.sun_dir);
+}
@@ -1,11 +1,21 @@
 // padrão
 use std::{
+    cmp::Reverse,
     collections::{
-        HashMap,
-        LinkedList
+        BinaryHeap,
+        HashMap
+    },
+
+    sync::{
+        mpsc,
+        Arc,
+
+        atomic::{
+            AtomicBool,
+            Ordering
+        }
     },
 
-    sync::mpsc,
     time::Duration
 };
 
@@ -16,10 +26,11 @@ use vek::*;
 use client::Client;
 
 use common::{
-    terrain::TerrainMap,
-
-    volumes::vol_map::VolMapErr,
-    vol::SampleVol
+    terrain::{
+        TerrainMap,
+        TerrainNeighbourhood,
+        LightNeighbourhood
+    }
 };
 
 // caixote
@@ -35,38 +46,178 @@ use crate::{
         TerrainLocals
     },
 
-    mesh::Meshable
+    mesh::{
+        terrain::{
+            TerrainMesher,
+
+            lit_terrain_mesh
+        },
+
+        lod::{
+            Lod,
+
+            lod_mesh
+        }
+    },
+
+    scene::frustum::{
+        Frustum,
+
+        aabb_within_distance
+    }
 };
 
+// máximo de chunks sendo meshados ao mesmo tempo, para que o threadpool continue livre para outros trabalhos
+const MAX_ACTIVE_MESH_JOBS: usize = 4;
+
+// distâncias, em chunks a partir do foco da câmera, em que cada nível de detalhe começa a ser utilizado
+const LOD_DISTANCES: [f32; Lod::MAX_LEVEL as usize] = [3.0, 6.0, 10.0];
+
+// quanto o jogador precisa passar do limite entre dois níveis para que o chunk troque de nível, em chunks
+const LOD_HYSTERESIS: f32 = 0.5;
+
 struct TerrainChunk {
     // dados de gpu
     model: Model<TerrainPipeline>,
-    locals: Consts<TerrainLocals>
+    locals: Consts<TerrainLocals>,
+
+    lod: Lod
+}
+
+/// um trabalhador meshando uma versão do chunk
+struct MeshJob {
+    // identifica a resposta deste trabalhador, já que um trabalho cancelado ainda responde
+    id: u64,
+
+    started_tick: u64,
+    lod: Lod,
+
+    // avisa o trabalhador que o resultado não é mais desejado
+    cancel: Arc<AtomicBool>
 }
 
 struct ChunkMetaState {
-    pos: Vec3<i32>,
+    // tick da última alteração do chunk e nível de detalhe desejado, o mesh desejado é o dessa versão
     started_tick: u64,
-    active_worker: bool
+    lod: Lod,
+
+    active_job: Option<MeshJob>
+}
+
+impl ChunkMetaState {
+    /// cancela o trabalhador do chunk, liberando a vaga dele imediatamente
+    ///
+    /// o trabalhador ainda termina o mesh que já começou, mas a resposta dele é descartada
+    fn cancel_job(&mut self) {
+        if let Some(job) = self.active_job.take() {
+            job.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// verifica se a resposta é do trabalhador atual do chunk, e não de um trabalho cancelado
+    fn is_current(&self, response: &MeshWorkerResponse) -> bool {
+        self.active_job.as_ref().map(|job| job.id == response.job_id).unwrap_or(false)
+    }
 }
 
 /// um tipo produzido por um trabalhador de threads mesh correspondente à posição e mesh de um chunk
 struct MeshWorkerResponse {
     pos: Vec3<i32>,
-    mesh: Mesh<TerrainPipeline>,
-    started_tick: u64
+    job_id: u64,
+
+    // `None` caso o trabalho tenha sido cancelado
+    mesh: Option<Mesh<TerrainPipeline>>,
+    lod: Lod
 }
 
 /// função executada por trabalhador de threads dedicado ao meshing de chunk
 fn mesh_worker(
     pos: Vec3<i32>,
-    started_tick: u64,
-    volume: <TerrainMap as SampleVol>::Sample
+    job_id: u64,
+    volume: TerrainNeighbourhood,
+    light: LightNeighbourhood,
+    lod: Lod,
+    mesher: TerrainMesher,
+    cancel: Arc<AtomicBool>
 ) -> MeshWorkerResponse {
+    let mesh = if cancel.load(Ordering::Relaxed) {
+        None
+    } else if lod == Lod::full() {
+        Some(lit_terrain_mesh(&volume, &light, mesher))
+    } else {
+        // chunks distantes são desenhados como se estivessem a céu aberto, já que cavernas não aparecem de longe
+        Some(lod_mesh(&volume, lod, mesher))
+    };
+
     MeshWorkerResponse {
         pos,
-        mesh: volume.generate_mesh(()),
-        started_tick
+        job_id,
+
+        // um trabalho cancelado durante o meshing não precisa mais do resultado
+        mesh: mesh.filter(|_| !cancel.load(Ordering::Relaxed)),
+        lod
+    }
+}
+
+/// número de chunks com um trabalhador que ainda não foi cancelado
+fn active_jobs(mesh_todo: &HashMap<Vec3<i32>, ChunkMetaState>) -> usize {
+    mesh_todo
+        .values()
+        .filter(|todo| todo.active_job.is_some())
+        .count()
+}
+
+/// chunks esperando por um trabalhador, na ordem em que devem ser meshados: primeiro os que passam por `visible` e
+/// então pela distância até a câmera em `cam_pos`
+fn mesh_queue<F>(
+    mesh_todo: &HashMap<Vec3<i32>, ChunkMetaState>,
+    cam_pos: Vec3<f32>,
+    visible: F
+) -> BinaryHeap<(bool, Reverse<u64>, [i32; 3])>
+where
+    F: Fn(Aabb<f32>) -> bool
+{
+    mesh_todo
+        .iter()
+        .filter(|(_, todo)| todo.active_job.is_none())
+        .map(|(pos, _)| {
+            let aabb = chunk_aabb(*pos);
+            let dist_sq = aabb.center().distance_squared(cam_pos) as u64;
+
+            (visible(aabb), Reverse(dist_sq), pos.into_array())
+        })
+        .collect()
+}
+
+/// caixa ocupada pelo chunk em coordenadas do mundo
+fn chunk_aabb(pos: Vec3<i32>) -> Aabb<f32> {
+    let size = TerrainMap::chunk_size().map(|e| e as f32);
+    let min = pos.map(|e| e as f32) * size;
+
+    Aabb {
+        min,
+        max: min + size
+    }
+}
+
+/// distância, em chunks, do foco da câmera até o ponto mais próximo do chunk
+fn chunk_distance(pos: Vec3<i32>, focus_pos: Vec3<f32>) -> f32 {
+    let aabb = chunk_aabb(pos);
+    let closest = Vec3::partial_max(aabb.min, Vec3::partial_min(aabb.max, focus_pos));
+
+    closest.distance(focus_pos) / TerrainMap::chunk_size().x as f32
+}
+
+/// nível de detalhe de um chunk à distância fornecida, mantendo o nível `current` perto dos limites entre níveis
+fn chunk_lod(dist: f32, current: Option<Lod>) -> Lod {
+    let level_at = |dist: f32| LOD_DISTANCES.iter().filter(|lod_dist| dist >= **lod_dist).count() as u8;
+
+    match current {
+        Some(current) if
+            level_at(dist - LOD_HYSTERESIS) <= current.level() &&
+            current.level() <= level_at(dist + LOD_HYSTERESIS) => current,
+
+        _ => Lod::new(level_at(dist))
     }
 }
 
@@ -77,7 +228,16 @@ pub struct Terrain {
     // manter o componente sender
     mesh_send_tmp: mpsc::Sender<MeshWorkerResponse>,
     mesh_recv: mpsc::Receiver<MeshWorkerResponse>,
-    mesh_todo: LinkedList<ChunkMetaState>
+    mesh_todo: HashMap<Vec3<i32>, ChunkMetaState>,
+
+    // identificador do próximo trabalhador
+    next_job_id: u64,
+
+    // chunks com modelo que passaram pelo culling do último frame
+    visible_chunks: Vec<Vec3<i32>>,
+
+    // algoritmo utilizado pelos trabalhadores de meshing
+    mesher: TerrainMesher
 }
 
 impl Terrain {
@@ -90,100 +250,202 @@ impl Terrain {
 
             mesh_send_tmp: send,
             mesh_recv: recv,
-            mesh_todo: LinkedList::new()
+            mesh_todo: HashMap::new(),
+
+            next_job_id: 0,
+            visible_chunks: Vec::new(),
+
+            mesher: TerrainMesher::default()
         }
     }
 
-    pub fn maintain(&mut self, renderer: &mut Renderer, client: &Client) {
+    /// altera o algoritmo de meshing utilizado pelos próximos chunks meshados
+    pub fn set_mesher(&mut self, mesher: TerrainMesher) {
+        self.mesher = mesher;
+    }
+
+    /// atualiza a fila de meshing e posta os meshes prontos na gpu
+    ///
+    /// chunks dentro do `frustum` da câmera em `cam_pos` são meshados primeiro, do mais próximo para o mais distante.
+    /// o nível de detalhe de cada chunk depende da distância até `focus_pos`
+    pub fn maintain(
+        &mut self,
+        renderer: &mut Renderer,
+        client: &Client,
+
+        cam_pos: Vec3<f32>,
+        focus_pos: Vec3<f32>,
+        frustum: &Frustum
+    ) {
         let current_tick = client.get_tick();
 
         // adicionar qualquer criação recente ou chunks alterados para a lista de chunks a serem meshados
         for pos in client.state().changes().new_chunks.iter()
             .chain(client.state().changes().changed_chunks.iter())
         {
-            // todo: outro problema aqui
-            // o que acontece se o bloco no topo de um chunk é modificado?
-            // é preciso spawnar um trabalhador mesh para corrigir essa vizinhança
-            match self.mesh_todo.iter_mut().find(|todo| todo.pos == *pos) {
-                Some(todo) => todo.started_tick = current_tick,
-
-                // o chunk está armazenado, adicionar na lista
-                None => self.mesh_todo.push_back(ChunkMetaState {
-                    pos: *pos,
-                    started_tick: current_tick,
-                    active_worker: false
-                })
+            let lod = chunk_lod(chunk_distance(*pos, focus_pos), self.chunks.get(pos).map(|chunk| chunk.lod));
+
+            let todo = self.mesh_todo.entry(*pos).or_insert(ChunkMetaState {
+                started_tick: current_tick,
+                lod,
+
+                active_job: None
+            });
+
+            // um mesh de uma versão anterior do chunk não é mais útil
+            if todo.active_job.as_ref().map(|job| job.started_tick != current_tick).unwrap_or(false) {
+                todo.cancel_job();
             }
+
+            todo.started_tick = current_tick;
         }
 
         // remover qualquer modelos dos chunks que foram removidos recentemente
         for pos in &client.state().changes().removed_chunks {
             self.chunks.remove(pos);
 
-            self.mesh_todo.drain_filter(|todo| todo.pos == *pos);
+            if let Some(mut todo) = self.mesh_todo.remove(pos) {
+                todo.cancel_job();
+            }
         }
 
-        // clonar o sender para a que a thread consiga enviar para o dado de chunk de volta
-        // todo: corrigir isso
-        let send = self.mesh_send_tmp.clone();
-
-        self.mesh_todo
-            .iter_mut()
-            // apenas spawnar trabalhadores para jobs meshing sem o trabalhador ativo atual
-            .filter(|todo| !todo.active_worker)
-            .for_each(|todo| {
-                // encontrar a área desejada do terreno
-                let aabb = Aabb {
-                    min: todo.pos.map2(TerrainMap::chunk_size(), |e, sz| e * sz as i32 - 1),
-                    max: todo.pos.map2(TerrainMap::chunk_size(), |e, sz| (e + 1) * sz as i32 + 1)
-                };
-
-                // copiar o dado de chunk desejado para performar o meshing
-                // fazer isso para obter um sample do terreno incluindo todos os chunks desejados
-                let volume = match client.state().terrain().sample(aabb) {
-                    Ok(sample) => sample,
-
-                    // caso esse chunk não exista ainda, manter na lista de todo para ser processado mais tarde
-                    Err(VolMapErr::NoSuchChunk) => return,
-
-                    _ => panic!("caso edge não tratado")
-                };
-
-                // clonar variadas coisas para assim poder movê-las para a thread
-                let send = send.clone();
-                let pos = todo.pos;
-
-                // listar a thread trabalhadora
-                client.thread_pool().execute(move || {
-                    send.send(mesh_worker(pos, current_tick, volume))
-                        .expect("falha ao enviar mesh de chunk para a thread principal");
+        // trocar o nível de detalhe dos chunks que se afastaram ou se aproximaram do foco
+        for (pos, chunk) in &self.chunks {
+            let lod = chunk_lod(chunk_distance(*pos, focus_pos), Some(chunk.lod));
+
+            if lod != chunk.lod && !self.mesh_todo.contains_key(pos) {
+                self.mesh_todo.insert(*pos, ChunkMetaState {
+                    started_tick: current_tick,
+                    lod,
+
+                    active_job: None
                 });
-                
-                todo.active_worker = true;
+            }
+        }
+
+        for (pos, todo) in self.mesh_todo.iter_mut() {
+            let lod = chunk_lod(chunk_distance(*pos, focus_pos), Some(todo.lod));
+
+            if lod != todo.lod {
+                todo.lod = lod;
+
+                // o modelo antigo continua sendo desenhado até o mesh no novo nível ficar pronto
+                if todo.active_job.as_ref().map(|job| job.lod != lod).unwrap_or(false) {
+                    todo.cancel_job();
+                }
+            }
+        }
+
+        // trabalhadores cancelados não ocupam vagas, mesmo que ainda estejam terminando um mesh
+        let mut active_jobs = active_jobs(&self.mesh_todo);
+        let mut queue = mesh_queue(&self.mesh_todo, cam_pos, |aabb| frustum.intersects_aabb(aabb));
+
+        while active_jobs < MAX_ACTIVE_MESH_JOBS {
+            let pos = match queue.pop() {
+                Some((_, _, pos)) => Vec3::from(pos),
+
+                None => break
+            };
+
+            let (started_tick, lod) = match self.mesh_todo.get(&pos) {
+                Some(todo) => (todo.started_tick, todo.lod),
+
+                None => continue
+            };
+
+            // compartilhar o chunk e seus vizinhos com o trabalhador, sem copiar os voxels
+            let volume = match client.state().terrain().neighbourhood(pos) {
+                Ok(volume) => volume,
+
+                // caso algum vizinho não exista ainda ou não possa ser lido, manter na lista de todo para ser
+                // processado mais tarde
+                Err(_) => continue
+            };
+
+            // cada chunk de terreno possui um chunk de luz, inserido junto com ele
+            let light = match client.state().light().neighbourhood(pos) {
+                Ok(light) => light,
+
+                Err(_) => continue
+            };
+
+            // clonar variadas coisas para assim poder movê-las para a thread
+            let send = self.mesh_send_tmp.clone();
+            let mesher = self.mesher;
+            let cancel = Arc::new(AtomicBool::new(false));
+            let worker_cancel = cancel.clone();
+
+            let id = self.next_job_id;
+            self.next_job_id += 1;
+
+            // listar a thread trabalhadora
+            client.thread_pool().execute(move || {
+                // a cena pode ter sido descartada enquanto o chunk era meshado
+                let _ = send.send(mesh_worker(pos, id, volume, light, lod, mesher, worker_cancel));
             });
 
+            if let Some(todo) = self.mesh_todo.get_mut(&pos) {
+                todo.active_job = Some(MeshJob {
+                    id,
+
+                    started_tick,
+                    lod,
+
+                    cancel
+                });
+            }
+
+            active_jobs += 1;
+        }
+
         // recebe meshes de chunks por meio das threads trabalhadoras
         // postar elas para a gpu e assim armazená-las
         while let Ok(response) = self.mesh_recv.recv_timeout(Duration::new(0, 0)) {
-            match self.mesh_todo.iter().find(|todo| todo.pos == response.pos) {
+            let todo = match self.mesh_todo.get_mut(&response.pos) {
+                Some(todo) if todo.is_current(&response) => todo,
+
+                // o chunk foi alterado, mudou de nível ou foi descartado enquanto era meshado
+                _ => continue
+            };
+
+            match response.mesh {
                 // é o mesh desejado, inserir o mais novo modelo finalizado para o modelo de terreno
-                Some(todo) if response.started_tick == todo.started_tick => {
+                Some(mesh) => {
                     self.chunks.insert(response.pos, TerrainChunk {
-                        model: renderer.create_model(&response.mesh).expect("falha ao postar mesh de chunk para a gpu"),
+                        model: renderer.create_model(&mesh).expect("falha ao postar mesh de chunk para a gpu"),
                         
                         locals: renderer.create_consts(&[TerrainLocals {
                             model_offs: response.pos.map2(TerrainMap::chunk_size(), |e, sz| e as f32 * sz as f32).into_array(),
-                        }]).expect("falha ao postar os locals do chunk para a gpu")
+                        }]).expect("falha ao postar os locals do chunk para a gpu"),
+
+                        lod: response.lod
                     });
+
+                    self.mesh_todo.remove(&response.pos);
                 },
 
-                _ => continue
+                // o trabalhador não produziu um mesh, então o chunk volta para a fila
+                None => todo.active_job = None
+            }
+        }
+    }
+
+    /// escolhe os chunks desenhados no próximo frame: os que aparecem no `frustum` e estão a no máximo `max_dist` de
+    /// `focus_pos`
+    pub fn cull(&mut self, frustum: &Frustum, focus_pos: Vec3<f32>, max_dist: f32) {
+        self.visible_chunks.clear();
+
+        for pos in self.chunks.keys() {
+            let aabb = chunk_aabb(*pos);
+
+            if aabb_within_distance(aabb, focus_pos, max_dist) && frustum.intersects_aabb(aabb) {
+                self.visible_chunks.push(*pos);
             }
         }
     }
 
     pub fn render(&self, renderer: &mut Renderer, globals: &Consts<Globals>) {
-        for (_, chunk) in &self.chunks {
+        for chunk in self.visible_chunks.iter().filter_map(|pos| self.chunks.get(pos)) {
             renderer.render_terrain_chunk(
                 &chunk.model,
                 globals,
@@ -192,3 +454,87 @@ impl Terrain {
         }
     }
 }
+
+// testes
+
+#[cfg(test)]
+fn test_todo(job_id: Option<u64>) -> ChunkMetaState {
+    ChunkMetaState {
+        started_tick: 0,
+        lod: Lod::full(),
+
+        active_job: job_id.map(|id| MeshJob {
+            id,
+
+            started_tick: 0,
+            lod: Lod::full(),
+
+            cancel: Arc::new(AtomicBool::new(false))
+        })
+    }
+}
+
+#[test]
+fn mesh_queue_order() {
+    let mut mesh_todo = HashMap::new();
+
+    for &x in [-3, -1, 0, 2, 5].iter() {
+        mesh_todo.insert(Vec3::new(x, 0, 0), test_todo(None));
+    }
+
+    // chunks que já possuem um trabalhador não voltam para a fila
+    mesh_todo.insert(Vec3::new(1, 0, 0), test_todo(Some(0)));
+
+    let cam_pos = chunk_aabb(Vec3::zero()).center();
+
+    // apenas os chunks com x negativo estão na frente da câmera
+    let queue = mesh_queue(&mesh_todo, cam_pos, |aabb| aabb.max.x <= 0.0);
+
+    let order = queue
+        .into_sorted_vec()
+        .into_iter()
+        .rev()
+        .map(|(_, _, pos)| pos[0])
+        .collect::<Vec<_>>();
+
+    // os visíveis primeiro, cada grupo do mais próximo para o mais distante
+    assert_eq!(order, vec![-1, -3, 0, 2, 5]);
+}
+
+#[test]
+fn cancelled_jobs_free_their_slot() {
+    let mut mesh_todo = HashMap::new();
+
+    for i in 0..MAX_ACTIVE_MESH_JOBS as i32 {
+        mesh_todo.insert(Vec3::new(i, 0, 0), test_todo(Some(i as u64)));
+    }
+
+    assert_eq!(active_jobs(&mesh_todo), MAX_ACTIVE_MESH_JOBS);
+
+    let todo = mesh_todo.get_mut(&Vec3::zero()).unwrap();
+    let cancel = todo.active_job.as_ref().unwrap().cancel.clone();
+
+    todo.cancel_job();
+
+    // o trabalhador é avisado, e a vaga fica livre antes mesmo dele responder
+    assert!(cancel.load(Ordering::Relaxed));
+    assert_eq!(active_jobs(&mesh_todo), MAX_ACTIVE_MESH_JOBS - 1);
+
+    let response = |job_id| MeshWorkerResponse {
+        pos: Vec3::zero(),
+        job_id,
+
+        mesh: None,
+        lod: Lod::full()
+    };
+
+    // a resposta do trabalho cancelado é descartada, mesmo depois de um novo trabalhador começar
+    let todo = mesh_todo.get_mut(&Vec3::zero()).unwrap();
+
+    assert!(!todo.is_current(&response(0)));
+
+    todo.active_job = test_todo(Some(MAX_ACTIVE_MESH_JOBS as u64)).active_job;
+
+    assert!(!todo.is_current(&response(0)));
+    assert!(todo.is_current(&response(MAX_ACTIVE_MESH_JOBS as u64)));
+}
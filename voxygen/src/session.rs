@@ -5,7 +5,10 @@ use std::time::Duration;
 use vek::*;
 
 // projeto
-use common::clock::Clock;
+use common::{
+    clock::Clock,
+    comp
+};
 
 use client::{
     self,
@@ -34,7 +37,11 @@ use crate::{
     render::Renderer,
     scene::Scene,
 
-	menu::test_hud::TestHud
+	menu::{
+        describe_client_error,
+
+        test_hud::TestHud
+    }
 };
 
 const FPS: u64 = 60;
@@ -51,16 +58,19 @@ pub struct SessionState {
 /// representa uma atividade de sessão de jogo
 impl SessionState {
     /// cria um novo `sessionstate`
-    pub fn new(window: &mut Window) -> Result<Self, Error> {
-        let client = Client::new(([127, 0, 0, 1], 59003))?.with_test_state(); // <--- todo: remover isso
+    pub fn new(window: &mut Window, client: Client, character: comp::Character) -> Self {
+        let mut client = client.with_test_state(); // <--- todo: remover isso
+
+        // enviar o personagem escolhido para o servidor
+        client.request_character(character);
         
-        Ok(Self {
+        Self {
             // cria uma cena para esta sessão
-            scene: Scene::new(window.renderer_mut(), &client),
+            scene: Scene::new(window.renderer_mut(), &client, &character),
             client,
             key_state: KeyState::new(),
 			test_hud: TestHud::new(window)
-        })
+        }
     }
 }
 
@@ -81,7 +91,15 @@ impl SessionState {
         let dir_vec = self.key_state.dir_vec();
         let move_dir = unit_vecs.0 * dir_vec[0] + unit_vecs.1 * dir_vec[1];
 
-        self.client.tick(client::Input { move_dir }, dt)?;
+        for event in self.client.tick(client::Input { move_dir }, dt)? {
+            match event {
+                client::Event::Chat { channel, sender, msg } => self.test_hud.new_chat_message(
+                    &channel,
+                    sender.as_ref().map(|s| s.as_str()),
+                    &msg
+                )
+            }
+        }
 
         Ok(())
     }
@@ -117,22 +135,23 @@ impl PlayState for SessionState {
         // configurar clock de fps
         let mut clock = Clock::new();
 
-        // carregar novos chunks. todo: remover isso
-        for x in -6..7 {
-            for y in -6..7 {
-                for z in -1..2 {
-                    self.client.load_chunk(Vec3::new(x, y, z));
-                }
-            }
-        }
-
         // loop de jogo
         loop {
             // auxiliar eventos de janela
             for event in global_state.window.fetch_events() {
+                // enquanto o jogador digita no chat, as teclas vão apenas para a ui
+                let typing = self.test_hud.is_typing();
+
                 let _handled = match event {
                     Event::Close => return PlayStateResult::Shutdown,
 
+					// passar eventos para ui
+					Event::UiEvent(input) => {
+						self.test_hud.handle_event(input);
+					},
+
+                    Event::Char(_) | Event::KeyDown(_) if typing => {},
+
                     // quando 'q' for pressionado, deixar sessão
                     Event::Char('q') => return PlayStateResult::Pop,
 
@@ -156,11 +175,6 @@ impl PlayState for SessionState {
                     Event::KeyUp(Key::MoveLeft) => self.key_state.left = false,
                     Event::KeyUp(Key::MoveRight) => self.key_state.right = false,
 
-					// passar eventos para ui
-					Event::UiEvent(input) => {
-						self.test_hud.handle_event(input);
-					}
-
                     // passar todos os outros eventos para a cena
                     event => {
                         self.scene.handle_input_event(event);
@@ -171,14 +185,28 @@ impl PlayState for SessionState {
             }
 
             // performar um tick em jogo
-            self.tick(clock.get_last_delta())
-                .expect("falha ao tickar a cena");
+            match self.tick(clock.get_last_delta()) {
+                Ok(()) => {},
+
+                // voltar ao menu mostrando o motivo da desconexão
+                Err(Error::ClientError(err)) => {
+                    log::error!("conexão com o servidor perdida: {:?}", err);
+
+                    global_state.info_message = Some(format!("desconectado: {}", describe_client_error(&err)));
+
+                    return PlayStateResult::Pop;
+                },
+
+                Err(err) => panic!("falha ao tickar a cena: {:?}", err)
+            }
 
             // mantém a cena
             self.scene.maintain(global_state.window.renderer_mut(), &self.client);
 
-			// mantém a ui
-			self.test_hud.maintain(global_state.window.renderer_mut());
+			// mantém a ui e envia o que foi digitado no chat
+			if let Some(line) = self.test_hud.maintain(global_state.window.renderer_mut()) {
+				self.client.send_chat_input(&line);
+			}
 
             // renderiza a sessão
             self.render(global_state.window.renderer_mut());
@@ -152,6 +152,11 @@ impl Ui {
         self.ui.widget_input(id)
     }
 
+	/// o widget que está recebendo o input do teclado, como um campo de texto com foco
+	pub fn keyboard_capture(&self) -> Option<WidgId> {
+		self.ui.global_input().current.widget_capturing_keyboard
+	}
+
 	pub fn maintain(&mut self, renderer: &mut Renderer) {
 		let ref mut ui = self.ui;
 
@@ -344,7 +349,7 @@ impl Ui {
 
 			// entrar para o comando final
 			match current_img {
-                None => self.draw_commands.push(DrawCommand::Plain(renderer.create_model(&mesh).unwrap()))
+                None => self.draw_commands.push(DrawCommand::Plain(renderer.create_model(&mesh).unwrap())),
                 Some(image_id) => self.draw_commands.push(DrawCommand::Image(renderer.create_model(&mesh).unwrap(), image_id))
             }
 		}